@@ -0,0 +1,3510 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    coins, to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Deps, DepsMut, Env,
+    Event, IbcMsg, MessageInfo, QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg,
+    SubMsgResult, Uint128, WasmMsg,
+};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse, AllowanceResponse,
+    BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration, MinterResponse,
+};
+use semver::Version;
+
+use crate::curve;
+use crate::error::ContractError;
+use crate::ibc::{local_denom, Ics20Packet, DEFAULT_TIMEOUT_SECONDS};
+use crate::msg::{
+    AccountExport, AllFrozenAccountsResponse, AllowanceEntriesResponse, AllowanceEntry,
+    AllowanceKey, AllowlistResponse, Asset, AuditLogResponse, BalancesResponse,
+    BlacklistedResponse, BurnRateResponse, ChannelEscrow, ChannelInfoResponse,
+    CirculatingSupplyResponse, CurveInfoResponse, ExchangeRateResponse, ExecuteMsg,
+    ExportStateResponse, FlashMintFeeResponse, FlashMintReceiverMsg, FreezeInfoResponse,
+    FrozenAccount, FrozenAmountResponse, FrozenResponse, HandleMsg, HealthCheckResponse,
+    HolderCountResponse, HooksResponse, InflationInfoResponse, InitMsg, IsVerifiedResponse,
+    KycQueryMsg, KycRegistryResponse, LimitExemptResponse, LockedTransfersResponse, MigrateMsg,
+    MintWindowResponse, MinterEntry, MintersResponse, MultisigResponse, NonceResponse, PairAsset,
+    PairAssetInfo, PairExecuteMsg, PendingAdminActionsResponse, PendingMinterResponse,
+    PendingRewardsResponse, PreviewTransferResponse, QueryMsg, ReceiveFailureToleratedResponse,
+    RecentActivityResponse, RewardDenomsResponse, RolesResponse, SharesResponse, SignedPermit,
+    SignedTransfer, SpendLimitResponse, StakedBalanceResponse, StreamResponse, StreamsResponse,
+    SubscriptionsResponse, SudoMsg, TokenHookMsg, TopHoldersResponse, TotalSupplyResponse,
+    TransferQuotaResponse, TreasuryResponse, VestingInfoResponse, VotingPowerResponse,
+    WrappedDenomResponse,
+};
+use crate::signature;
+use crate::state::{
+    add_balance, bps_of, index_holders, index_spender_allowances, index_total_staked,
+    migrate_from_cw20_base, migrate_frozen_balances, migrate_minter_roles,
+    migrate_singleton_layout, AdminAction, Escrow, FeeConfig, FeeTier, FreezeRecord, HolderCap,
+    InflationRecipient, MultisigAction, MultisigConfig, MultisigProposal, RateLimit, ReadonlyState,
+    ReplyKind, Role, StakingConfig, State, Stream, Subscription, Swap, TokenInfo, TransferLimits,
+    TransferOutcome, DEFAULT_LIMIT, MAX_BALANCES_QUERY, MAX_LIMIT, MAX_MEMO_LENGTH, MINTER,
+    TOKEN_INFO, TOTAL_SUPPLY,
+};
+
+// version info for migration info
+const CONTRACT_NAME: &str = concat!("crates.io:", env!("CARGO_PKG_NAME"));
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Actor recorded for actions taken through `sudo`.
+const GOVERNANCE: &str = "governance";
+// The cw2 name stock cw20-base deployments record.
+const CW20_BASE_NAME: &str = "crates.io:cw20-base";
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: InitMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    msg.validate()?;
+    let mut state = State::new(deps.storage);
+    state.grant_role(Role::Admin, info.sender.as_str())?;
+    let mut total_supply = Uint128::zero();
+    for coin in &msg.initial_balances {
+        let address = deps.api.addr_validate(&coin.address)?;
+        let balance = add_balance(state.read().balance(address.as_str())?, coin.amount)?;
+        state.set_balance(address.as_str(), balance, env.block.height)?;
+        total_supply = add_balance(total_supply, coin.amount)?;
+    }
+    if let Some(mint) = &msg.mint {
+        if mint.cap.map_or(false, |cap| total_supply > cap) {
+            return Err(ContractError::CapExceeded {});
+        }
+        let minter = deps.api.addr_validate(&mint.minter)?;
+        MINTER.save(
+            deps.storage,
+            &MinterResponse {
+                minter: minter.to_string(),
+                cap: mint.cap,
+            },
+        )?;
+        let mut state = State::new(deps.storage);
+        state.grant_role(Role::Minter, minter.as_str())?;
+        state.grant_role(Role::Freezer, minter.as_str())?;
+        if let Some(cap) = mint.cap {
+            state.update_cap(cap)?;
+        }
+    }
+    if let Some(delay) = msg.timelock_delay {
+        State::new(deps.storage).update_timelock_delay(delay)?;
+    }
+    if let Some(deadline) = msg.mint_deadline {
+        if deadline.is_expired(&env.block) {
+            return Err(ContractError::MintWindowClosed { deadline });
+        }
+        State::new(deps.storage).set_mint_deadline(deadline)?;
+    }
+    if let Some(mut inflation) = msg.inflation {
+        if let InflationRecipient::Address { address } = &mut inflation.recipient {
+            *address = deps.api.addr_validate(address)?.to_string();
+        }
+        State::new(deps.storage).set_inflation(inflation, env.block.time.seconds())?;
+    }
+    if let Some(marketing) = msg.marketing {
+        let marketing_admin = marketing
+            .marketing
+            .map(|address| deps.api.addr_validate(&address))
+            .transpose()?;
+        let mut state = State::new(deps.storage);
+        state.update_marketing(
+            marketing.project,
+            marketing.description,
+            Some(marketing_admin),
+        )?;
+        if let Some(logo) = marketing.logo {
+            state.upload_logo(logo)?;
+        }
+    }
+    if let Some(fee) = msg.fee {
+        let fee = validate_fee_config(
+            deps.api,
+            fee.fee_bps,
+            &fee.treasury,
+            &fee.exempt,
+            &fee.tiers,
+        )?;
+        State::new(deps.storage).update_fee_config(fee)?;
+    }
+    if let Some(curve) = msg.bonding_curve {
+        State::new(deps.storage).set_bonding_curve(curve)?;
+    }
+    if let Some(rate) = msg.exchange_rate {
+        State::new(deps.storage).update_exchange_rate(rate)?;
+    }
+    if let Some(denom) = &msg.wrapped_denom {
+        State::new(deps.storage).set_wrapped_denom(denom)?;
+    }
+    if msg.permissioned {
+        let mut state = State::new(deps.storage);
+        state.set_permissioned(true)?;
+        state.grant_role(Role::Compliance, info.sender.as_str())?;
+    }
+    TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
+    TOKEN_INFO.save(
+        deps.storage,
+        &TokenInfo {
+            name: msg.name,
+            symbol: msg.symbol,
+            decimals: msg.decimals,
+        },
+    )?;
+    Ok(Response::default())
+}
+
+/// Executes `msg` if it is a `HandleMsg`. Anything else fails with
+/// `UnknownMessage`, or is accepted as a no-op once unknown messages are
+/// ignored.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Handle(msg) => handle(deps, env, info, msg),
+        ExecuteMsg::Unknown(_) if ReadonlyState::new(deps.storage).ignore_unknown_messages()? => {
+            Ok(Response::new().add_attribute("action", "ignore_unknown_message"))
+        }
+        ExecuteMsg::Unknown(_) => Err(ContractError::UnknownMessage {}),
+    }
+}
+
+pub fn handle(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: HandleMsg,
+) -> Result<Response, ContractError> {
+    let actor = info.sender.clone();
+    let mut state = State::new(deps.storage);
+    if state.read().migration_in_progress()? {
+        return Err(ContractError::MigrationInProgress {});
+    }
+    if let Some(registry) = state.read().kyc_registry()? {
+        for address in receiving_addresses(&msg, &state.read())? {
+            ensure_verified(&deps.querier, &registry, &address)?;
+        }
+    }
+    let watched = state.read().watched_balances()?;
+    let response = match &msg {
+        HandleMsg::Transfer {
+            recipient,
+            amount,
+            memo,
+        } => {
+            let sender_address = info.sender.clone();
+            let recipient_address = deps.api.addr_validate(recipient)?;
+            let outcome = state.transfer(
+                &sender_address.to_string(),
+                &recipient_address.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let mut response =
+                charge_attributes(Response::new(), sender_address.as_str(), &outcome);
+            let threshold = state.read().whale_threshold()?;
+            if !threshold.is_zero() && *amount >= threshold {
+                response = response.add_attribute("whale_alert", amount.to_string());
+            }
+            let response = response
+                .add_event(movement_event(
+                    "transfer",
+                    Some(sender_address.as_str()),
+                    Some(recipient_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "transfer")
+                .add_attribute("from", sender_address)
+                .add_attribute("to", recipient_address)
+                .add_attribute("amount", amount.to_string());
+            with_memo(response, memo)
+        }
+        HandleMsg::TransferBySignature {
+            owner,
+            recipient,
+            amount,
+            nonce,
+            expiration,
+            public_key,
+            signature,
+        } => {
+            let owner = deps.api.addr_validate(owner)?;
+            let recipient = deps.api.addr_validate(recipient)?;
+            if expiration.is_expired(&env.block) {
+                return Err(ContractError::SignatureExpired {});
+            }
+            let payload = SignedTransfer {
+                chain_id: env.block.chain_id.clone(),
+                contract: env.contract.address.to_string(),
+                owner: owner.to_string(),
+                recipient: recipient.to_string(),
+                amount: *amount,
+                nonce: *nonce,
+                expiration: *expiration,
+            };
+            signature::verify(deps.api, &owner, &payload, public_key, signature)?;
+            state.use_nonce(owner.as_str(), *nonce)?;
+            let outcome =
+                state.transfer(owner.as_str(), recipient.as_str(), *amount, &env.block)?;
+            let response = charge_attributes(Response::new(), owner.as_str(), &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "transfer",
+                    Some(owner.as_str()),
+                    Some(recipient.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "transfer_by_signature")
+                .add_attribute("from", owner)
+                .add_attribute("to", recipient)
+                .add_attribute("by", info.sender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("nonce", nonce.to_string()))
+        }
+        HandleMsg::Permit {
+            owner,
+            spender,
+            amount,
+            expiration,
+            nonce,
+            public_key,
+            signature,
+        } => {
+            let owner = deps.api.addr_validate(owner)?;
+            let spender = deps.api.addr_validate(spender)?;
+            if expiration.is_expired(&env.block) {
+                return Err(ContractError::SignatureExpired {});
+            }
+            let payload = SignedPermit {
+                chain_id: env.block.chain_id.clone(),
+                contract: env.contract.address.to_string(),
+                owner: owner.to_string(),
+                spender: spender.to_string(),
+                amount: *amount,
+                nonce: *nonce,
+                expiration: *expiration,
+            };
+            signature::verify(deps.api, &owner, &payload, public_key, signature)?;
+            state.use_nonce(owner.as_str(), *nonce)?;
+            state.set_allowance(
+                owner.as_str(),
+                spender.as_str(),
+                *amount,
+                *expiration,
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "permit")
+                .add_attribute("owner", owner)
+                .add_attribute("spender", spender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("nonce", nonce.to_string()))
+        }
+        HandleMsg::TransferBatch { transfers } => {
+            let transfers = transfers
+                .iter()
+                .map(|entry| {
+                    Ok((
+                        deps.api.addr_validate(&entry.recipient)?.to_string(),
+                        entry.amount,
+                    ))
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            let outcome = state.transfer_batch(info.sender.as_str(), &transfers, &env.block)?;
+            let total = outcome.sent;
+            let response = charge_attributes(Response::new(), info.sender.as_str(), &outcome);
+            Ok(response
+                .add_event(
+                    movement_event("transfer_batch", Some(info.sender.as_str()), None, total)
+                        .add_attribute("recipients", transfers.len().to_string()),
+                )
+                .add_attribute("action", "transfer_batch")
+                .add_attribute("from", info.sender)
+                .add_attribute("recipients", transfers.len().to_string())
+                .add_attribute("amount", total.to_string()))
+        }
+        HandleMsg::Delegate { delegate } => {
+            let delegate = deps.api.addr_validate(delegate)?;
+            state.delegate(info.sender.as_str(), delegate.as_str(), env.block.height)?;
+            Ok(Response::new()
+                .add_attribute("action", "delegate")
+                .add_attribute("delegator", info.sender)
+                .add_attribute("delegate", delegate))
+        }
+        HandleMsg::Mint { recipient, amount } => {
+            if !state.read().has_role(Role::Minter, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let recipient_address = match recipient {
+                Some(recipient) => deps.api.addr_validate(recipient)?.to_string(),
+                None => state
+                    .read()
+                    .default_mint_recipient()?
+                    .ok_or(ContractError::NoMintRecipient {})?,
+            };
+            state.consume_mint_allowance(info.sender.as_str(), *amount, &env.block)?;
+            state.mint(&recipient_address, *amount, env.block.height)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "mint",
+                    None,
+                    Some(&recipient_address),
+                    *amount,
+                ))
+                .add_attribute("action", "mint")
+                .add_attribute("to", recipient_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::AddMinter { address, allowance } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.set_minter_allowance(address.as_str(), *allowance)?;
+            Ok(Response::new()
+                .add_attribute("action", "add_minter")
+                .add_attribute("minter", address)
+                .add_attribute("allowance", allowance_attribute(allowance)))
+        }
+        HandleMsg::RemoveMinter { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.revoke_role(Role::Minter, address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_minter")
+                .add_attribute("minter", address))
+        }
+        HandleMsg::SetMinterAllowance { address, allowance } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            if !state.read().has_role(Role::Minter, address.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.set_minter_allowance(address.as_str(), *allowance)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_minter_allowance")
+                .add_attribute("minter", address)
+                .add_attribute("allowance", allowance_attribute(allowance)))
+        }
+        HandleMsg::SetMintDailyQuota { address, quota } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            if !state.read().has_role(Role::Minter, address.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.set_mint_daily_quota(address.as_str(), *quota)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_mint_daily_quota")
+                .add_attribute("minter", address)
+                .add_attribute("quota", allowance_attribute(quota)))
+        }
+        HandleMsg::ScheduleAdminAction { action } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let action = validate_admin_action(deps.api, action)?;
+            let scheduled = state.schedule_admin_action(action, env.block.time.seconds())?;
+            Ok(Response::new()
+                .add_attribute("action", "schedule_admin_action")
+                .add_attribute("id", scheduled.id.to_string())
+                .add_attribute("executable_at", scheduled.executable_at.to_string()))
+        }
+        HandleMsg::ExecuteAdminAction { id } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let action = state.take_admin_action(*id, env.block.time.seconds())?;
+            let response = Response::new()
+                .add_attribute("action", "execute_admin_action")
+                .add_attribute("id", id.to_string());
+            apply_admin_action(&mut state, action, response)
+        }
+        HandleMsg::AddHook {
+            addr,
+            tolerate_failure,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let addr = deps.api.addr_validate(addr)?;
+            state.add_hook(addr.as_str(), *tolerate_failure)?;
+            Ok(Response::new()
+                .add_attribute("action", "add_hook")
+                .add_attribute("hook", addr)
+                .add_attribute("tolerate_failure", tolerate_failure.to_string()))
+        }
+        HandleMsg::RemoveHook { addr } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let addr = deps.api.addr_validate(addr)?;
+            state.remove_hook(addr.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_hook")
+                .add_attribute("hook", addr))
+        }
+        HandleMsg::SetReceiveFailureTolerance { contract, tolerate } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let contract = deps.api.addr_validate(contract)?;
+            state.set_receive_failure_tolerance(contract.as_str(), *tolerate)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_receive_failure_tolerance")
+                .add_attribute("contract", contract)
+                .add_attribute("tolerate", tolerate.to_string()))
+        }
+        HandleMsg::UpdateMultisig { multisig } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let multisig = multisig
+                .as_ref()
+                .map(|config| -> StdResult<_> {
+                    Ok(MultisigConfig {
+                        signers: config
+                            .signers
+                            .iter()
+                            .map(|signer| deps.api.addr_validate(signer).map(|a| a.to_string()))
+                            .collect::<StdResult<_>>()?,
+                        threshold: config.threshold,
+                    })
+                })
+                .transpose()?;
+            let threshold = multisig.as_ref().map_or(0, |config| config.threshold);
+            state.update_multisig(multisig)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_multisig")
+                .add_attribute("threshold", threshold.to_string()))
+        }
+        HandleMsg::ProposeAction { action } => {
+            let action = match action {
+                MultisigAction::Mint { recipient, amount } => MultisigAction::Mint {
+                    recipient: deps.api.addr_validate(recipient)?.to_string(),
+                    amount: *amount,
+                },
+            };
+            let (proposal, ready) = state.propose_multisig_action(info.sender.as_str(), action)?;
+            multisig_response(&mut state, "propose_action", proposal, ready, &env.block)
+        }
+        HandleMsg::ApproveAction { id } => {
+            let (proposal, ready) = state.approve_multisig_action(*id, info.sender.as_str())?;
+            multisig_response(&mut state, "approve_action", proposal, ready, &env.block)
+        }
+        HandleMsg::CancelAdminAction { id } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.cancel_admin_action(*id)?;
+            Ok(Response::new()
+                .add_attribute("action", "cancel_admin_action")
+                .add_attribute("id", id.to_string()))
+        }
+        HandleMsg::RenounceMinter {} => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.renounce_minter()?;
+            Ok(Response::new()
+                .add_attribute("action", "renounce_minter")
+                .add_attribute("total_supply", state.read().total_supply()?.to_string()))
+        }
+        HandleMsg::ProposeNewMinter { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.propose_minter(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "propose_new_minter")
+                .add_attribute("pending_minter", address))
+        }
+        HandleMsg::AcceptMinter {} => {
+            state.accept_minter(info.sender.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "accept_minter")
+                .add_attribute("minter", info.sender))
+        }
+        HandleMsg::Freeze {
+            address,
+            expires,
+            reason,
+        } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            let expires = expires.unwrap_or_default();
+            let record = freeze_record(&info, &env, reason);
+            if !state.freeze(&address.to_string(), expires, &record, &env.block)? {
+                return Ok(Response::new()
+                    .add_attribute("action", "freeze")
+                    .add_attribute("already_frozen", address));
+            }
+            Ok(Response::new()
+                .add_event(
+                    Event::new("freeze")
+                        .add_attribute("address", address.as_str())
+                        .add_attribute("expires", expires.to_string()),
+                )
+                .add_attribute("action", "freeze")
+                .add_attribute("address", address)
+                .add_attribute("expires", expires.to_string()))
+        }
+        HandleMsg::FreezeAmount {
+            address,
+            amount,
+            reason,
+        } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            let record = freeze_record(&info, &env, reason);
+            state.freeze_amount(address.as_str(), *amount, &record, &env.block)?;
+            Ok(Response::new()
+                .add_event(
+                    Event::new("freeze")
+                        .add_attribute("address", address.as_str())
+                        .add_attribute("amount", amount.to_string()),
+                )
+                .add_attribute("action", "freeze_amount")
+                .add_attribute("address", address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Unfreeze { address } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.unfreeze(&address.to_string())?;
+            Ok(Response::new()
+                .add_event(Event::new("unfreeze").add_attribute("address", address.as_str()))
+                .add_attribute("action", "unfreeze")
+                .add_attribute("address", address))
+        }
+        HandleMsg::UnfreezeByReason {
+            reason,
+            start_after,
+            limit,
+        } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let (unfrozen, last) =
+                state.unfreeze_by_reason(reason, start_after.as_deref(), *limit)?;
+            let mut response = Response::new()
+                .add_attribute("action", "unfreeze_by_reason")
+                .add_attribute("reason", reason)
+                .add_attribute("unfrozen", unfrozen.len().to_string())
+                .add_attribute("last_address", last.unwrap_or_default());
+            for address in unfrozen {
+                response =
+                    response.add_event(Event::new("unfreeze").add_attribute("address", address));
+            }
+            Ok(response)
+        }
+        HandleMsg::UpdateTransferLimits {
+            max_transfer_amount,
+            max_wallet_balance,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_transfer_limits(TransferLimits {
+                max_transfer_amount: *max_transfer_amount,
+                max_wallet_balance: *max_wallet_balance,
+            })?;
+            Ok(Response::new().add_attribute("action", "update_transfer_limits"))
+        }
+        HandleMsg::UpdateRateLimit { rate_limit } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_rate_limit(rate_limit.clone())?;
+            Ok(Response::new().add_attribute("action", "update_rate_limit"))
+        }
+        HandleMsg::AddLimitExemption { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.add_limit_exemption(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "add_limit_exemption")
+                .add_attribute("address", address))
+        }
+        HandleMsg::RemoveLimitExemption { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.remove_limit_exemption(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_limit_exemption")
+                .add_attribute("address", address))
+        }
+        HandleMsg::UpdateKycRegistry { registry } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let registry = registry
+                .as_ref()
+                .map(|registry| deps.api.addr_validate(registry))
+                .transpose()?;
+            state.update_kyc_registry(registry.as_ref().map(|registry| registry.to_string()))?;
+            Ok(Response::new()
+                .add_attribute("action", "update_kyc_registry")
+                .add_attribute(
+                    "registry",
+                    registry.map_or_else(|| "none".to_string(), |registry| registry.to_string()),
+                ))
+        }
+        HandleMsg::AddToAllowlist { address } => {
+            if !state
+                .read()
+                .has_role(Role::Compliance, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.add_to_allowlist(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "add_to_allowlist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::RemoveFromAllowlist { address } => {
+            if !state
+                .read()
+                .has_role(Role::Compliance, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.remove_from_allowlist(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_from_allowlist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::Blacklist { address } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.blacklist(address.as_str())?;
+            Ok(Response::new()
+                .add_event(Event::new("blacklist").add_attribute("address", address.as_str()))
+                .add_attribute("action", "blacklist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::Unblacklist { address } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.unblacklist(address.as_str())?;
+            Ok(Response::new()
+                .add_event(Event::new("unblacklist").add_attribute("address", address.as_str()))
+                .add_attribute("action", "unblacklist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::Clawback {
+            address,
+            recipient,
+            amount,
+            reason,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            if reason.trim().is_empty() {
+                return Err(ContractError::MissingReason {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            let recipient = deps.api.addr_validate(recipient)?;
+            state.clawback(address.as_str(), recipient.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "clawback",
+                    Some(address.as_str()),
+                    Some(recipient.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "clawback")
+                .add_attribute("from", address)
+                .add_attribute("to", recipient)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("reason", reason))
+        }
+        HandleMsg::FreezeBatch { addresses, reason } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let addresses = addresses
+                .iter()
+                .map(|address| deps.api.addr_validate(address))
+                .collect::<StdResult<Vec<_>>>()?;
+            let record = freeze_record(&info, &env, reason);
+            let mut response = Response::new().add_attribute("action", "freeze_batch");
+            for address in addresses {
+                if state.freeze(address.as_str(), Expiration::Never {}, &record, &env.block)? {
+                    response = response
+                        .add_event(Event::new("freeze").add_attribute("address", address.as_str()))
+                        .add_attribute("address", address);
+                } else {
+                    response = response.add_attribute("already_frozen", address);
+                }
+            }
+            Ok(response)
+        }
+        HandleMsg::UnfreezeBatch { addresses } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let addresses = addresses
+                .iter()
+                .map(|address| deps.api.addr_validate(address))
+                .collect::<StdResult<Vec<_>>>()?;
+            let mut response = Response::new().add_attribute("action", "unfreeze_batch");
+            for address in addresses {
+                state.unfreeze(address.as_str())?;
+                response = response
+                    .add_event(Event::new("unfreeze").add_attribute("address", address.as_str()))
+                    .add_attribute("address", address);
+            }
+            Ok(response)
+        }
+        HandleMsg::SetMintAllowlistEnabled { enabled } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.set_mint_allowlist_enabled(*enabled)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_mint_allowlist_enabled")
+                .add_attribute("enabled", enabled.to_string()))
+        }
+        HandleMsg::AddToMintAllowlist { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.add_to_mint_allowlist(&address.to_string())?;
+            Ok(Response::new()
+                .add_attribute("action", "add_to_mint_allowlist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::RemoveFromMintAllowlist { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.remove_from_mint_allowlist(&address.to_string())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_from_mint_allowlist")
+                .add_attribute("address", address))
+        }
+        HandleMsg::UpdateMaxReceivePerTx { amount } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_max_receive_per_tx(*amount)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_max_receive_per_tx")
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::UpdateHolderCap {
+            max_holders,
+            minter_bypasses_holder_cap,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_holder_cap(max_holders.map(|max_holders| HolderCap {
+                max_holders,
+                minter_bypasses_holder_cap: *minter_bypasses_holder_cap,
+            }))?;
+            Ok(Response::new()
+                .add_attribute("action", "update_holder_cap")
+                .add_attribute(
+                    "max_holders",
+                    max_holders.map_or_else(|| "none".to_string(), |max| max.to_string()),
+                )
+                .add_attribute(
+                    "minter_bypasses_holder_cap",
+                    minter_bypasses_holder_cap.to_string(),
+                ))
+        }
+        HandleMsg::ImportLegacyBalances { entries, proof } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let entries = entries
+                .iter()
+                .map(|coin| {
+                    Ok((
+                        deps.api.addr_validate(&coin.address)?.to_string(),
+                        coin.amount,
+                    ))
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            let imported = state.import_legacy_balances(&entries, env.block.height)?;
+            Ok(Response::new()
+                .add_attribute("action", "import_legacy_balances")
+                .add_attribute("proof", proof)
+                .add_attribute("accounts", entries.len().to_string())
+                .add_attribute("amount", imported.to_string()))
+        }
+        HandleMsg::LockCap {} => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.lock_cap()?;
+            Ok(Response::new().add_attribute("action", "lock_cap"))
+        }
+        HandleMsg::UpdateDefaultMintRecipient { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = address
+                .as_ref()
+                .map(|address| deps.api.addr_validate(address).map(|a| a.to_string()))
+                .transpose()?;
+            let address_attr = address.clone().unwrap_or_default();
+            state.update_default_mint_recipient(address)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_default_mint_recipient")
+                .add_attribute("address", address_attr))
+        }
+        HandleMsg::MintAndSend {
+            contract,
+            amount,
+            msg,
+        } => {
+            if !state.read().has_role(Role::Minter, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let contract_address = deps.api.addr_validate(contract)?;
+            state.consume_mint_allowance(info.sender.as_str(), *amount, &env.block)?;
+            state.mint(&contract_address.to_string(), *amount, env.block.height)?;
+            let receive = receive_message(
+                &mut state,
+                contract_address.as_str(),
+                info.sender.as_str(),
+                *amount,
+                msg,
+                None,
+                env.block.height,
+            )?;
+            Ok(Response::new()
+                .add_submessage(receive)
+                .add_event(movement_event(
+                    "mint",
+                    None,
+                    Some(contract_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "mint_and_send")
+                .add_attribute("to", contract_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::SetSoulbound { enabled } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.set_soulbound(*enabled)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_soulbound")
+                .add_attribute("enabled", enabled.to_string()))
+        }
+        HandleMsg::SetIgnoreUnknownMessages { enabled } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.set_ignore_unknown_messages(*enabled)?;
+            Ok(Response::new()
+                .add_attribute("action", "set_ignore_unknown_messages")
+                .add_attribute("enabled", enabled.to_string()))
+        }
+        HandleMsg::Revoke { address, amount } => {
+            let sender = info.sender.as_str();
+            if !state.read().has_role(Role::Admin, sender)?
+                && !state.read().has_role(Role::Minter, sender)?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.revoke(address.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "revoke",
+                    Some(address.as_str()),
+                    None,
+                    *amount,
+                ))
+                .add_attribute("action", "revoke")
+                .add_attribute("from", address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::MintLocked { recipient, amount } => {
+            if !state.read().has_role(Role::Minter, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let recipient_address = deps.api.addr_validate(recipient)?;
+            state.consume_mint_allowance(info.sender.as_str(), *amount, &env.block)?;
+            state.mint_locked(&recipient_address.to_string(), *amount, env.block.height)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "mint_locked",
+                    None,
+                    Some(recipient_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "mint_locked")
+                .add_attribute("to", recipient_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::ReleaseLocked { address, amount } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.release_locked(address.as_str(), *amount)?;
+            Ok(Response::new()
+                .add_attribute("action", "release_locked")
+                .add_attribute("address", address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::UpdateWhaleThreshold { amount } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_whale_threshold(*amount)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_whale_threshold")
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::UpdateFreezeGraceSeconds { seconds } => {
+            if !state.read().has_role(Role::Freezer, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_freeze_grace_seconds(*seconds)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_freeze_grace_seconds")
+                .add_attribute("seconds", seconds.to_string()))
+        }
+        HandleMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => {
+            let spender_address = deps.api.addr_validate(spender)?;
+            state.increase_allowance(
+                &info.sender.to_string(),
+                &spender_address.to_string(),
+                *amount,
+                *expires,
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "increase_allowance")
+                .add_attribute("owner", info.sender)
+                .add_attribute("spender", spender_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => {
+            let spender_address = deps.api.addr_validate(spender)?;
+            state.decrease_allowance(
+                &info.sender.to_string(),
+                &spender_address.to_string(),
+                *amount,
+                *expires,
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "decrease_allowance")
+                .add_attribute("owner", info.sender)
+                .add_attribute("spender", spender_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::PruneExpiredAllowances {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let owner_address = deps.api.addr_validate(owner)?;
+            let (pruned, last) = state.prune_expired_allowances(
+                owner_address.as_str(),
+                start_after.as_deref(),
+                *limit,
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "prune_expired_allowances")
+                .add_attribute("owner", owner_address)
+                .add_attribute("pruned", pruned.to_string())
+                .add_attribute("last_spender", last.unwrap_or_default()))
+        }
+        HandleMsg::RevokeSpenderAll {
+            spender,
+            start_after,
+            limit,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let spender_address = deps.api.addr_validate(spender)?;
+            let (revoked, last) = state.revoke_spender_all(
+                spender_address.as_str(),
+                start_after.as_deref(),
+                *limit,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "revoke_spender_all")
+                .add_attribute("spender", spender_address)
+                .add_attribute("revoked", revoked.to_string())
+                .add_attribute("last_owner", last.unwrap_or_default()))
+        }
+        HandleMsg::ClaimTreasury { to, amount } => {
+            let treasury = state.read().fee_config()?.map(|config| config.treasury);
+            if treasury.as_deref() != Some(info.sender.as_str())
+                && !state.read().has_role(Role::Admin, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let to = deps.api.addr_validate(to)?;
+            let treasury = state.claim_treasury(to.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "claim_treasury",
+                    Some(treasury.as_str()),
+                    Some(to.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "claim_treasury")
+                .add_attribute("treasury", treasury)
+                .add_attribute("to", to)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::RotateTreasury { new_treasury } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let new_treasury = deps.api.addr_validate(new_treasury)?;
+            let (previous, moved) = state.rotate_treasury(new_treasury.as_str(), &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "rotate_treasury",
+                    Some(previous.as_str()),
+                    Some(new_treasury.as_str()),
+                    moved,
+                ))
+                .add_attribute("action", "rotate_treasury")
+                .add_attribute("previous_treasury", previous)
+                .add_attribute("treasury", new_treasury)
+                .add_attribute("moved", moved.to_string()))
+        }
+        HandleMsg::SecureAccount { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            let record = freeze_record(&info, &env, &Some("secure_account".to_string()));
+            let mut response = Response::new();
+            if state.freeze(address.as_str(), Expiration::Never {}, &record, &env.block)? {
+                response = response.add_event(
+                    Event::new("freeze")
+                        .add_attribute("address", address.as_str())
+                        .add_attribute("expires", Expiration::Never {}.to_string()),
+                );
+            }
+            let (revoked, more) = state.revoke_granted_allowances(address.as_str())?;
+            Ok(response
+                .add_attribute("action", "secure_account")
+                .add_attribute("address", address)
+                .add_attribute("revoked", revoked.to_string())
+                .add_attribute("more", more.to_string()))
+        }
+        HandleMsg::VerifyInvariants { start_after, limit } => {
+            let (last, sum) = state.verify_invariants(start_after.as_deref(), *limit)?;
+            let response = Response::new()
+                .add_attribute("action", "verify_invariants")
+                .add_attribute("last_account", last.unwrap_or_default());
+            Ok(match sum {
+                Some(sum) => {
+                    let supply = state.read().total_supply()?;
+                    let status = if sum == supply { "ok" } else { "mismatch" };
+                    response.add_event(
+                        Event::new("supply_invariant")
+                            .add_attribute("status", status)
+                            .add_attribute("balances", sum.to_string())
+                            .add_attribute("total_supply", supply.to_string()),
+                    )
+                }
+                None => response,
+            })
+        }
+        HandleMsg::SetSpendLimit {
+            spender,
+            amount_per_period,
+            period,
+        } => {
+            let spender_address = deps.api.addr_validate(spender)?;
+            state.set_spend_limit(
+                info.sender.as_str(),
+                spender_address.as_str(),
+                Some(RateLimit {
+                    window_seconds: *period,
+                    limit: *amount_per_period,
+                }),
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "set_spend_limit")
+                .add_attribute("owner", info.sender)
+                .add_attribute("spender", spender_address)
+                .add_attribute("amount_per_period", amount_per_period.to_string())
+                .add_attribute("period", period.to_string()))
+        }
+        HandleMsg::RemoveSpendLimit { spender } => {
+            let spender_address = deps.api.addr_validate(spender)?;
+            state.set_spend_limit(info.sender.as_str(), spender_address.as_str(), None)?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_spend_limit")
+                .add_attribute("owner", info.sender)
+                .add_attribute("spender", spender_address))
+        }
+        HandleMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => {
+            let owner_address = deps.api.addr_validate(owner)?;
+            let recipient_address = deps.api.addr_validate(recipient)?;
+            state.deduct_allowance(
+                &owner_address.to_string(),
+                &info.sender.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let outcome = state.transfer(
+                &owner_address.to_string(),
+                &recipient_address.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let response = charge_attributes(Response::new(), owner_address.as_str(), &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "transfer",
+                    Some(owner_address.as_str()),
+                    Some(recipient_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "transfer_from")
+                .add_attribute("from", owner_address)
+                .add_attribute("to", recipient_address)
+                .add_attribute("by", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Burn { amount, memo } => {
+            let sender_address = info.sender.to_string();
+            state.burn(&sender_address, *amount, &env.block)?;
+            let response = Response::new()
+                .add_event(movement_event("burn", Some(&sender_address), None, *amount))
+                .add_attribute("action", "burn")
+                .add_attribute("from", sender_address)
+                .add_attribute("amount", amount.to_string());
+            with_memo(response, memo)
+        }
+        HandleMsg::BurnFrom { owner, amount } => {
+            let owner_address = deps.api.addr_validate(owner)?;
+            state.deduct_allowance(
+                &owner_address.to_string(),
+                &info.sender.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            state.burn(&owner_address.to_string(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "burn",
+                    Some(owner_address.as_str()),
+                    None,
+                    *amount,
+                ))
+                .add_attribute("action", "burn_from")
+                .add_attribute("from", owner_address)
+                .add_attribute("by", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Send {
+            contract,
+            amount,
+            msg,
+            memo,
+        } => {
+            let contract_address = deps.api.addr_validate(contract)?;
+            let outcome = state.transfer(
+                &info.sender.to_string(),
+                &contract_address.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let receive = receive_message(
+                &mut state,
+                contract_address.as_str(),
+                info.sender.as_str(),
+                outcome.received,
+                msg,
+                Some(info.sender.as_str()),
+                env.block.height,
+            )?;
+            let response = charge_attributes(Response::new(), info.sender.as_str(), &outcome)
+                .add_submessage(receive)
+                .add_event(movement_event(
+                    "transfer",
+                    Some(info.sender.as_str()),
+                    Some(contract_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "send")
+                .add_attribute("from", info.sender)
+                .add_attribute("to", contract_address)
+                .add_attribute("amount", amount.to_string());
+            with_memo(response, memo)
+        }
+        HandleMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => {
+            let owner_address = deps.api.addr_validate(owner)?;
+            let contract_address = deps.api.addr_validate(contract)?;
+            state.deduct_allowance(
+                &owner_address.to_string(),
+                &info.sender.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let outcome = state.transfer(
+                &owner_address.to_string(),
+                &contract_address.to_string(),
+                *amount,
+                &env.block,
+            )?;
+            let receive = receive_message(
+                &mut state,
+                contract_address.as_str(),
+                info.sender.as_str(),
+                outcome.received,
+                msg,
+                Some(owner_address.as_str()),
+                env.block.height,
+            )?;
+            let response = charge_attributes(Response::new(), owner_address.as_str(), &outcome);
+            Ok(response
+                .add_submessage(receive)
+                .add_event(movement_event(
+                    "transfer",
+                    Some(owner_address.as_str()),
+                    Some(contract_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "send_from")
+                .add_attribute("from", owner_address)
+                .add_attribute("to", contract_address)
+                .add_attribute("by", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => {
+            if !state.read().marketing_allowed(info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let marketing = match marketing.as_deref() {
+                None => None,
+                Some("") => Some(None),
+                Some(address) => Some(Some(deps.api.addr_validate(address)?)),
+            };
+            state.update_marketing(project.clone(), description.clone(), marketing)?;
+            Ok(Response::new().add_attribute("action", "update_marketing"))
+        }
+        HandleMsg::Pause {} => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())?
+                && !state.read().has_role(Role::Pauser, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.pause()?;
+            Ok(Response::new().add_attribute("action", "pause"))
+        }
+        HandleMsg::Unpause {} => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())?
+                && !state.read().has_role(Role::Pauser, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.unpause()?;
+            Ok(Response::new().add_attribute("action", "unpause"))
+        }
+        HandleMsg::UpdateBurnRate { burn_bps } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_burn_bps(*burn_bps)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_burn_rate")
+                .add_attribute("burn_bps", burn_bps.to_string()))
+        }
+        HandleMsg::UpdateBuyback { buyback } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let buyback = buyback
+                .clone()
+                .map(|mut config| {
+                    config.pair = deps.api.addr_validate(&config.pair)?.to_string();
+                    Ok::<_, StdError>(config)
+                })
+                .transpose()?;
+            let pair = buyback
+                .as_ref()
+                .map(|config| config.pair.clone())
+                .unwrap_or_default();
+            state.update_buyback(buyback)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_buyback")
+                .add_attribute("pair", pair))
+        }
+        HandleMsg::FundTreasury {} => {
+            let denom = state
+                .read()
+                .buyback()?
+                .ok_or(ContractError::NoBuyback {})?
+                .denom;
+            let amount = match info.funds.as_slice() {
+                [coin] if coin.denom == denom && !coin.amount.is_zero() => coin.amount,
+                _ => return Err(ContractError::InvalidFunds { denom }),
+            };
+            state.fund_treasury(&denom, amount)?;
+            Ok(Response::new()
+                .add_attribute("action", "fund_treasury")
+                .add_attribute("from", info.sender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("denom", denom))
+        }
+        HandleMsg::BuybackAndBurn { amount, max_spread } => {
+            let config = state.read().buyback()?.ok_or(ContractError::NoBuyback {})?;
+            let is_admin = state.read().has_role(Role::Admin, info.sender.as_str())?;
+            if !is_admin && !config.permissionless {
+                return Err(ContractError::Unauthorized {});
+            }
+            let spent = state.spend_treasury(&config.denom, *amount)?;
+            let context = ReplyKind::Buyback {
+                caller: info.sender.to_string(),
+                spent: Coin::new(spent.u128(), &config.denom),
+                balance_before: state.read().balance(env.contract.address.as_str())?,
+                bounty_bps: if is_admin { 0 } else { config.bounty_bps },
+            };
+            let swap = WasmMsg::Execute {
+                contract_addr: config.pair.clone(),
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: PairAsset {
+                        info: PairAssetInfo::NativeToken {
+                            denom: config.denom.clone(),
+                        },
+                        amount: spent,
+                    },
+                    belief_price: None,
+                    max_spread: *max_spread,
+                    to: None,
+                })?,
+                funds: coins(spent.u128(), &config.denom),
+            };
+            let id = state.push_reply_context(context, env.block.height)?;
+            Ok(Response::new()
+                .add_submessage(SubMsg::reply_on_success(swap, id))
+                .add_attribute("action", "buyback_and_burn")
+                .add_attribute("pair", config.pair)
+                .add_attribute("spent", spent.to_string())
+                .add_attribute("denom", config.denom))
+        }
+        HandleMsg::UpdateFlashMintFee { fee_bps } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_flash_mint_fee(*fee_bps)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_flash_mint_fee")
+                .add_attribute("fee_bps", fee_bps.to_string()))
+        }
+        HandleMsg::FlashMint {
+            amount,
+            msg,
+            recipient_contract,
+        } => {
+            let borrower = deps.api.addr_validate(recipient_contract)?;
+            let (fee, context) = state.flash_mint(borrower.as_str(), *amount, env.block.height)?;
+            let call = WasmMsg::Execute {
+                contract_addr: borrower.to_string(),
+                msg: to_binary(&FlashMintReceiverMsg::ReceiveFlashMint {
+                    initiator: info.sender.to_string(),
+                    amount: *amount,
+                    fee,
+                    msg: msg.clone(),
+                })?,
+                funds: vec![],
+            };
+            let id = state.push_reply_context(context, env.block.height)?;
+            Ok(Response::new()
+                .add_submessage(SubMsg::reply_on_success(call, id))
+                .add_event(movement_event(
+                    "flash_mint",
+                    None,
+                    Some(borrower.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "flash_mint")
+                .add_attribute("to", borrower)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("fee", fee.to_string()))
+        }
+        HandleMsg::UpdateStakingConfig {
+            unbonding_seconds,
+            epoch_seconds,
+            reward_rate,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.update_staking_config(StakingConfig {
+                unbonding_seconds: *unbonding_seconds,
+                epoch_seconds: *epoch_seconds,
+                reward_rate: *reward_rate,
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "update_staking_config")
+                .add_attribute("unbonding_seconds", unbonding_seconds.to_string())
+                .add_attribute("epoch_seconds", epoch_seconds.to_string())
+                .add_attribute("reward_rate", reward_rate.to_string()))
+        }
+        HandleMsg::Stake { amount } => {
+            state.stake(info.sender.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_attribute("action", "stake")
+                .add_attribute("address", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Unstake { amount } => {
+            state.unstake(info.sender.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_attribute("action", "unstake")
+                .add_attribute("address", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::TransferLocked {
+            recipient,
+            amount,
+            release,
+        } => {
+            let recipient = deps.api.addr_validate(recipient)?;
+            let (locked, outcome) = state.transfer_locked(
+                info.sender.as_str(),
+                recipient.as_str(),
+                env.contract.address.as_str(),
+                *amount,
+                *release,
+                &env.block,
+            )?;
+            let response = charge_attributes(Response::new(), info.sender.as_str(), &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "transfer_locked",
+                    Some(info.sender.as_str()),
+                    Some(env.contract.address.as_str()),
+                    locked.amount,
+                ))
+                .add_attribute("action", "transfer_locked")
+                .add_attribute("id", locked.id.to_string())
+                .add_attribute("from", info.sender)
+                .add_attribute("to", recipient)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("release", release.to_string()))
+        }
+        HandleMsg::ClaimLocked {} => {
+            let claimed = state.claim_locked(
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "claim_locked",
+                    Some(env.contract.address.as_str()),
+                    Some(info.sender.as_str()),
+                    claimed,
+                ))
+                .add_attribute("action", "claim_locked")
+                .add_attribute("to", info.sender)
+                .add_attribute("amount", claimed.to_string()))
+        }
+        HandleMsg::CreateEscrow {
+            recipient,
+            arbiter,
+            amount,
+            expires,
+        } => {
+            let escrow = state.create_escrow(
+                Escrow {
+                    id: 0,
+                    sender: info.sender.to_string(),
+                    recipient: deps.api.addr_validate(recipient)?.to_string(),
+                    arbiter: deps.api.addr_validate(arbiter)?.to_string(),
+                    amount: *amount,
+                    expires: *expires,
+                },
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "create_escrow",
+                    Some(info.sender.as_str()),
+                    Some(env.contract.address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "create_escrow")
+                .add_attribute("id", escrow.id.to_string())
+                .add_attribute("from", info.sender)
+                .add_attribute("recipient", escrow.recipient)
+                .add_attribute("arbiter", escrow.arbiter)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::ReleaseEscrow { id } => {
+            let (escrow, outcome) = state.release_escrow(
+                *id,
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            let response = charge_attributes(Response::new(), &escrow.sender, &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "release_escrow",
+                    Some(env.contract.address.as_str()),
+                    Some(&escrow.recipient),
+                    outcome.received,
+                ))
+                .add_attribute("action", "release_escrow")
+                .add_attribute("id", id.to_string())
+                .add_attribute("to", escrow.recipient)
+                .add_attribute("amount", escrow.amount.to_string()))
+        }
+        HandleMsg::RefundEscrow { id } => {
+            let escrow = state.refund_escrow(*id, env.contract.address.as_str(), &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "refund_escrow",
+                    Some(env.contract.address.as_str()),
+                    Some(&escrow.sender),
+                    escrow.amount,
+                ))
+                .add_attribute("action", "refund_escrow")
+                .add_attribute("id", id.to_string())
+                .add_attribute("to", escrow.sender)
+                .add_attribute("amount", escrow.amount.to_string()))
+        }
+        HandleMsg::CreateSwap {
+            recipient,
+            hash,
+            expires,
+            amount,
+        } => {
+            let swap = state.create_swap(
+                Swap {
+                    id: 0,
+                    sender: info.sender.to_string(),
+                    recipient: deps.api.addr_validate(recipient)?.to_string(),
+                    hash: hash.clone(),
+                    amount: *amount,
+                    expires: *expires,
+                },
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "create_swap",
+                    Some(info.sender.as_str()),
+                    Some(env.contract.address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "create_swap")
+                .add_attribute("id", swap.id.to_string())
+                .add_attribute("from", info.sender)
+                .add_attribute("recipient", swap.recipient)
+                .add_attribute("hash", swap.hash.to_hex())
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::ClaimSwap { id, preimage } => {
+            let (swap, outcome) = state.claim_swap(
+                *id,
+                preimage.as_slice(),
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            let response = charge_attributes(Response::new(), &swap.sender, &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "claim_swap",
+                    Some(env.contract.address.as_str()),
+                    Some(&swap.recipient),
+                    outcome.received,
+                ))
+                .add_attribute("action", "claim_swap")
+                .add_attribute("id", id.to_string())
+                .add_attribute("to", swap.recipient)
+                .add_attribute("amount", swap.amount.to_string())
+                .add_attribute("preimage", preimage.to_hex()))
+        }
+        HandleMsg::RefundSwap { id } => {
+            let swap = state.refund_swap(*id, env.contract.address.as_str(), &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "refund_swap",
+                    Some(env.contract.address.as_str()),
+                    Some(&swap.sender),
+                    swap.amount,
+                ))
+                .add_attribute("action", "refund_swap")
+                .add_attribute("id", id.to_string())
+                .add_attribute("to", swap.sender)
+                .add_attribute("amount", swap.amount.to_string()))
+        }
+        HandleMsg::CreateStream {
+            recipient,
+            amount,
+            start,
+            end,
+        } => {
+            let stream = state.create_stream(
+                Stream {
+                    id: 0,
+                    sender: info.sender.to_string(),
+                    recipient: deps.api.addr_validate(recipient)?.to_string(),
+                    amount: *amount,
+                    withdrawn: Uint128::zero(),
+                    start: *start,
+                    end: *end,
+                },
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "create_stream",
+                    Some(info.sender.as_str()),
+                    Some(env.contract.address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "create_stream")
+                .add_attribute("id", stream.id.to_string())
+                .add_attribute("from", info.sender)
+                .add_attribute("recipient", stream.recipient)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("start", start.to_string())
+                .add_attribute("end", end.to_string()))
+        }
+        HandleMsg::WithdrawFromStream { id } => {
+            let (stream, outcome) = state.withdraw_from_stream(
+                *id,
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            let response = charge_attributes(Response::new(), &stream.sender, &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "withdraw_from_stream",
+                    Some(env.contract.address.as_str()),
+                    Some(&stream.recipient),
+                    outcome.received,
+                ))
+                .add_attribute("action", "withdraw_from_stream")
+                .add_attribute("id", id.to_string())
+                .add_attribute("to", stream.recipient)
+                .add_attribute("amount", outcome.sent.to_string())
+                .add_attribute("amount_received", outcome.received.to_string()))
+        }
+        HandleMsg::CancelStream { id } => {
+            let (stream, outcome, refund) = state.cancel_stream(
+                *id,
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                &env.block,
+            )?;
+            let mut response = charge_attributes(Response::new(), &stream.sender, &outcome);
+            if !outcome.received.is_zero() {
+                response = response.add_event(movement_event(
+                    "withdraw_from_stream",
+                    Some(env.contract.address.as_str()),
+                    Some(&stream.recipient),
+                    outcome.received,
+                ));
+            }
+            if !refund.is_zero() {
+                response = response.add_event(movement_event(
+                    "cancel_stream",
+                    Some(env.contract.address.as_str()),
+                    Some(&stream.sender),
+                    refund,
+                ));
+            }
+            Ok(response
+                .add_attribute("action", "cancel_stream")
+                .add_attribute("id", id.to_string())
+                .add_attribute("recipient", stream.recipient)
+                .add_attribute("amount_received", outcome.received.to_string())
+                .add_attribute("refund", refund.to_string()))
+        }
+        HandleMsg::CreateSubscription {
+            merchant,
+            amount,
+            period,
+            start,
+        } => {
+            let subscription = state.create_subscription(Subscription {
+                id: 0,
+                subscriber: info.sender.to_string(),
+                merchant: deps.api.addr_validate(merchant)?.to_string(),
+                amount: *amount,
+                period: *period,
+                next_collection: start.unwrap_or_else(|| env.block.time.seconds()),
+            })?;
+            Ok(Response::new()
+                .add_attribute("action", "create_subscription")
+                .add_attribute("id", subscription.id.to_string())
+                .add_attribute("subscriber", info.sender)
+                .add_attribute("merchant", subscription.merchant)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("period", period.to_string()))
+        }
+        HandleMsg::CollectSubscription { id } => {
+            let (subscription, outcome) =
+                state.collect_subscription(*id, info.sender.as_str(), &env.block)?;
+            let response = charge_attributes(Response::new(), &subscription.subscriber, &outcome);
+            Ok(response
+                .add_event(movement_event(
+                    "collect_subscription",
+                    Some(&subscription.subscriber),
+                    Some(&subscription.merchant),
+                    outcome.received,
+                ))
+                .add_attribute("action", "collect_subscription")
+                .add_attribute("id", id.to_string())
+                .add_attribute("from", subscription.subscriber)
+                .add_attribute("to", subscription.merchant)
+                .add_attribute("amount", outcome.sent.to_string())
+                .add_attribute("amount_received", outcome.received.to_string())
+                .add_attribute("next_collection", subscription.next_collection.to_string()))
+        }
+        HandleMsg::CancelSubscription { id } => {
+            let subscription = state.cancel_subscription(*id, info.sender.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "cancel_subscription")
+                .add_attribute("id", id.to_string())
+                .add_attribute("subscriber", subscription.subscriber)
+                .add_attribute("merchant", subscription.merchant))
+        }
+        HandleMsg::IbcTransfer {
+            channel,
+            remote_address,
+            amount,
+            timeout,
+        } => {
+            state.ibc_escrow(
+                channel,
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                *amount,
+                &env.block,
+            )?;
+            let packet = Ics20Packet {
+                amount: *amount,
+                denom: local_denom(&env),
+                receiver: remote_address.clone(),
+                sender: info.sender.to_string(),
+            };
+            let timeout = env
+                .block
+                .time
+                .plus_seconds(timeout.unwrap_or(DEFAULT_TIMEOUT_SECONDS));
+            Ok(Response::new()
+                .add_message(IbcMsg::SendPacket {
+                    channel_id: channel.clone(),
+                    data: to_binary(&packet)?,
+                    timeout: timeout.into(),
+                })
+                .add_event(movement_event(
+                    "ibc_transfer",
+                    Some(info.sender.as_str()),
+                    Some(env.contract.address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "ibc_transfer")
+                .add_attribute("from", info.sender)
+                .add_attribute("channel", channel)
+                .add_attribute("receiver", remote_address)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Deposit {} => {
+            let denom = state
+                .read()
+                .wrapped_denom()?
+                .ok_or(ContractError::NotWrapped {})?;
+            let amount = match info.funds.as_slice() {
+                [coin] if coin.denom == denom && !coin.amount.is_zero() => coin.amount,
+                _ => return Err(ContractError::InvalidFunds { denom }),
+            };
+            state.mint(info.sender.as_str(), amount, env.block.height)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "mint",
+                    None,
+                    Some(info.sender.as_str()),
+                    amount,
+                ))
+                .add_attribute("action", "deposit")
+                .add_attribute("to", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::Buy {} => {
+            let denom = state
+                .read()
+                .bonding_curve()?
+                .ok_or(ContractError::NoBondingCurve {})?
+                .reserve_denom;
+            let paid = match info.funds.as_slice() {
+                [coin] if coin.denom == denom && !coin.amount.is_zero() => coin.amount,
+                _ => return Err(ContractError::InvalidFunds { denom }),
+            };
+            let amount = state.buy(info.sender.as_str(), paid, env.block.height)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "mint",
+                    None,
+                    Some(info.sender.as_str()),
+                    amount,
+                ))
+                .add_attribute("action", "buy")
+                .add_attribute("to", info.sender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("paid", paid.to_string()))
+        }
+        HandleMsg::Sell { amount } => {
+            let denom = state
+                .read()
+                .bonding_curve()?
+                .ok_or(ContractError::NoBondingCurve {})?
+                .reserve_denom;
+            let refund = state.sell(info.sender.as_str(), *amount, &env.block)?;
+            let mut response = Response::new();
+            if !refund.is_zero() {
+                response = response.add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: coins(refund.u128(), denom),
+                });
+            }
+            Ok(response
+                .add_event(movement_event(
+                    "burn",
+                    Some(info.sender.as_str()),
+                    None,
+                    *amount,
+                ))
+                .add_attribute("action", "sell")
+                .add_attribute("from", info.sender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("refund", refund.to_string()))
+        }
+        HandleMsg::Withdraw { amount } => {
+            let denom = state
+                .read()
+                .wrapped_denom()?
+                .ok_or(ContractError::NotWrapped {})?;
+            state.burn(info.sender.as_str(), *amount, &env.block)?;
+            Ok(Response::new()
+                .add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: coins(amount.u128(), denom),
+                })
+                .add_event(movement_event(
+                    "burn",
+                    Some(info.sender.as_str()),
+                    None,
+                    *amount,
+                ))
+                .add_attribute("action", "withdraw")
+                .add_attribute("from", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::AddNonCirculating { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.add_non_circulating(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "add_non_circulating")
+                .add_attribute("address", address))
+        }
+        HandleMsg::RemoveNonCirculating { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.remove_non_circulating(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_non_circulating")
+                .add_attribute("address", address))
+        }
+        HandleMsg::RecoverFunds { asset, recipient } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let recipient = deps.api.addr_validate(recipient)?;
+            let recovery: CosmosMsg = match asset {
+                Asset::Native { denom, amount } => {
+                    // coins of the wrapped denom back the supply, the curve
+                    // reserve backs curve sales, the treasury is kept for
+                    // buybacks and unclaimed holder rewards are owed; only
+                    // the surplus was sent by mistake
+                    let mut reserved = state.read().dividend_pool(denom)?;
+                    if state.read().wrapped_denom()?.as_ref() == Some(denom) {
+                        reserved = add_balance(reserved, state.read().total_supply()?)?;
+                    }
+                    reserved = add_balance(reserved, state.read().treasury(denom)?)?;
+                    if let Some(curve) = state.read().bonding_curve()? {
+                        if &curve.reserve_denom == denom {
+                            reserved = add_balance(reserved, state.read().curve_reserve()?)?;
+                        }
+                    }
+                    if !reserved.is_zero() {
+                        let held = deps
+                            .querier
+                            .query_balance(env.contract.address.as_str(), denom)?
+                            .amount;
+                        let available = held.saturating_sub(reserved);
+                        if *amount > available {
+                            return Err(ContractError::InsufficientSurplus { available });
+                        }
+                    }
+                    BankMsg::Send {
+                        to_address: recipient.to_string(),
+                        amount: coins(amount.u128(), denom),
+                    }
+                    .into()
+                }
+                Asset::Cw20 { contract, amount } => {
+                    let contract = deps.api.addr_validate(contract)?;
+                    if contract == env.contract.address {
+                        return Err(ContractError::CannotRecoverOwnToken {});
+                    }
+                    WasmMsg::Execute {
+                        contract_addr: contract.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                            recipient: recipient.to_string(),
+                            amount: *amount,
+                        })?,
+                        funds: vec![],
+                    }
+                    .into()
+                }
+            };
+            Ok(Response::new()
+                .add_message(recovery)
+                .add_attribute("action", "recover_funds")
+                .add_attribute("recipient", recipient))
+        }
+        HandleMsg::ClaimUnbonded {} => {
+            let released = state.claim_unbonded(info.sender.as_str(), env.block.time.seconds())?;
+            Ok(Response::new()
+                .add_attribute("action", "claim_unbonded")
+                .add_attribute("address", info.sender)
+                .add_attribute("amount", released.to_string()))
+        }
+        HandleMsg::DistributeRewards { amount } => {
+            let amount = amount.unwrap_or_default();
+            if amount.is_zero() && info.funds.is_empty() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+            let custodian = env.contract.address.as_str();
+            let mut response = Response::new();
+            if !amount.is_zero() {
+                state.deposit_dividends(
+                    info.sender.as_str(),
+                    custodian,
+                    &local_denom(&env),
+                    amount,
+                    &env.block,
+                )?;
+                response = response.add_event(movement_event(
+                    "distribute_rewards",
+                    Some(info.sender.as_str()),
+                    Some(custodian),
+                    amount,
+                ));
+            }
+            for coin in &info.funds {
+                if !state.read().reward_denom_allowed(&coin.denom)? {
+                    return Err(ContractError::RewardDenomNotAllowed {
+                        denom: coin.denom.clone(),
+                    });
+                }
+                state.distribute_dividends(custodian, &coin.denom, coin.amount)?;
+            }
+            let funds: Vec<String> = info.funds.iter().map(|coin| coin.to_string()).collect();
+            Ok(response
+                .add_attribute("action", "distribute_rewards")
+                .add_attribute("from", info.sender)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("funds", funds.join(",")))
+        }
+        HandleMsg::AddRewardDenom { denom } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.add_reward_denom(denom)?;
+            Ok(Response::new()
+                .add_attribute("action", "add_reward_denom")
+                .add_attribute("denom", denom))
+        }
+        HandleMsg::RemoveRewardDenom { denom } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.remove_reward_denom(denom)?;
+            Ok(Response::new()
+                .add_attribute("action", "remove_reward_denom")
+                .add_attribute("denom", denom))
+        }
+        HandleMsg::WatchAccount { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.watch_account(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "watch_account")
+                .add_attribute("address", address))
+        }
+        HandleMsg::UnwatchAccount { address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.unwatch_account(address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "unwatch_account")
+                .add_attribute("address", address))
+        }
+        HandleMsg::ClaimRewards {} => {
+            let rewards = state.claim_rewards(info.sender.as_str(), &env.block)?;
+            let (dividends, native) = state.claim_dividends(
+                info.sender.as_str(),
+                env.contract.address.as_str(),
+                &local_denom(&env),
+                env.block.height,
+            )?;
+            if rewards.is_zero() && dividends.is_zero() && native.is_empty() {
+                return Err(ContractError::NothingToClaim {});
+            }
+            let mut response = Response::new();
+            if !rewards.is_zero() {
+                response = response.add_event(movement_event(
+                    "staking_reward",
+                    None,
+                    Some(info.sender.as_str()),
+                    rewards,
+                ));
+            }
+            if !dividends.is_zero() {
+                response = response.add_event(movement_event(
+                    "claim_dividends",
+                    Some(env.contract.address.as_str()),
+                    Some(info.sender.as_str()),
+                    dividends,
+                ));
+            }
+            if !native.is_empty() {
+                response = response.add_message(BankMsg::Send {
+                    to_address: info.sender.to_string(),
+                    amount: native,
+                });
+            }
+            Ok(response
+                .add_attribute("action", "claim_rewards")
+                .add_attribute("to", info.sender)
+                .add_attribute("amount", rewards.to_string())
+                .add_attribute("dividends", dividends.to_string()))
+        }
+        HandleMsg::UpdateExchangeRate { rate } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())?
+                && !state.read().has_role(Role::Oracle, info.sender.as_str())?
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+            let previous = state.read().exchange_rate()?;
+            state.update_exchange_rate(*rate)?;
+            Ok(Response::new()
+                .add_attribute("action", "update_exchange_rate")
+                .add_attribute(
+                    "previous",
+                    previous.map(|rate| rate.to_string()).unwrap_or_default(),
+                )
+                .add_attribute("rate", rate.to_string()))
+        }
+        HandleMsg::Rebase {
+            numerator,
+            denominator,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let shares = state.read().total_supply()?;
+            let supply_before = state.read().share_value(shares)?;
+            let (previous, index) = state.rebase(*numerator, *denominator)?;
+            let supply_after = state.read().share_value(shares)?;
+            let (direction, delta) = if supply_after >= supply_before {
+                ("increase", supply_after - supply_before)
+            } else {
+                ("decrease", supply_before - supply_after)
+            };
+            Ok(Response::new()
+                .add_event(
+                    Event::new("rebase")
+                        .add_attribute("previous_index", previous.to_string())
+                        .add_attribute("index", index.to_string())
+                        .add_attribute("supply_before", supply_before.to_string())
+                        .add_attribute("supply_after", supply_after.to_string())
+                        .add_attribute("direction", direction)
+                        .add_attribute("delta", delta.to_string()),
+                )
+                .add_attribute("action", "rebase")
+                .add_attribute("numerator", numerator.to_string())
+                .add_attribute("denominator", denominator.to_string()))
+        }
+        HandleMsg::AdvanceEpoch {} => {
+            let (recipient, amount) = state.advance_epoch(&env.block)?;
+            let epoch = state.read().inflation_epochs()?.minted;
+            let mut response = Response::new();
+            if let Some(recipient) = &recipient {
+                response =
+                    response.add_event(movement_event("inflation", None, Some(recipient), amount));
+            }
+            Ok(response
+                .add_attribute("action", "advance_epoch")
+                .add_attribute("epoch", epoch.to_string())
+                .add_attribute("to", recipient.unwrap_or_else(|| "stakers".to_string()))
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::CreateVesting {
+            recipient,
+            amount,
+            start,
+            cliff,
+            end,
+        } => {
+            if !state.read().has_role(Role::Minter, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let recipient_address = deps.api.addr_validate(recipient)?;
+            state.consume_mint_allowance(info.sender.as_str(), *amount, &env.block)?;
+            state.create_vesting(
+                recipient_address.as_str(),
+                *amount,
+                *start,
+                *cliff,
+                *end,
+                env.block.height,
+            )?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "create_vesting",
+                    None,
+                    Some(recipient_address.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "create_vesting")
+                .add_attribute("to", recipient_address)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("cliff", cliff.to_string())
+                .add_attribute("end", end.to_string()))
+        }
+        HandleMsg::ClaimVested {} => {
+            let claimed = state.claim_vested(info.sender.as_str(), env.block.time.seconds())?;
+            Ok(Response::new()
+                .add_attribute("action", "claim_vested")
+                .add_attribute("address", info.sender)
+                .add_attribute("amount", claimed.to_string()))
+        }
+        HandleMsg::RegisterAirdrop {
+            merkle_root,
+            total_amount,
+            expiration,
+        } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let stage = state.register_airdrop(
+                merkle_root.clone(),
+                *total_amount,
+                *expiration,
+                &env.block,
+            )?;
+            Ok(Response::new()
+                .add_attribute("action", "register_airdrop")
+                .add_attribute("stage", stage.to_string())
+                .add_attribute("merkle_root", merkle_root.to_hex())
+                .add_attribute("total_amount", total_amount.to_string()))
+        }
+        HandleMsg::Claim { amount, proof } => {
+            state.claim_airdrop(info.sender.as_str(), *amount, proof, &env.block)?;
+            Ok(Response::new()
+                .add_event(movement_event(
+                    "airdrop_claim",
+                    None,
+                    Some(info.sender.as_str()),
+                    *amount,
+                ))
+                .add_attribute("action", "claim")
+                .add_attribute("to", info.sender)
+                .add_attribute("amount", amount.to_string()))
+        }
+        HandleMsg::ClawbackAirdrop {} => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let unclaimed = state.clawback_airdrop(&env.block)?;
+            Ok(Response::new()
+                .add_attribute("action", "clawback_airdrop")
+                .add_attribute("unclaimed", unclaimed.to_string()))
+        }
+        HandleMsg::GrantRole { role, address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.grant_role(*role, address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "grant_role")
+                .add_attribute("role", role.as_str())
+                .add_attribute("address", address))
+        }
+        HandleMsg::RevokeRole { role, address } => {
+            if !state.read().has_role(Role::Admin, info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            let address = deps.api.addr_validate(address)?;
+            state.revoke_role(*role, address.as_str())?;
+            Ok(Response::new()
+                .add_attribute("action", "revoke_role")
+                .add_attribute("role", role.as_str())
+                .add_attribute("address", address))
+        }
+        HandleMsg::UploadLogo(logo) => {
+            if !state.read().marketing_allowed(info.sender.as_str())? {
+                return Err(ContractError::Unauthorized {});
+            }
+            state.upload_logo(logo.clone())?;
+            Ok(Response::new().add_attribute("action", "upload_logo"))
+        }
+    }?;
+    state.record_activity(&watched, &action_attribute(&response), env.block.height)?;
+    let hooks = hook_messages(&mut state, &response, env.block.height)?;
+    let response = response.add_submessages(hooks);
+    if is_privileged(&msg) {
+        let action = action_attribute(&response);
+        state.append_audit_entry(actor, action, to_binary(&msg)?, &env.block)?;
+    }
+    Ok(response)
+}
+
+/// Lets chain governance pause the token, freeze an address or rotate a lost
+/// admin key. Every action goes into the audit log with `governance` as the
+/// actor.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn sudo(deps: DepsMut, env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
+    let mut state = State::new(deps.storage);
+    let governance = Addr::unchecked(GOVERNANCE);
+    let response = match &msg {
+        SudoMsg::Pause {} => {
+            state.pause()?;
+            Response::new().add_attribute("action", "sudo_pause")
+        }
+        SudoMsg::Unpause {} => {
+            state.unpause()?;
+            Response::new().add_attribute("action", "sudo_unpause")
+        }
+        SudoMsg::Freeze {
+            address,
+            expires,
+            reason,
+        } => {
+            let address = deps.api.addr_validate(address)?;
+            let expires = expires.unwrap_or_default();
+            let record = FreezeRecord {
+                freezer: governance.clone(),
+                reason: reason.clone(),
+                height: env.block.height,
+                time: env.block.time.seconds(),
+            };
+            let mut response = Response::new();
+            if state.freeze(address.as_str(), expires, &record, &env.block)? {
+                response = response.add_event(
+                    Event::new("freeze")
+                        .add_attribute("address", address.as_str())
+                        .add_attribute("expires", expires.to_string()),
+                );
+            }
+            response
+                .add_attribute("action", "sudo_freeze")
+                .add_attribute("address", address)
+                .add_attribute("expires", expires.to_string())
+        }
+        SudoMsg::RotateAdmin {
+            old_admin,
+            new_admin,
+        } => {
+            let new_admin = deps.api.addr_validate(new_admin)?;
+            let old_admin = old_admin
+                .as_ref()
+                .map(|old_admin| deps.api.addr_validate(old_admin))
+                .transpose()?;
+            if let Some(old_admin) = &old_admin {
+                state.revoke_role(Role::Admin, old_admin.as_str())?;
+            }
+            state.grant_role(Role::Admin, new_admin.as_str())?;
+            Response::new()
+                .add_attribute("action", "sudo_rotate_admin")
+                .add_attribute("old_admin", old_admin.map(String::from).unwrap_or_default())
+                .add_attribute("new_admin", new_admin)
+        }
+    };
+    let action = action_attribute(&response);
+    state.append_audit_entry(governance, action, to_binary(&msg)?, &env.block)?;
+    Ok(response)
+}
+
+/// The first `action` attribute of `response`, under which it is audited.
+fn action_attribute(response: &Response) -> String {
+    response
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == "action")
+        .map(|attribute| attribute.value.clone())
+        .unwrap_or_default()
+}
+
+fn allowance_attribute(allowance: &Option<Uint128>) -> String {
+    allowance.map_or_else(
+        || "unlimited".to_string(),
+        |allowance| allowance.to_string(),
+    )
+}
+
+/// Addresses a message would credit, which must pass the KYC registry
+/// when one is configured.
+fn receiving_addresses(msg: &HandleMsg, state: &ReadonlyState) -> StdResult<Vec<String>> {
+    Ok(match msg {
+        HandleMsg::Transfer { recipient, .. }
+        | HandleMsg::TransferBySignature { recipient, .. }
+        | HandleMsg::TransferFrom { recipient, .. }
+        | HandleMsg::TransferLocked { recipient, .. }
+        | HandleMsg::CreateEscrow { recipient, .. }
+        | HandleMsg::CreateSwap { recipient, .. }
+        | HandleMsg::CreateStream { recipient, .. }
+        | HandleMsg::MintLocked { recipient, .. }
+        | HandleMsg::Mint {
+            recipient: Some(recipient),
+            ..
+        }
+        | HandleMsg::ProposeAction {
+            action: MultisigAction::Mint { recipient, .. },
+        } => vec![recipient.clone()],
+        HandleMsg::Mint {
+            recipient: None, ..
+        } => state.default_mint_recipient()?.into_iter().collect(),
+        HandleMsg::Send { contract, .. }
+        | HandleMsg::SendFrom { contract, .. }
+        | HandleMsg::MintAndSend { contract, .. } => vec![contract.clone()],
+        HandleMsg::CreateSubscription { merchant, .. } => vec![merchant.clone()],
+        HandleMsg::TransferBatch { transfers } => transfers
+            .iter()
+            .map(|entry| entry.recipient.clone())
+            .collect(),
+        _ => vec![],
+    })
+}
+
+fn ensure_verified(
+    querier: &QuerierWrapper,
+    registry: &str,
+    address: &str,
+) -> Result<(), ContractError> {
+    let response: IsVerifiedResponse = querier.query_wasm_smart(
+        registry,
+        &KycQueryMsg::IsVerified {
+            address: address.to_string(),
+        },
+    )?;
+    if !response.verified {
+        return Err(ContractError::NotVerified {
+            address: address.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Messages gated by a role, recorded in the audit log when they succeed.
+fn is_privileged(msg: &HandleMsg) -> bool {
+    matches!(
+        msg,
+        HandleMsg::Mint { .. }
+            | HandleMsg::MintAndSend { .. }
+            | HandleMsg::MintLocked { .. }
+            | HandleMsg::ImportLegacyBalances { .. }
+            | HandleMsg::ScheduleAdminAction { .. }
+            | HandleMsg::ExecuteAdminAction { .. }
+            | HandleMsg::CancelAdminAction { .. }
+            | HandleMsg::UpdateMultisig { .. }
+            | HandleMsg::AddHook { .. }
+            | HandleMsg::RemoveHook { .. }
+            | HandleMsg::SetReceiveFailureTolerance { .. }
+            | HandleMsg::UpdateExchangeRate { .. }
+            | HandleMsg::Rebase { .. }
+            | HandleMsg::ProposeAction { .. }
+            | HandleMsg::ApproveAction { .. }
+            | HandleMsg::RenounceMinter {}
+            | HandleMsg::ProposeNewMinter { .. }
+            | HandleMsg::AcceptMinter {}
+            | HandleMsg::LockCap { .. }
+            | HandleMsg::UpdateDefaultMintRecipient { .. }
+            | HandleMsg::SetMintAllowlistEnabled { .. }
+            | HandleMsg::AddToMintAllowlist { .. }
+            | HandleMsg::RemoveFromMintAllowlist { .. }
+            | HandleMsg::UpdateMaxReceivePerTx { .. }
+            | HandleMsg::UpdateHolderCap { .. }
+            | HandleMsg::SetSoulbound { .. }
+            | HandleMsg::Revoke { .. }
+            | HandleMsg::ReleaseLocked { .. }
+            | HandleMsg::SetIgnoreUnknownMessages { .. }
+            | HandleMsg::UpdateWhaleThreshold { .. }
+            | HandleMsg::UpdateFreezeGraceSeconds { .. }
+            | HandleMsg::Freeze { .. }
+            | HandleMsg::FreezeAmount { .. }
+            | HandleMsg::Unfreeze { .. }
+            | HandleMsg::FreezeBatch { .. }
+            | HandleMsg::UnfreezeBatch { .. }
+            | HandleMsg::UnfreezeByReason { .. }
+            | HandleMsg::UpdateTransferLimits { .. }
+            | HandleMsg::UpdateRateLimit { .. }
+            | HandleMsg::AddLimitExemption { .. }
+            | HandleMsg::RemoveLimitExemption { .. }
+            | HandleMsg::UpdateKycRegistry { .. }
+            | HandleMsg::AddToAllowlist { .. }
+            | HandleMsg::RemoveFromAllowlist { .. }
+            | HandleMsg::Blacklist { .. }
+            | HandleMsg::Unblacklist { .. }
+            | HandleMsg::Clawback { .. }
+            | HandleMsg::RecoverFunds { .. }
+            | HandleMsg::AddNonCirculating { .. }
+            | HandleMsg::RemoveNonCirculating { .. }
+            | HandleMsg::AddRewardDenom { .. }
+            | HandleMsg::RemoveRewardDenom { .. }
+            | HandleMsg::WatchAccount { .. }
+            | HandleMsg::UnwatchAccount { .. }
+            | HandleMsg::Pause {}
+            | HandleMsg::Unpause {}
+            | HandleMsg::UpdateBurnRate { .. }
+            | HandleMsg::UpdateFlashMintFee { .. }
+            | HandleMsg::UpdateBuyback { .. }
+            | HandleMsg::UpdateStakingConfig { .. }
+            | HandleMsg::CreateVesting { .. }
+            | HandleMsg::RegisterAirdrop { .. }
+            | HandleMsg::ClawbackAirdrop {}
+            | HandleMsg::AddMinter { .. }
+            | HandleMsg::RevokeSpenderAll { .. }
+            | HandleMsg::SecureAccount { .. }
+            | HandleMsg::ClaimTreasury { .. }
+            | HandleMsg::RotateTreasury { .. }
+            | HandleMsg::RemoveMinter { .. }
+            | HandleMsg::SetMinterAllowance { .. }
+            | HandleMsg::SetMintDailyQuota { .. }
+            | HandleMsg::GrantRole { .. }
+            | HandleMsg::RevokeRole { .. }
+    )
+}
+
+/// Reports the fee and burn taken from a transfer, if any. Burns also get
+/// their own `token_movement` event since they change the supply.
+fn charge_attributes(mut response: Response, from: &str, outcome: &TransferOutcome) -> Response {
+    if !outcome.fee.is_zero() {
+        response = response.add_attribute("fee", outcome.fee.to_string());
+    }
+    if !outcome.burned.is_zero() {
+        response = response
+            .add_event(movement_event("burn", Some(from), None, outcome.burned))
+            .add_attribute("amount_sent", outcome.sent.to_string())
+            .add_attribute("amount_burned", outcome.burned.to_string())
+            .add_attribute("amount_received", outcome.received.to_string());
+    }
+    response
+}
+
+fn freeze_record(info: &MessageInfo, env: &Env, reason: &Option<String>) -> FreezeRecord {
+    FreezeRecord {
+        freezer: info.sender.clone(),
+        reason: reason.clone(),
+        height: env.block.height,
+        time: env.block.time.seconds(),
+    }
+}
+
+/// Validates the addresses in `action` before it is scheduled.
+fn validate_admin_action(api: &dyn Api, action: &AdminAction) -> StdResult<AdminAction> {
+    Ok(match action {
+        AdminAction::UpdateMinter { minter } => AdminAction::UpdateMinter {
+            minter: api.addr_validate(minter)?.to_string(),
+        },
+        AdminAction::UpdateFeeConfig {
+            fee_bps,
+            treasury,
+            exempt,
+            tiers,
+        } => {
+            let config = validate_fee_config(api, *fee_bps, treasury, exempt, tiers)?;
+            AdminAction::UpdateFeeConfig {
+                fee_bps: config.fee_bps,
+                treasury: config.treasury,
+                exempt: config.exempt,
+                tiers: config.tiers,
+            }
+        }
+        AdminAction::IncreaseCap { .. } | AdminAction::UpdateTimelockDelay { .. } => action.clone(),
+    })
+}
+
+/// Reports the approvals of a multisig proposal, carrying it out once it is
+/// ready.
+fn multisig_response(
+    state: &mut State,
+    action: &str,
+    proposal: MultisigProposal,
+    ready: bool,
+    block: &BlockInfo,
+) -> Result<Response, ContractError> {
+    let response = Response::new()
+        .add_attribute("action", action)
+        .add_attribute("id", proposal.id.to_string())
+        .add_attribute("approvals", proposal.approvals.len().to_string())
+        .add_attribute("executed", ready.to_string());
+    if !ready {
+        return Ok(response);
+    }
+    match proposal.action {
+        MultisigAction::Mint { recipient, amount } => {
+            state.ensure_mint_window_open(block)?;
+            state.mint(&recipient, amount, block.height)?;
+            Ok(response
+                .add_event(movement_event("mint", None, Some(&recipient), amount))
+                .add_attribute("to", recipient)
+                .add_attribute("amount", amount.to_string()))
+        }
+    }
+}
+
+/// Carries out an admin action that has waited out the timelock.
+fn apply_admin_action(
+    state: &mut State,
+    action: AdminAction,
+    response: Response,
+) -> Result<Response, ContractError> {
+    match action {
+        AdminAction::UpdateMinter { minter } => {
+            state.update_minter(minter.clone())?;
+            Ok(response
+                .add_attribute("operation", "update_minter")
+                .add_attribute("minter", minter))
+        }
+        AdminAction::UpdateFeeConfig {
+            fee_bps,
+            treasury,
+            exempt,
+            tiers,
+        } => {
+            state.update_fee_config(FeeConfig {
+                fee_bps,
+                treasury: treasury.clone(),
+                exempt,
+                tiers,
+            })?;
+            Ok(response
+                .add_attribute("operation", "update_fee_config")
+                .add_attribute("fee_bps", fee_bps.to_string())
+                .add_attribute("treasury", treasury))
+        }
+        AdminAction::IncreaseCap { new_cap } => {
+            let previous = state.increase_cap(new_cap)?;
+            Ok(response
+                .add_event(
+                    Event::new("cap_increased")
+                        .add_attribute("previous_cap", previous.to_string())
+                        .add_attribute("cap", new_cap.to_string()),
+                )
+                .add_attribute("operation", "increase_cap")
+                .add_attribute("cap", new_cap.to_string()))
+        }
+        AdminAction::UpdateTimelockDelay { seconds } => {
+            state.update_timelock_delay(seconds)?;
+            Ok(response
+                .add_attribute("operation", "update_timelock_delay")
+                .add_attribute("delay", seconds.to_string()))
+        }
+    }
+}
+
+fn validate_fee_config(
+    api: &dyn Api,
+    fee_bps: u16,
+    treasury: &str,
+    exempt: &[String],
+    tiers: &[FeeTier],
+) -> StdResult<FeeConfig> {
+    Ok(FeeConfig {
+        fee_bps,
+        treasury: api.addr_validate(treasury)?.to_string(),
+        exempt: exempt
+            .iter()
+            .map(|address| api.addr_validate(address).map(|a| a.to_string()))
+            .collect::<StdResult<_>>()?,
+        tiers: tiers.to_vec(),
+    })
+}
+
+/// Adds `memo`, if any, to the attributes of `response`, rejecting memos
+/// longer than `MAX_MEMO_LENGTH` bytes.
+fn with_memo(response: Response, memo: &Option<String>) -> Result<Response, ContractError> {
+    match memo {
+        Some(memo) if memo.len() > MAX_MEMO_LENGTH => Err(ContractError::MemoTooLong {
+            max: MAX_MEMO_LENGTH,
+        }),
+        Some(memo) => Ok(response.add_attribute("memo", memo)),
+        None => Ok(response),
+    }
+}
+
+/// Calls `Receive` on `contract` for `amount` it was just given by `sender`.
+/// When the contract's failure is tolerated the call replies on error, so
+/// `reply` can return the tokens to `refund_to`, or burn them when they were
+/// minted.
+fn receive_message(
+    state: &mut State,
+    contract: &str,
+    sender: &str,
+    amount: Uint128,
+    msg: &Binary,
+    refund_to: Option<&str>,
+    height: u64,
+) -> Result<SubMsg, ContractError> {
+    let receive = Cw20ReceiveMsg {
+        sender: sender.to_string(),
+        amount,
+        msg: msg.clone(),
+    }
+    .into_cosmos_msg(contract.to_string())?;
+    if !state.read().receive_failure_tolerated(contract)? {
+        return Ok(SubMsg::new(receive));
+    }
+    let kind = ReplyKind::Receive {
+        contract: contract.to_string(),
+        refund_to: refund_to.map(str::to_string),
+        amount,
+    };
+    Ok(SubMsg::reply_on_error(
+        receive,
+        state.push_reply_context(kind, height)?,
+    ))
+}
+
+/// Notifies each hook of every `token_movement` event in `response`.
+/// Notifications of hooks that tolerate failure reply on error, so `reply`
+/// can swallow it.
+fn hook_messages(
+    state: &mut State,
+    response: &Response,
+    height: u64,
+) -> Result<Vec<SubMsg>, ContractError> {
+    let hooks = state.read().hooks()?;
+    if hooks.is_empty() {
+        return Ok(vec![]);
+    }
+    let mut messages = vec![];
+    for event in response
+        .events
+        .iter()
+        .filter(|event| event.ty == "token_movement")
+    {
+        let attribute = |key: &str| {
+            event
+                .attributes
+                .iter()
+                .find(|attribute| attribute.key == key)
+                .map(|attribute| attribute.value.clone())
+        };
+        let notification = to_binary(&TokenHookMsg::TokenMovement {
+            action: attribute("action").unwrap_or_default(),
+            from: attribute("from"),
+            to: attribute("to"),
+            amount: attribute("amount")
+                .unwrap_or_default()
+                .parse::<u128>()
+                .map_err(|err| StdError::generic_err(err.to_string()))?
+                .into(),
+        })?;
+        for hook in &hooks {
+            let notify = WasmMsg::Execute {
+                contract_addr: hook.address.clone(),
+                msg: notification.clone(),
+                funds: vec![],
+            };
+            messages.push(if hook.tolerate_failure {
+                let kind = ReplyKind::Hook {
+                    hook: hook.address.clone(),
+                };
+                SubMsg::reply_on_error(notify, state.push_reply_context(kind, height)?)
+            } else {
+                SubMsg::new(notify)
+            });
+        }
+    }
+    Ok(messages)
+}
+
+/// Builds the `token_movement` event emitted next to the attributes of every
+/// branch that changes balances, so indexers can follow supply and transfers
+/// from a single event type.
+pub(crate) fn movement_event(
+    action: &str,
+    from: Option<&str>,
+    to: Option<&str>,
+    amount: Uint128,
+) -> Event {
+    let mut event = Event::new("token_movement").add_attribute("action", action);
+    if let Some(from) = from {
+        event = event.add_attribute("from", from);
+    }
+    if let Some(to) = to {
+        event = event.add_attribute("to", to);
+    }
+    event.add_attribute("amount", amount.to_string())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let state = ReadonlyState::new(deps.storage);
+    match msg {
+        QueryMsg::Balance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&BalanceResponse {
+                balance: state.balance(address.as_str())?,
+            })
+        }
+        QueryMsg::Balances { addresses } => {
+            if addresses.len() > MAX_BALANCES_QUERY {
+                return Err(StdError::generic_err(format!(
+                    "Cannot query more than {} balances at once",
+                    MAX_BALANCES_QUERY
+                )));
+            }
+            let balances = addresses
+                .into_iter()
+                .map(|address| {
+                    let address = deps.api.addr_validate(&address)?;
+                    Ok(Cw20Coin {
+                        amount: state.balance(address.as_str())?,
+                        address: address.into_string(),
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&BalancesResponse { balances })
+        }
+        QueryMsg::BalanceAt { address, height } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&BalanceResponse {
+                balance: state.balance_at(address.as_str(), height)?,
+            })
+        }
+        QueryMsg::TotalSupplyAt { height } => to_binary(&TotalSupplyResponse {
+            total_supply: state.total_supply_at(height)?,
+        }),
+        QueryMsg::VotingPowerAt { address, height } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&VotingPowerResponse {
+                power: state.voting_power_at(address.as_str(), height)?,
+            })
+        }
+        QueryMsg::TokenInfo {} => to_binary(&state.token_info()?),
+        QueryMsg::Minter {} => to_binary(&state.minter()?),
+        QueryMsg::Hooks {} => to_binary(&HooksResponse {
+            hooks: state.hooks()?,
+        }),
+        QueryMsg::ReceiveFailureTolerated { contract } => {
+            to_binary(&ReceiveFailureToleratedResponse {
+                tolerated: state.receive_failure_tolerated(&contract)?,
+            })
+        }
+        QueryMsg::Nonce { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&NonceResponse {
+                nonce: state.nonce(address.as_str())?,
+            })
+        }
+        QueryMsg::MintWindow {} => {
+            let deadline = state.mint_deadline()?;
+            to_binary(&MintWindowResponse {
+                open: !deadline.map_or(false, |deadline| deadline.is_expired(&env.block)),
+                deadline,
+            })
+        }
+        QueryMsg::CurveInfo {} => {
+            let config = state.bonding_curve()?;
+            let supply = state.curve_supply()?;
+            let spot_price = match &config {
+                Some(config) => Some(
+                    curve::spot_price(&config.curve, supply.u128())
+                        .ok_or_else(|| StdError::generic_err("Curve price overflowed"))?,
+                ),
+                None => None,
+            };
+            to_binary(&CurveInfoResponse {
+                curve: config,
+                reserve: state.curve_reserve()?,
+                supply,
+                spot_price,
+            })
+        }
+        QueryMsg::ExchangeRate {} => {
+            let total_shares = state.total_supply()?;
+            to_binary(&ExchangeRateResponse {
+                rate: state.exchange_rate()?,
+                total_shares,
+                total_value: state.share_value(total_shares)?,
+            })
+        }
+        QueryMsg::Shares { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let shares = state.balance(address.as_str())?;
+            to_binary(&SharesResponse {
+                shares,
+                value: state.share_value(shares)?,
+            })
+        }
+        QueryMsg::InflationInfo {} => {
+            let next = state.next_inflation()?;
+            to_binary(&InflationInfoResponse {
+                config: state.inflation()?,
+                epochs_minted: state.inflation_epochs()?.minted,
+                next_epoch_at: next.map(|(at, _)| at),
+                next_amount: next.map(|(_, amount)| amount),
+            })
+        }
+        QueryMsg::Multisig { start_after, limit } => to_binary(&MultisigResponse {
+            config: state.multisig()?,
+            proposals: state.multisig_proposals(start_after, limit)?,
+        }),
+        QueryMsg::PendingAdminActions { start_after, limit } => {
+            to_binary(&PendingAdminActionsResponse {
+                delay: state.timelock_delay()?,
+                actions: state.scheduled_actions(start_after, limit)?,
+            })
+        }
+        QueryMsg::Minters { start_after, limit } => to_binary(&MintersResponse {
+            minters: state
+                .minters(start_after.as_deref(), limit)?
+                .into_iter()
+                .map(|(address, info)| MinterEntry {
+                    address,
+                    allowance: info.allowance,
+                })
+                .collect(),
+        }),
+        QueryMsg::Frozen { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&FrozenResponse {
+                frozen: state.is_frozen(address.as_str(), &env.block)?,
+            })
+        }
+        QueryMsg::FrozenAmount { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&FrozenAmountResponse {
+                amount: state.frozen_amount(address.as_str(), &env.block)?,
+            })
+        }
+        QueryMsg::TransferLimits {} => to_binary(&state.transfer_limits()?),
+        QueryMsg::SpendLimit { owner, spender } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            let spender = deps.api.addr_validate(&spender)?;
+            let quota =
+                state.spend_quota(owner.as_str(), spender.as_str(), env.block.time.seconds())?;
+            to_binary(&SpendLimitResponse {
+                limit: state.spend_limit(owner.as_str(), spender.as_str())?,
+                remaining: quota.map(|(remaining, _)| remaining),
+                resets_at: quota.map(|(_, resets_at)| resets_at),
+            })
+        }
+        QueryMsg::TransferQuota { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let quota = state.transfer_quota(address.as_str(), env.block.time.seconds())?;
+            to_binary(&TransferQuotaResponse {
+                remaining: quota.map(|(remaining, _)| remaining),
+                resets_at: quota.map(|(_, resets_at)| resets_at),
+            })
+        }
+        QueryMsg::LimitExempt { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&LimitExemptResponse {
+                exempt: state.limit_exempt(address.as_str())?,
+            })
+        }
+        QueryMsg::KycRegistry {} => to_binary(&KycRegistryResponse {
+            registry: state.kyc_registry()?,
+        }),
+        QueryMsg::Allowlist { start_after, limit } => to_binary(&AllowlistResponse {
+            permissioned: state.permissioned()?,
+            addresses: state.allowlist(start_after.as_deref(), limit)?,
+        }),
+        QueryMsg::Blacklisted { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&BlacklistedResponse {
+                blacklisted: state.is_blacklisted(address.as_str())?,
+            })
+        }
+        QueryMsg::FreezeInfo { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&FreezeInfoResponse {
+                freeze: state.freeze_info(address.as_str())?,
+                record: state.freeze_record(address.as_str())?,
+            })
+        }
+        QueryMsg::AuditLog { start_after, limit } => to_binary(&AuditLogResponse {
+            entries: state.audit_log(start_after, limit)?,
+        }),
+        QueryMsg::RecentActivity { address, limit } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&RecentActivityResponse {
+                entries: state.recent_activity(address.as_str(), limit)?,
+            })
+        }
+        QueryMsg::AllFrozenAccounts { start_after, limit } => {
+            let accounts = state
+                .all_frozen_accounts(&env.block, start_after.as_deref(), limit)?
+                .into_iter()
+                .map(|(address, freeze)| FrozenAccount {
+                    address,
+                    amount: freeze.amount,
+                    active_from: freeze.active_from,
+                    expires: freeze.expires,
+                })
+                .collect();
+            to_binary(&AllFrozenAccountsResponse { accounts })
+        }
+        QueryMsg::AllAccounts { start_after, limit } => to_binary(&AllAccountsResponse {
+            accounts: state.all_accounts(start_after.as_deref(), limit)?,
+        }),
+        QueryMsg::Allowance { owner, spender } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            let spender = deps.api.addr_validate(&spender)?;
+            to_binary(&state.active_allowance(owner.as_str(), spender.as_str(), &env.block)?)
+        }
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let owner = deps.api.addr_validate(&owner)?;
+            to_binary(&AllAllowancesResponse {
+                allowances: state.all_allowances(owner.as_str(), start_after.as_deref(), limit)?,
+            })
+        }
+        QueryMsg::ExportState { start_after, limit } => {
+            let accounts = state
+                .all_accounts(start_after.as_deref(), limit)?
+                .into_iter()
+                .map(|address| {
+                    let (allowances, more_allowances) = state.allowances_granted(&address)?;
+                    Ok(AccountExport {
+                        balance: state.balance(&address)?,
+                        frozen: state.freeze_info(&address)?,
+                        allowances,
+                        more_allowances,
+                        address,
+                    })
+                })
+                .collect::<StdResult<Vec<_>>>()?;
+            to_binary(&ExportStateResponse { accounts })
+        }
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => {
+            let spender = deps.api.addr_validate(&spender)?;
+            to_binary(&AllSpenderAllowancesResponse {
+                allowances: state.all_spender_allowances(
+                    spender.as_str(),
+                    start_after.as_deref(),
+                    limit,
+                )?,
+            })
+        }
+        QueryMsg::PreviewTransfer { from, to, amount } => {
+            let from = deps.api.addr_validate(&from)?;
+            let to = deps.api.addr_validate(&to)?;
+            let (outcome, blocked) =
+                match state.preview_transfer(from.as_str(), to.as_str(), amount, &env.block) {
+                    Ok(outcome) => (outcome, None),
+                    Err(error) => (TransferOutcome::default(), Some(error.to_string())),
+                };
+            let mut from_balance = state.balance(from.as_str())?;
+            let mut to_balance = state.balance(to.as_str())?;
+            if from != to {
+                from_balance -= outcome.sent;
+                to_balance += outcome.received;
+            }
+            to_binary(&PreviewTransferResponse {
+                received: outcome.received,
+                fee: outcome.fee,
+                burned: outcome.burned,
+                from_balance,
+                to_balance,
+                blocked,
+            })
+        }
+        QueryMsg::HealthCheck {} => {
+            let supply = state.total_supply()?;
+            let reserve_backs_supply = match state.wrapped_denom()? {
+                Some(denom) => {
+                    deps.querier
+                        .query_balance(env.contract.address.as_str(), denom)?
+                        .amount
+                        >= supply
+                }
+                None => true,
+            };
+            to_binary(&HealthCheckResponse {
+                supply_le_cap: state.cap()?.map_or(true, |cap| supply <= cap),
+                reserve_backs_supply,
+                frozen_le_supply: state.frozen_total()? <= supply,
+            })
+        }
+        QueryMsg::ExpiringAllowances {
+            within_seconds,
+            start_after,
+            limit,
+        } => {
+            let deadline = env.block.time.plus_seconds(within_seconds);
+            to_binary(&allowance_entries(
+                &state,
+                start_after,
+                limit,
+                |allowance| match allowance.expires {
+                    Expiration::AtTime(time) => time > env.block.time && time <= deadline,
+                    _ => false,
+                },
+            )?)
+        }
+        QueryMsg::StaleAllowances { start_after, limit } => to_binary(&allowance_entries(
+            &state,
+            start_after,
+            limit,
+            |allowance| allowance.allowance.is_zero() || allowance.expires.is_expired(&env.block),
+        )?),
+        QueryMsg::MarketingInfo {} => to_binary(&state.marketing_info()?),
+        QueryMsg::DownloadLogo {} => to_binary(&state.download_logo()?),
+        QueryMsg::FeeConfig {} => to_binary(&state.fee_config()?),
+        QueryMsg::HolderCap {} => to_binary(&state.holder_cap()?),
+        QueryMsg::Treasury {} => {
+            let buyback = state.buyback()?;
+            let balance = match &buyback {
+                Some(config) => Some(Coin::new(
+                    state.treasury(&config.denom)?.u128(),
+                    &config.denom,
+                )),
+                None => None,
+            };
+            to_binary(&TreasuryResponse { buyback, balance })
+        }
+        QueryMsg::FlashMintFee {} => to_binary(&FlashMintFeeResponse {
+            fee_bps: state.flash_mint_fee_bps()?,
+        }),
+        QueryMsg::BurnRate {} => to_binary(&BurnRateResponse {
+            burn_bps: state.burn_bps()?,
+        }),
+        QueryMsg::ChannelInfo { start_after, limit } => to_binary(&ChannelInfoResponse {
+            channels: state
+                .channels(start_after.as_deref(), limit)?
+                .into_iter()
+                .map(|(info, escrowed)| ChannelEscrow { info, escrowed })
+                .collect(),
+        }),
+        QueryMsg::CirculatingSupply {} => to_binary(&CirculatingSupplyResponse {
+            circulating_supply: state.circulating_supply()?,
+            total_supply: state.total_supply()?,
+            non_circulating: state.non_circulating()?,
+        }),
+        QueryMsg::HolderCount {} => to_binary(&HolderCountResponse {
+            count: state.holder_count()?,
+        }),
+        QueryMsg::TopHolders { limit } => to_binary(&TopHoldersResponse {
+            holders: state
+                .top_holders(limit)?
+                .into_iter()
+                .map(|(address, amount)| Cw20Coin { address, amount })
+                .collect(),
+        }),
+        QueryMsg::WrappedDenom {} => to_binary(&WrappedDenomResponse {
+            denom: state.wrapped_denom()?,
+        }),
+        QueryMsg::Escrow { id } => to_binary(&state.escrow(id)?),
+        QueryMsg::Swap { id } => to_binary(&state.swap(id)?),
+        QueryMsg::Stream { id } => {
+            let stream = state.stream(id)?;
+            to_binary(&StreamResponse {
+                withdrawable: stream.withdrawable_at(env.block.time.seconds()),
+                stream,
+            })
+        }
+        QueryMsg::Subscription { id } => to_binary(&state.subscription(id)?),
+        QueryMsg::SubscriptionsBySubscriber {
+            subscriber,
+            start_after,
+            limit,
+        } => {
+            let subscriber = deps.api.addr_validate(&subscriber)?;
+            to_binary(&SubscriptionsResponse {
+                subscriptions: state.subscriptions_by_subscriber(
+                    subscriber.as_str(),
+                    start_after,
+                    limit,
+                )?,
+            })
+        }
+        QueryMsg::SubscriptionsByMerchant {
+            merchant,
+            start_after,
+            limit,
+        } => {
+            let merchant = deps.api.addr_validate(&merchant)?;
+            to_binary(&SubscriptionsResponse {
+                subscriptions: state.subscriptions_by_merchant(
+                    merchant.as_str(),
+                    start_after,
+                    limit,
+                )?,
+            })
+        }
+        QueryMsg::StreamsBySender {
+            sender,
+            start_after,
+            limit,
+        } => {
+            let sender = deps.api.addr_validate(&sender)?;
+            to_binary(&StreamsResponse {
+                streams: state.streams_by_sender(sender.as_str(), start_after, limit)?,
+            })
+        }
+        QueryMsg::StreamsByRecipient {
+            recipient,
+            start_after,
+            limit,
+        } => {
+            let recipient = deps.api.addr_validate(&recipient)?;
+            to_binary(&StreamsResponse {
+                streams: state.streams_by_recipient(recipient.as_str(), start_after, limit)?,
+            })
+        }
+        QueryMsg::LockedTransfers {
+            address,
+            start_after,
+            limit,
+        } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&LockedTransfersResponse {
+                transfers: state.locked_transfers(address.as_str(), start_after, limit)?,
+            })
+        }
+        QueryMsg::StakedBalance { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&StakedBalanceResponse {
+                staked: state.staked(address.as_str())?,
+                unbonding: state.unbonding(address.as_str())?,
+            })
+        }
+        QueryMsg::PendingRewards { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let (rewards, _) = state.pending_rewards(address.as_str(), env.block.time.seconds())?;
+            to_binary(&PendingRewardsResponse {
+                rewards,
+                dividends: state.pending_dividends(address.as_str())?,
+            })
+        }
+        QueryMsg::RewardDenoms {} => to_binary(&RewardDenomsResponse {
+            denoms: state.reward_denoms()?,
+        }),
+        QueryMsg::VestingInfo { address } => {
+            let address = deps.api.addr_validate(&address)?;
+            let schedule = state.vesting(address.as_str())?;
+            let (vested, claimable) = schedule.as_ref().map_or(Default::default(), |schedule| {
+                let vested = schedule.vested_at(env.block.time.seconds());
+                (vested, vested - schedule.released)
+            });
+            to_binary(&VestingInfoResponse {
+                schedule,
+                vested,
+                claimable,
+            })
+        }
+        QueryMsg::Airdrop {} => to_binary(&state.airdrop()?),
+        QueryMsg::PendingMinter {} => to_binary(&PendingMinterResponse {
+            pending_minter: state.pending_minter()?,
+        }),
+        QueryMsg::Roles {} => to_binary(&RolesResponse {
+            admins: state.role_holders(Role::Admin)?,
+            minter: state.minter()?.map(|minter| minter.minter),
+            pending_minter: state.pending_minter()?,
+        }),
+    }
+}
+
+/// The allowances `matches` keeps among up to `limit` after `start_after`.
+fn allowance_entries(
+    state: &ReadonlyState,
+    start_after: Option<AllowanceKey>,
+    limit: Option<u32>,
+    matches: impl Fn(&AllowanceResponse) -> bool,
+) -> StdResult<AllowanceEntriesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let page = state.allowances_page(
+        start_after
+            .as_ref()
+            .map(|key| (key.owner.as_str(), key.spender.as_str())),
+        Some(limit),
+    )?;
+    let last = match page.last() {
+        Some(((owner, spender), _)) if page.len() == limit as usize => Some(AllowanceKey {
+            owner: owner.clone(),
+            spender: spender.clone(),
+        }),
+        _ => None,
+    };
+    let allowances = page
+        .into_iter()
+        .filter(|(_, allowance)| matches(allowance))
+        .map(|((owner, spender), allowance)| AllowanceEntry {
+            owner,
+            spender,
+            allowance: allowance.allowance,
+            expires: allowance.expires,
+        })
+        .collect();
+    Ok(AllowanceEntriesResponse { allowances, last })
+}
+
+/// Handles the replies of submessages that stored a reply context. A flash
+/// mint is checked for repayment once the borrower's call succeeded. The rest
+/// were dispatched with `reply_on_error`: a failed hook notification is only
+/// reported; a failed `Receive` returns the tokens given to the contract.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut state = State::new(deps.storage);
+    let context = state.take_reply_context(msg.id)?;
+    let error = match msg.result {
+        SubMsgResult::Ok(_) => None,
+        SubMsgResult::Err(error) => Some(error),
+    };
+    match (context.kind, error) {
+        (
+            ReplyKind::FlashMint {
+                borrower,
+                amount,
+                fee,
+                supply_before,
+            },
+            _,
+        ) => {
+            state.ensure_flash_mint_repaid(supply_before, fee)?;
+            Ok(Response::new()
+                .add_attribute("action", "flash_mint_repaid")
+                .add_attribute("from", borrower)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("fee", fee.to_string()))
+        }
+        (
+            ReplyKind::Buyback {
+                caller,
+                spent,
+                balance_before,
+                bounty_bps,
+            },
+            _,
+        ) => {
+            let contract = env.contract.address.as_str();
+            let bought = state
+                .read()
+                .balance(contract)?
+                .saturating_sub(balance_before);
+            let bounty = bps_of(bought, bounty_bps)?;
+            let burned = bought - bounty;
+            let mut response = Response::new();
+            if !bounty.is_zero() {
+                state.transfer(contract, &caller, bounty, &env.block)?;
+                response = response.add_event(movement_event(
+                    "buyback_bounty",
+                    Some(contract),
+                    Some(&caller),
+                    bounty,
+                ));
+            }
+            if !burned.is_zero() {
+                state.burn(contract, burned, &env.block)?;
+                response = response.add_event(movement_event("burn", Some(contract), None, burned));
+            }
+            Ok(response
+                .add_event(
+                    Event::new("buyback_burn")
+                        .add_attribute("spent", spent.to_string())
+                        .add_attribute("bought", bought.to_string())
+                        .add_attribute("burned", burned.to_string())
+                        .add_attribute("bounty", bounty.to_string())
+                        .add_attribute("caller", caller),
+                )
+                .add_attribute("action", "buyback_burned")
+                .add_attribute("amount", burned.to_string()))
+        }
+        (_, None) => Ok(Response::new()),
+        (ReplyKind::Hook { hook }, Some(error)) => Ok(Response::new()
+            .add_attribute("action", "hook_failed")
+            .add_attribute("hook", hook)
+            .add_attribute("error", error)),
+        (
+            ReplyKind::Receive {
+                contract,
+                refund_to,
+                amount,
+            },
+            Some(error),
+        ) => {
+            state.return_failed_receive(
+                &contract,
+                refund_to.as_deref(),
+                amount,
+                env.block.height,
+            )?;
+            let event = match &refund_to {
+                Some(owner) => movement_event("refund", Some(&contract), Some(owner), amount),
+                None => movement_event("burn", Some(&contract), None, amount),
+            };
+            Ok(Response::new()
+                .add_event(event)
+                .add_attribute("action", "receive_failed")
+                .add_attribute("contract", contract)
+                .add_attribute("amount", amount.to_string())
+                .add_attribute("error", error))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    if let MigrateMsg::FromCw20Base { limit } = msg {
+        // the cw2 name stays that of cw20-base until the last batch is moved,
+        // so the migration can be run again to resume
+        let stored = get_contract_version(deps.storage)?;
+        if stored.contract != CW20_BASE_NAME {
+            return Err(ContractError::CannotMigrate {
+                previous_contract: stored.contract,
+            });
+        }
+        let (balances, allowances, done) =
+            migrate_from_cw20_base(deps.storage, env.block.height, limit)?;
+        let response = if done {
+            upgrade(deps)?
+        } else {
+            Response::new().add_attribute("action", "migrate")
+        };
+        return Ok(response
+            .add_attribute("migrated_balances", balances.to_string())
+            .add_attribute("migrated_allowances", allowances.to_string())
+            .add_attribute("done", done.to_string()));
+    }
+    // Deployments that predate cw2 have no stored version and still use the
+    // singleton-based layout, so convert them before recording a version.
+    let converted = match get_contract_version(deps.storage) {
+        Ok(stored) => {
+            if stored.contract != CONTRACT_NAME {
+                return Err(ContractError::CannotMigrate {
+                    previous_contract: stored.contract,
+                });
+            }
+            let previous = stored.version.parse::<Version>()?;
+            let current = CONTRACT_VERSION.parse::<Version>()?;
+            if previous > current {
+                return Err(ContractError::CannotMigrateVersion {
+                    previous_version: stored.version,
+                });
+            }
+            0
+        }
+        Err(_) => migrate_singleton_layout(deps.storage)?,
+    };
+    Ok(upgrade(deps)?.add_attribute("converted_keys", converted.to_string()))
+}
+
+/// Brings storage that already uses this layout up to date and records the
+/// current version.
+fn upgrade(deps: DepsMut) -> Result<Response, ContractError> {
+    let converted_freezes = migrate_frozen_balances(deps.storage)?;
+    let moved_minters = migrate_minter_roles(deps.storage)?;
+    let seeded_roles = State::new(deps.storage).seed_roles_from_minter()?;
+    let indexed_holders = index_holders(deps.storage)?;
+    index_total_staked(deps.storage)?;
+    let indexed_allowances = index_spender_allowances(deps.storage)?;
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("seeded_roles", seeded_roles.to_string())
+        .add_attribute("converted_freezes", converted_freezes.to_string())
+        .add_attribute("moved_minters", moved_minters.to_string())
+        .add_attribute("indexed_holders", indexed_holders.to_string())
+        .add_attribute("indexed_allowances", indexed_allowances.to_string())
+        .add_attribute("version", CONTRACT_VERSION))
+}
@@ -0,0 +1,142 @@
+//! Bonding-curve pricing for `Buy` and `Sell`.
+//!
+//! Prices are in reserve units per token unit, both in their smallest
+//! denomination, and depend only on the supply issued through the curve. The
+//! reserve for a trade is the integral of the price over the supply it moves
+//! across, rounded up when buying and down when selling, so the reserve always
+//! covers selling the whole curve supply back. `None` means the arithmetic
+//! overflowed.
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint256};
+
+use crate::state::CurveType;
+
+/// Price of the next token unit at `supply`.
+pub fn spot_price(curve: &CurveType, supply: u128) -> Option<Decimal256> {
+    match curve {
+        CurveType::Linear { slope, intercept } => widen(*slope)?
+            .checked_mul(units(supply)?)
+            .ok()?
+            .checked_add(widen(*intercept)?)
+            .ok(),
+        CurveType::Exponential { base, growth, step } => {
+            step_price(*base, *growth, supply / step.u128())
+        }
+    }
+}
+
+/// Reserve to pay for `amount` tokens bought at `supply`.
+pub fn buy_cost(curve: &CurveType, supply: u128, amount: u128) -> Option<u128> {
+    let to = supply.checked_add(amount)?;
+    let atomics = integral(curve, supply, to)?.atomics();
+    let one = Decimal256::one().atomics();
+    let ceil = atomics.checked_add(one - Uint256::one()).ok()? / one;
+    Uint128::try_from(ceil).ok().map(Uint128::u128)
+}
+
+/// Reserve returned for `amount` tokens sold back at `supply`.
+pub fn sell_refund(curve: &CurveType, supply: u128, amount: u128) -> Option<u128> {
+    let from = supply.checked_sub(amount)?;
+    let floor = integral(curve, from, supply)?.atomics() / Decimal256::one().atomics();
+    Uint128::try_from(floor).ok().map(Uint128::u128)
+}
+
+/// Most tokens `reserve` buys at `supply`.
+pub fn tokens_for(curve: &CurveType, supply: u128, reserve: u128) -> u128 {
+    let affordable =
+        |amount: u128| buy_cost(curve, supply, amount).map_or(false, |cost| cost <= reserve);
+    // grow an upper bound, then bisect below it
+    let mut high = 1u128;
+    while affordable(high) {
+        match high.checked_mul(2) {
+            Some(next) => high = next,
+            None => return high,
+        }
+    }
+    let mut low = 0u128;
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if affordable(middle) {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Area under the price between supply `from` and `to`.
+fn integral(curve: &CurveType, from: u128, to: u128) -> Option<Decimal256> {
+    match curve {
+        CurveType::Linear { slope, intercept } => {
+            let span = units(to - from)?;
+            // slope * (to² - from²) / 2, with to² - from² = (to - from)(to + from)
+            let area = widen(*slope)?
+                .checked_mul(span)
+                .ok()?
+                .checked_mul(units(to.checked_add(from)?)?)
+                .ok()?
+                .checked_div(Decimal256::from_atomics(2u128, 0).ok()?)
+                .ok()?;
+            widen(*intercept)?
+                .checked_mul(span)
+                .ok()?
+                .checked_add(area)
+                .ok()
+        }
+        CurveType::Exponential { base, growth, step } => {
+            // the price is flat within each step: part of the first step,
+            // the whole steps in between as a geometric series, and part of
+            // the last step
+            let step = step.u128();
+            let (first, last) = (from / step, to / step);
+            if first == last {
+                return step_price(*base, *growth, first)?
+                    .checked_mul(units(to - from)?)
+                    .ok();
+            }
+            let head = step_price(*base, *growth, first)?
+                .checked_mul(units((first + 1).checked_mul(step)? - from)?)
+                .ok()?;
+            let tail = step_price(*base, *growth, last)?
+                .checked_mul(units(to - last * step)?)
+                .ok()?;
+            let steps = if growth.is_zero() {
+                units(last - first - 1)?
+            } else {
+                let ratio = widen(*growth)?.checked_add(Decimal256::one()).ok()?;
+                ratio
+                    .checked_pow(u32::try_from(last).ok()?)
+                    .ok()?
+                    .checked_sub(ratio.checked_pow(u32::try_from(first + 1).ok()?).ok()?)
+                    .ok()?
+                    .checked_div(widen(*growth)?)
+                    .ok()?
+            };
+            let middle = widen(*base)?
+                .checked_mul(units(step)?)
+                .ok()?
+                .checked_mul(steps)
+                .ok()?;
+            head.checked_add(middle).ok()?.checked_add(tail).ok()
+        }
+    }
+}
+
+/// Price within step `index` of an exponential curve.
+fn step_price(base: Decimal, growth: Decimal, index: u128) -> Option<Decimal256> {
+    let factor = widen(growth)?
+        .checked_add(Decimal256::one())
+        .ok()?
+        .checked_pow(u32::try_from(index).ok()?)
+        .ok()?;
+    widen(base)?.checked_mul(factor).ok()
+}
+
+fn units(amount: u128) -> Option<Decimal256> {
+    Decimal256::from_atomics(amount, 0).ok()
+}
+
+fn widen(value: Decimal) -> Option<Decimal256> {
+    Decimal256::from_atomics(value.atomics(), value.decimal_places()).ok()
+}
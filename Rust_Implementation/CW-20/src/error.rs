@@ -0,0 +1,363 @@
+use cosmwasm_std::{StdError, Uint128};
+use cw20::Expiration;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Insufficient funds: available {available}, required {required}")]
+    InsufficientFunds {
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Account {address} is frozen")]
+    AccountFrozen { address: String },
+
+    #[error("Account {address} is blacklisted")]
+    AccountBlacklisted { address: String },
+
+    #[error("Account {address} is not frozen")]
+    AccountNotFrozen { address: String },
+
+    #[error("A reason is required")]
+    MissingReason {},
+
+    #[error("Cannot exceed the minter cap")]
+    CapExceeded {},
+
+    #[error("Minter can only mint {remaining} more")]
+    MintAllowanceExceeded { remaining: Uint128 },
+
+    #[error("Minter can only mint {remaining} more until {resets_at}")]
+    MintQuotaExceeded { remaining: Uint128, resets_at: u64 },
+
+    #[error("Cannot hold more than the maximum wallet balance of {max}")]
+    MaxWalletBalanceExceeded { max: Uint128 },
+
+    #[error("Cannot transfer more than {max} at once")]
+    MaxTransferExceeded { max: Uint128 },
+
+    #[error("Transfer exceeds the remaining quota of {remaining} for this window")]
+    RateLimitExceeded { remaining: Uint128 },
+
+    #[error("Rate limit window must be longer than zero seconds")]
+    InvalidRateLimit {},
+
+    #[error("Spender may only move {remaining} more this period")]
+    SpendLimitExceeded { remaining: Uint128 },
+
+    #[error("Cap is locked")]
+    CapLocked {},
+
+    #[error("Minting has been renounced")]
+    MintingRenounced {},
+
+    #[error("Minting closed at {deadline}")]
+    MintWindowClosed { deadline: Expiration },
+
+    #[error("New cap must be above the current cap")]
+    InvalidCapIncrease {},
+
+    #[error("Scheduled action can only be executed at {executable_at}")]
+    ActionNotReady { executable_at: u64 },
+
+    #[error("Threshold must be between 1 and the number of signers")]
+    InvalidThreshold {},
+
+    #[error("No multisig is configured")]
+    NoMultisig {},
+
+    #[error("Signer has already approved this action")]
+    AlreadyApproved {},
+
+    #[error("Inflation needs a non-zero epoch length and a rate of at most 1")]
+    InvalidInflation {},
+
+    #[error("Exchange rate must be greater than zero")]
+    InvalidExchangeRate {},
+
+    #[error("Rebase needs a non-zero numerator and denominator")]
+    InvalidRebase {},
+
+    #[error("Inflation is not enabled")]
+    InflationDisabled {},
+
+    #[error("Next epoch begins at {next_epoch_at}")]
+    EpochNotStarted { next_epoch_at: u64 },
+
+    #[error("Arithmetic overflow")]
+    Overflow {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Token is non-transferable")]
+    NonTransferable {},
+
+    #[error("Locked tokens cannot be moved: transferable {transferable}, required {required}")]
+    LockedTokens {
+        transferable: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Cannot receive more than {max} in a single transfer")]
+    ReceiveLimitExceeded { max: Uint128 },
+
+    #[error("{address} is not on the mint allowlist")]
+    NotOnMintAllowlist { address: String },
+
+    #[error("{address} is not on the receiver allowlist")]
+    NotOnAllowlist { address: String },
+
+    #[error("{address} is not verified by the KYC registry")]
+    NotVerified { address: String },
+
+    #[error("No recipient given and no default mint recipient set")]
+    NoMintRecipient {},
+
+    #[error("Legacy balances have already been imported")]
+    AlreadyImported {},
+
+    #[error("Cannot set allowance to own account")]
+    CannotSetOwnAccount {},
+
+    #[error("Insufficient allowance: available {available}, required {required}")]
+    InsufficientAllowance {
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Allowance is expired")]
+    Expired {},
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    #[error("Given address already registered as a hook")]
+    HookAlreadyRegistered {},
+
+    #[error("Given address not registered as a hook")]
+    HookNotRegistered {},
+
+    #[error("Signature has expired")]
+    SignatureExpired {},
+
+    #[error("Invalid nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("Invalid expiration value")]
+    InvalidExpiration {},
+
+    #[error("Name is not in the expected format (3-50 UTF-8 bytes)")]
+    InvalidName {},
+
+    #[error("Ticker symbol is not in expected format [a-zA-Z\\-]{{3,12}}")]
+    InvalidSymbol {},
+
+    #[error("Decimals must not exceed 18")]
+    InvalidDecimals {},
+
+    #[error("No minter has been proposed")]
+    NoPendingMinter {},
+
+    #[error("Vesting schedule must satisfy start <= cliff <= end with start < end")]
+    InvalidVestingSchedule {},
+
+    #[error("{address} already has a vesting schedule")]
+    VestingExists { address: String },
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("No holders to distribute rewards to")]
+    NoRewardRecipients {},
+
+    #[error("Rewards can be paid in at most {max} denoms")]
+    TooManyRewardDenoms { max: u32 },
+
+    #[error("At most {max} accounts can be watched")]
+    TooManyWatchedAccounts { max: u32 },
+
+    #[error("Rewards cannot be distributed in {denom}")]
+    RewardDenomNotAllowed { denom: String },
+
+    #[error("Transfer fee and burn rate together cannot exceed 10000 basis points")]
+    InvalidFee {},
+
+    #[error("Fee tiers must be sorted by strictly increasing minimum amount")]
+    InvalidFeeTiers {},
+
+    #[error("Staking has not been configured")]
+    StakingDisabled {},
+
+    #[error("Staking epoch must be longer than zero seconds")]
+    InvalidStakingConfig {},
+
+    #[error("Insufficient stake: available {available}, required {required}")]
+    InsufficientStake {
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("Merkle root must be a 32 byte sha256 hash")]
+    InvalidMerkleRoot {},
+
+    #[error("Merkle proof is invalid")]
+    InvalidProof {},
+
+    #[error("Airdrop already claimed")]
+    AlreadyClaimed {},
+
+    #[error("No airdrop is active")]
+    NoAirdrop {},
+
+    #[error("An airdrop is already active")]
+    AirdropActive {},
+
+    #[error("Airdrop has expired")]
+    AirdropExpired {},
+
+    #[error("Airdrop has not expired yet")]
+    AirdropNotExpired {},
+
+    #[error("Escrow has expired")]
+    EscrowExpired {},
+
+    #[error("Escrow has not expired yet")]
+    EscrowNotExpired {},
+
+    #[error("Stream must end after it starts and after the current block")]
+    InvalidStream {},
+
+    #[error("Subscription period must not be zero")]
+    InvalidPeriod {},
+
+    #[error("Subscription can next be collected at {next_collection}")]
+    PaymentNotDue { next_collection: u64 },
+
+    #[error("Swap hash must be a 32 byte sha256 hash")]
+    InvalidSwapHash {},
+
+    #[error("Preimage does not match the swap hash")]
+    InvalidPreimage {},
+
+    #[error("Swap has expired")]
+    SwapExpired {},
+
+    #[error("Swap has not expired yet")]
+    SwapNotExpired {},
+
+    #[error("Only unordered channels are supported")]
+    InvalidIbcOrder {},
+
+    #[error("Invalid IBC channel version {version}")]
+    InvalidIbcVersion { version: String },
+
+    #[error("Channel {id} is not open")]
+    NoSuchChannel { id: String },
+
+    #[error("Cannot redeem denom {denom} on this chain")]
+    InvalidDenom { denom: String },
+
+    #[error("Channel {id} does not escrow enough tokens")]
+    InsufficientEscrow { id: String },
+
+    #[error("ICS-20 channels cannot be closed")]
+    CannotCloseChannel {},
+
+    #[error("This token does not wrap a native denom")]
+    NotWrapped {},
+
+    #[error("A wrapped token cannot be in share mode")]
+    WrappedShareMode {},
+
+    #[error("Send exactly one coin of {denom}")]
+    InvalidFunds { denom: String },
+
+    #[error("This token has no bonding curve")]
+    NoBondingCurve {},
+
+    #[error("Invalid bonding curve: the price must be positive and steps non-zero")]
+    InvalidCurve {},
+
+    #[error("Payment does not buy a single token unit")]
+    PaymentTooSmall {},
+
+    #[error("Only {available} tokens were issued through the curve")]
+    InsufficientCurveSupply { available: Uint128 },
+
+    #[error("Curve price overflowed")]
+    CurveOverflow {},
+
+    #[error("No buyback is configured")]
+    NoBuyback {},
+
+    #[error("Token already has the maximum of {max} holders")]
+    HolderCapReached { max: u64 },
+
+    #[error("No transfer fee is configured")]
+    NoFeeConfig {},
+
+    #[error("Treasury only holds {available}")]
+    InsufficientTreasury { available: Uint128 },
+
+    #[error("Memo is longer than {max} bytes")]
+    MemoTooLong { max: usize },
+
+    #[error("Flash mint not repaid: {outstanding} more tokens must be burned")]
+    FlashMintNotRepaid { outstanding: Uint128 },
+
+    #[error("Cannot recover the contract's own tokens")]
+    CannotRecoverOwnToken {},
+
+    #[error("Only {available} of the wrapped denom is not backing the supply")]
+    InsufficientSurplus { available: Uint128 },
+
+    #[error("Claim exceeds the remaining airdrop allocation")]
+    AllocationExceeded {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Logo binary data exceeds 5KB limit")]
+    LogoTooBig {},
+
+    #[error("Invalid xml preamble for SVG")]
+    InvalidXmlPreamble {},
+
+    #[error("Invalid png header")]
+    InvalidPngHeader {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from newer version: {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("Migration from cw20-base is still in progress")]
+    MigrationInProgress {},
+
+    #[error("A supply check is in progress; resume it after {last_account}")]
+    InvariantCheckInProgress { last_account: String },
+
+    #[error("No supply check is in progress")]
+    NoInvariantCheck {},
+
+    #[error("Unknown or malformed message")]
+    UnknownMessage {},
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
+}
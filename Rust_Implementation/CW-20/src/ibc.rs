@@ -0,0 +1,229 @@
+//! ICS-20 transfers of this token to other chains.
+//!
+//! Tokens sent out are held in the contract's own balance and counted as
+//! escrowed for the channel they left through. The counterparty mints a
+//! voucher for denom `cw20:<contract address>`; when vouchers come back, the
+//! packet denom carries the counterparty port and channel as prefix and the
+//! escrowed tokens are paid out again. Failed or timed out sends are refunded
+//! from the same escrow.
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Binary, DepsMut, Env, Ibc3ChannelOpenResponse, IbcBasicResponse,
+    IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcChannelOpenResponse, IbcOrder, IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg,
+    IbcPacketTimeoutMsg, IbcReceiveResponse, StdResult, Uint128,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::movement_event;
+use crate::error::ContractError;
+use crate::state::{ChannelInfo, State};
+
+pub const ICS20_VERSION: &str = "ics20-1";
+pub const ICS20_ORDERING: IbcOrder = IbcOrder::Unordered;
+
+/// Packet timeout used when `IbcTransfer` does not give one.
+pub const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
+
+/// ICS-20 fungible token packet data.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Ics20Packet {
+    pub amount: Uint128,
+    pub denom: String,
+    pub receiver: String,
+    pub sender: String,
+}
+
+/// ICS-20 acknowledgement: `result` on success, `error` otherwise.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ics20Ack {
+    Result(Binary),
+    Error(String),
+}
+
+/// The denom this token travels under on other chains.
+pub fn local_denom(env: &Env) -> String {
+    format!("cw20:{}", env.contract.address)
+}
+
+fn ack_success() -> StdResult<Binary> {
+    to_binary(&Ics20Ack::Result(b"1".into()))
+}
+
+fn ack_fail(err: String) -> StdResult<Binary> {
+    to_binary(&Ics20Ack::Error(err))
+}
+
+fn enforce_order_and_version(
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.version != ICS20_VERSION {
+        return Err(ContractError::InvalidIbcVersion {
+            version: channel.version.clone(),
+        });
+    }
+    if let Some(version) = counterparty_version {
+        if version != ICS20_VERSION {
+            return Err(ContractError::InvalidIbcVersion {
+                version: version.to_string(),
+            });
+        }
+    }
+    if channel.order != ICS20_ORDERING {
+        return Err(ContractError::InvalidIbcOrder {});
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    _deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    enforce_order_and_version(msg.channel(), msg.counterparty_version())?;
+    Ok(Some(Ibc3ChannelOpenResponse {
+        version: ICS20_VERSION.to_string(),
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    enforce_order_and_version(channel, msg.counterparty_version())?;
+    State::new(deps.storage).save_channel(&ChannelInfo {
+        id: channel.endpoint.channel_id.clone(),
+        counterparty_endpoint: channel.counterparty_endpoint.clone(),
+        connection_id: channel.connection_id.clone(),
+    })?;
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_connect")
+        .add_attribute("channel", &channel.endpoint.channel_id))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    _deps: DepsMut,
+    _env: Env,
+    _msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    // closing would strand the tokens escrowed for the channel
+    Err(ContractError::CannotCloseChannel {})
+}
+
+/// Pays out returning tokens. Errors become a failed acknowledgement so the
+/// counterparty refunds its sender; nothing is written before they occur.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    match do_packet_receive(deps, &env, &msg.packet) {
+        Ok(packet) => Ok(IbcReceiveResponse::new()
+            .set_ack(ack_success()?)
+            .add_event(movement_event(
+                "ibc_receive",
+                Some(env.contract.address.as_str()),
+                Some(&packet.receiver),
+                packet.amount,
+            ))
+            .add_attribute("action", "ibc_receive")
+            .add_attribute("receiver", packet.receiver)
+            .add_attribute("amount", packet.amount.to_string())
+            .add_attribute("success", "true")),
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(ack_fail(err.to_string())?)
+            .add_attribute("action", "ibc_receive")
+            .add_attribute("success", "false")
+            .add_attribute("error", err.to_string())),
+    }
+}
+
+fn do_packet_receive(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+) -> Result<Ics20Packet, ContractError> {
+    let data: Ics20Packet = from_binary(&packet.data)?;
+    // vouchers coming home carry the counterparty end of the channel as prefix
+    let prefix = format!("{}/{}/", packet.src.port_id, packet.src.channel_id);
+    if data.denom.strip_prefix(&prefix) != Some(local_denom(env).as_str()) {
+        return Err(ContractError::InvalidDenom { denom: data.denom });
+    }
+    let receiver = deps.api.addr_validate(&data.receiver)?;
+    State::new(deps.storage).ibc_release(
+        &packet.dest.channel_id,
+        receiver.as_str(),
+        env.contract.address.as_str(),
+        data.amount,
+        env.block.height,
+    )?;
+    Ok(data)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    match from_binary(&msg.acknowledgement.data)? {
+        Ics20Ack::Result(_) => {
+            let data: Ics20Packet = from_binary(&msg.original_packet.data)?;
+            Ok(IbcBasicResponse::new()
+                .add_attribute("action", "ibc_acknowledge")
+                .add_attribute("sender", data.sender)
+                .add_attribute("amount", data.amount.to_string())
+                .add_attribute("success", "true"))
+        }
+        Ics20Ack::Error(err) => refund(deps, &env, &msg.original_packet, "ibc_acknowledge")
+            .map(|response| response.add_attribute("error", err)),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    refund(deps, &env, &msg.packet, "ibc_timeout")
+}
+
+/// Returns the tokens of a packet that did not make it to its sender.
+fn refund(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    action: &str,
+) -> Result<IbcBasicResponse, ContractError> {
+    let data: Ics20Packet = from_binary(&packet.data)?;
+    State::new(deps.storage).ibc_release(
+        &packet.src.channel_id,
+        &data.sender,
+        env.contract.address.as_str(),
+        data.amount,
+        env.block.height,
+    )?;
+    Ok(IbcBasicResponse::new()
+        .add_event(movement_event(
+            "ibc_refund",
+            Some(env.contract.address.as_str()),
+            Some(&data.sender),
+            data.amount,
+        ))
+        .add_attribute("action", action)
+        .add_attribute("sender", data.sender)
+        .add_attribute("amount", data.amount.to_string())
+        .add_attribute("success", "false"))
+}
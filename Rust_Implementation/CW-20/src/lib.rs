@@ -0,0 +1,17 @@
+pub mod contract;
+pub mod curve;
+pub mod error;
+pub mod ibc;
+#[cfg(feature = "math")]
+pub mod math;
+#[cfg(not(feature = "math"))]
+mod math;
+pub mod merkle;
+pub mod msg;
+pub mod signature;
+pub mod state;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::error::ContractError;
@@ -0,0 +1,172 @@
+//! Checked arithmetic used by the balance, supply, fee and reward
+//! bookkeeping.
+//!
+//! Everything here is a pure function over `u128` and only depends on `core`,
+//! so the overflow-prone paths can be checked without touching storage.
+//! Build with the `math` feature to export the module from the crate.
+
+/// Basis points in a whole.
+pub const BPS_DENOMINATOR: u128 = 10_000;
+/// Atomics of one unit of a `cosmwasm_std::Decimal`, the type reward indexes
+/// are stored as.
+pub const INDEX_UNIT: u128 = 1_000_000_000_000_000_000;
+
+/// Credits `amount` to `balance`, returning `None` on overflow.
+pub fn add_balance(balance: u128, amount: u128) -> Option<u128> {
+    balance.checked_add(amount)
+}
+
+/// Debits `amount` from `balance`, returning `None` if it would go negative.
+pub fn sub_balance(balance: u128, amount: u128) -> Option<u128> {
+    balance.checked_sub(amount)
+}
+
+/// Returns `true` if `current + amount` stays within `cap`.
+pub fn within_cap(current: u128, amount: u128, cap: u128) -> bool {
+    add_balance(current, amount).map_or(false, |total| total <= cap)
+}
+
+/// `a * b / c`, rounded down, with a 256-bit intermediate product. Returns
+/// `None` when `c` is zero or the quotient does not fit in `u128`.
+pub fn mul_div(a: u128, b: u128, c: u128) -> Option<u128> {
+    if c == 0 {
+        return None;
+    }
+    let (high, low) = mul_wide(a, b);
+    if high >= c {
+        return None;
+    }
+    // long division of the 256-bit product, one bit at a time; the
+    // remainder stays below `c` so only its top bit can carry out
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= c {
+            remainder = remainder.wrapping_sub(c);
+            quotient |= 1;
+        }
+    }
+    Some(quotient)
+}
+
+/// The full product of `a` and `b` as `(high, low)` halves.
+fn mul_wide(a: u128, b: u128) -> (u128, u128) {
+    const MASK: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & MASK);
+    let (b_high, b_low) = (b >> 64, b & MASK);
+    let low_low = a_low * b_low;
+    let high_low = a_high * b_low;
+    let low_high = a_low * b_high;
+    let high_high = a_high * b_high;
+    let middle = (low_low >> 64) + (high_low & MASK) + (low_high & MASK);
+    let low = (middle << 64) | (low_low & MASK);
+    let high = high_high + (high_low >> 64) + (low_high >> 64) + (middle >> 64);
+    (high, low)
+}
+
+/// `bps` basis points of `amount`, rounded down. `None` only when `bps`
+/// exceeds a whole and the result overflows.
+pub fn bps_of(amount: u128, bps: u16) -> Option<u128> {
+    mul_div(amount, u128::from(bps), BPS_DENOMINATOR)
+}
+
+/// The fee and burn share taken from a transfer of `amount`, each rounded
+/// down. `None` when the two rates together exceed a whole, as they could
+/// then take more than `amount`.
+pub fn split(amount: u128, fee_bps: u16, burn_bps: u16) -> Option<(u128, u128)> {
+    if u128::from(fee_bps) + u128::from(burn_bps) > BPS_DENOMINATOR {
+        return None;
+    }
+    Some((bps_of(amount, fee_bps)?, bps_of(amount, burn_bps)?))
+}
+
+/// The share of `amount` owed to a holder of `part` out of `total`, rounded
+/// down, so the shares of all holders never add up to more than `amount`.
+pub fn pro_rata(amount: u128, part: u128, total: u128) -> Option<u128> {
+    mul_div(amount, part, total)
+}
+
+/// How far a reward index moves, in `INDEX_UNIT` atomics, when `amount` is
+/// distributed over `eligible` units. Rounded down.
+pub fn index_step(amount: u128, eligible: u128) -> Option<u128> {
+    mul_div(amount, INDEX_UNIT, eligible)
+}
+
+/// Rewards earned by `balance` while the reward index moved by `step`
+/// atomics. Rounded down.
+pub fn accrued(balance: u128, step: u128) -> Option<u128> {
+    mul_div(balance, step, INDEX_UNIT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn mul_div_handles_wide_products() {
+        assert_eq!(mul_div(u128::MAX, u128::MAX, u128::MAX), Some(u128::MAX));
+        assert_eq!(mul_div(u128::MAX, 2, 4), Some(u128::MAX / 2));
+        assert_eq!(mul_div(u128::MAX, 2, 1), None);
+        assert_eq!(mul_div(1, 1, 0), None);
+    }
+
+    proptest! {
+        #[test]
+        fn mul_div_matches_narrow_arithmetic(a in any::<u64>(), b in any::<u64>(), c in 1..u128::MAX) {
+            let (a, b) = (u128::from(a), u128::from(b));
+            prop_assert_eq!(mul_div(a, b, c), Some(a * b / c));
+        }
+
+        #[test]
+        fn fee_and_burn_never_exceed_amount(
+            amount in any::<u128>(),
+            fee_bps in 0..=10_000u16,
+            burn_bps in 0..=10_000u16,
+        ) {
+            match split(amount, fee_bps, burn_bps) {
+                Some((fee, burned)) => prop_assert!(fee + burned <= amount),
+                None => prop_assert!(u32::from(fee_bps) + u32::from(burn_bps) > 10_000),
+            }
+        }
+
+        #[test]
+        fn bps_of_never_exceeds_amount(amount in any::<u128>(), bps in 0..=10_000u16) {
+            prop_assert!(bps_of(amount, bps).unwrap() <= amount);
+        }
+
+        #[test]
+        fn pro_rata_shares_sum_to_at_most_amount(
+            amount in any::<u64>(),
+            parts in prop::collection::vec(1..u64::MAX, 1..20),
+        ) {
+            let amount = u128::from(amount);
+            let total: u128 = parts.iter().map(|part| u128::from(*part)).sum();
+            let paid: u128 = parts
+                .iter()
+                .map(|part| pro_rata(amount, u128::from(*part), total).unwrap())
+                .sum();
+            // each share loses less than one unit to rounding
+            prop_assert!(paid <= amount);
+            prop_assert!(amount - paid < parts.len() as u128);
+        }
+
+        #[test]
+        fn dividends_sum_to_at_most_distribution(
+            amount in any::<u64>(),
+            balances in prop::collection::vec(1..u64::MAX, 1..20),
+        ) {
+            let amount = u128::from(amount);
+            let eligible: u128 = balances.iter().map(|balance| u128::from(*balance)).sum();
+            let step = index_step(amount, eligible).unwrap();
+            let paid: u128 = balances
+                .iter()
+                .map(|balance| accrued(u128::from(*balance), step).unwrap())
+                .sum();
+            prop_assert!(paid <= amount);
+        }
+    }
+}
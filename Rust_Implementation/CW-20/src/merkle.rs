@@ -0,0 +1,22 @@
+//! Merkle proof verification for airdrop claims.
+//!
+//! Leaves are `sha256(address ++ amount)` with the amount in decimal, and each
+//! level hashes the sorted pair of children, so proofs carry no left/right
+//! flags. This matches the trees built by the cw20-merkle-airdrop tooling.
+
+use sha2::{Digest, Sha256};
+
+/// Hash of the leaf for `address` claiming `amount`.
+pub fn leaf(address: &str, amount: u128) -> [u8; 32] {
+    Sha256::digest(format!("{}{}", address, amount)).into()
+}
+
+/// Returns `true` if folding `proof` onto `leaf` yields `root`.
+pub fn verify(root: &[u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    let computed = proof.iter().fold(leaf, |hash, sibling| {
+        let mut pair = [hash, *sibling];
+        pair.sort_unstable();
+        Sha256::digest(pair.concat()).into()
+    });
+    computed == *root
+}
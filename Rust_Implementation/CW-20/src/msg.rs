@@ -0,0 +1,1461 @@
+use cosmwasm_std::{Binary, Coin, Decimal, Decimal256, HexBinary, Uint128};
+use cw20::{AllowanceInfo, Cw20Coin, Expiration, InstantiateMarketingInfo, Logo, MinterResponse};
+use schemars::JsonSchema;
+use serde::de::IgnoredAny;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+use crate::state::{
+    ActivityEntry, AdminAction, AuditEntry, BondingCurve, BuybackConfig, ChannelInfo, FeeConfig,
+    FreezeInfo, FreezeRecord, Hook, InflationConfig, LockedTransfer, MultisigAction,
+    MultisigConfig, MultisigProposal, RateLimit, Role, ScheduledAction, Stream, Subscription,
+    Unbonding, VestingSchedule,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InitMsg {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub initial_balances: Vec<Cw20Coin>,
+    pub mint: Option<MinterResponse>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    pub fee: Option<FeeConfig>,
+    /// Restricts transfers to recipients on the allowlist kept by the
+    /// `Compliance` role.
+    #[serde(default)]
+    pub permissioned: bool,
+    /// Native denom to wrap 1:1 through `Deposit` and `Withdraw`. Cannot be
+    /// combined with `exchange_rate`.
+    pub wrapped_denom: Option<String>,
+    /// Seconds a scheduled admin action waits before it can be executed.
+    /// Defaults to two days.
+    pub timelock_delay: Option<u64>,
+    /// Mints a share of the supply every epoch, starting at instantiation.
+    pub inflation: Option<InflationConfig>,
+    /// Minters can no longer mint once this has passed. Cannot be changed
+    /// after instantiation.
+    pub mint_deadline: Option<Expiration>,
+    /// Starts the contract in share mode at this exchange rate: balances and
+    /// amounts are shares, each worth `exchange_rate` tokens. Wrapped tokens
+    /// stay out of share mode, as each one is backed by exactly one coin.
+    pub exchange_rate: Option<Decimal>,
+    /// Lets anyone buy newly minted tokens with the reserve denom, and sell
+    /// them back, along this curve. Cannot be changed after instantiation.
+    pub bonding_curve: Option<BondingCurve>,
+}
+
+impl InitMsg {
+    /// Checks name, symbol and decimals against the cw20 spec.
+    pub fn validate(&self) -> Result<(), ContractError> {
+        if !(3..=50).contains(&self.name.len()) {
+            return Err(ContractError::InvalidName {});
+        }
+        let symbol_ok = (3..=12).contains(&self.symbol.len())
+            && self
+                .symbol
+                .bytes()
+                .all(|c| c.is_ascii_alphabetic() || c == b'-');
+        if !symbol_ok {
+            return Err(ContractError::InvalidSymbol {});
+        }
+        if self.decimals > 18 {
+            return Err(ContractError::InvalidDecimals {});
+        }
+        Ok(())
+    }
+}
+
+/// Funds held by the contract that `RecoverFunds` can send back.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Asset {
+    Native { denom: String, amount: Uint128 },
+    Cw20 { contract: String, amount: Uint128 },
+}
+
+/// `Swap` of a Terraswap/Astroport style pair, as sent by `BuybackAndBurn`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairExecuteMsg {
+    Swap {
+        offer_asset: PairAsset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PairAsset {
+    pub info: PairAssetInfo,
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairAssetInfo {
+    NativeToken { denom: String },
+    Token { contract_addr: String },
+}
+
+/// What `owner` signs to authorize `TransferBySignature`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedTransfer {
+    pub chain_id: String,
+    pub contract: String,
+    pub owner: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub nonce: u64,
+    pub expiration: Expiration,
+}
+
+/// What `owner` signs to authorize `Permit`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedPermit {
+    pub chain_id: String,
+    pub contract: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub nonce: u64,
+    pub expiration: Expiration,
+}
+
+/// Sent to every hook for each `token_movement` event of an execution,
+/// with the same fields.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenHookMsg {
+    TokenMovement {
+        action: String,
+        from: Option<String>,
+        to: Option<String>,
+        amount: Uint128,
+    },
+}
+
+/// Sent to the recipient of a flash mint, which must burn `amount` plus `fee`
+/// before returning. `initiator` asked for the flash mint.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FlashMintReceiverMsg {
+    ReceiveFlashMint {
+        initiator: String,
+        amount: Uint128,
+        fee: Uint128,
+        msg: Binary,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferEntry {
+    pub recipient: String,
+    pub amount: Uint128,
+}
+
+/// What the `execute` entry point accepts. JSON that is not a valid
+/// `HandleMsg` lands in `Unknown` instead of failing to deserialize, so the
+/// contract can reject it with `UnknownMessage` or ignore it.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum ExecuteMsg {
+    Handle(HandleMsg),
+    Unknown(IgnoredAny),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HandleMsg {
+    /// Moves `amount` to `recipient`. `memo`, if given, is emitted with the
+    /// transfer, e.g. for deposit attribution.
+    Transfer {
+        recipient: String,
+        amount: Uint128,
+        memo: Option<String>,
+    },
+    /// Transfers from `owner` on the strength of its signature over a
+    /// `SignedTransfer`, so that anyone can relay it and pay the gas.
+    /// `public_key` is the secp256k1 key of `owner`.
+    TransferBySignature {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+        nonce: u64,
+        expiration: Expiration,
+        public_key: Binary,
+        signature: Binary,
+    },
+    /// Sets the allowance of `spender` over the tokens of `owner` to
+    /// `amount` until `expiration`, on the strength of the signature of
+    /// `owner` over a `SignedPermit`. Anyone can relay it.
+    Permit {
+        owner: String,
+        spender: String,
+        amount: Uint128,
+        expiration: Expiration,
+        nonce: u64,
+        public_key: Binary,
+        signature: Binary,
+    },
+    /// Delegates the voting power of the sender's balance to `delegate`.
+    /// Delegating to oneself takes it back.
+    Delegate {
+        delegate: String,
+    },
+    /// Mints to `recipient`, or to the configured default mint recipient
+    /// when it is omitted.
+    Mint {
+        recipient: Option<String>,
+        amount: Uint128,
+    },
+    /// Sends to many recipients at once, debiting the sender a single time.
+    TransferBatch {
+        transfers: Vec<TransferEntry>,
+    },
+    /// Lets `address` mint up to `allowance`, or without limit below the cap
+    /// when it is `None`. Admin only.
+    AddMinter {
+        address: String,
+        allowance: Option<Uint128>,
+    },
+    RemoveMinter {
+        address: String,
+    },
+    /// Replaces what minter `address` may still mint.
+    SetMinterAllowance {
+        address: String,
+        allowance: Option<Uint128>,
+    },
+    /// Bounds what minter `address` may mint per UTC day, on top of its
+    /// allowance. `None` lifts the bound. Admin only.
+    SetMintDailyQuota {
+        address: String,
+        quota: Option<Uint128>,
+    },
+    /// Queues `action` behind the timelock delay. Admin only.
+    ScheduleAdminAction {
+        action: AdminAction,
+    },
+    /// Carries out scheduled action `id` once its delay has passed.
+    /// Admin only.
+    ExecuteAdminAction {
+        id: u64,
+    },
+    /// Drops scheduled action `id`. Admin only.
+    CancelAdminAction {
+        id: u64,
+    },
+    /// Registers contract `addr` to be notified of every balance change.
+    /// With `tolerate_failure`, an error in its notification no longer
+    /// reverts the transaction. Admin only.
+    AddHook {
+        addr: String,
+        #[serde(default)]
+        tolerate_failure: bool,
+    },
+    RemoveHook {
+        addr: String,
+    },
+    /// With `tolerate`, a failing `Receive` of `contract` after a `Send`,
+    /// `SendFrom` or `MintAndSend` returns the tokens to where they came from
+    /// instead of reverting the transaction. Admin only.
+    SetReceiveFailureTolerance {
+        contract: String,
+        tolerate: bool,
+    },
+    /// Replaces the minting co-signers, or removes them when `None`.
+    /// Admin only.
+    UpdateMultisig {
+        multisig: Option<MultisigConfig>,
+    },
+    /// Proposes `action` to the co-signers, counting the sender's approval.
+    ProposeAction {
+        action: MultisigAction,
+    },
+    /// Approves proposal `id`, which is carried out as soon as the
+    /// threshold is reached. Co-signers only.
+    ApproveAction {
+        id: u64,
+    },
+    /// Removes every minter for good, fixing the supply. `Minter` returns
+    /// `None` afterwards and no minter can be added again. Admin only.
+    RenounceMinter {},
+    /// First step of a minter handover. Admin only.
+    ProposeNewMinter {
+        address: String,
+    },
+    /// Second step of a minter handover, sent by the proposed address.
+    AcceptMinter {},
+    /// Freezes the whole account of `address`, lifted automatically at
+    /// `expires` if given. `reason` is kept for the `FreezeInfo` query.
+    Freeze {
+        address: String,
+        expires: Option<Expiration>,
+        reason: Option<String>,
+    },
+    /// Freezes only `amount` of the balance of `address`, replacing any
+    /// existing freeze on it.
+    FreezeAmount {
+        address: String,
+        amount: Uint128,
+        reason: Option<String>,
+    },
+    Unfreeze {
+        address: String,
+    },
+    /// Lifts every freeze placed with `reason`, looking at up to `limit`
+    /// frozen accounts after `start_after`. Repeat from `last_address` until
+    /// it is empty.
+    UnfreezeByReason {
+        reason: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Replaces the anti-whale limits. `None` disables a limit.
+    UpdateTransferLimits {
+        max_transfer_amount: Option<Uint128>,
+        max_wallet_balance: Option<Uint128>,
+    },
+    /// Sets the per-account transfer rate limit. `None` removes it.
+    UpdateRateLimit {
+        rate_limit: Option<RateLimit>,
+    },
+    /// Exempts `address` from the transfer limits, as sender and recipient,
+    /// and from the rate limit.
+    AddLimitExemption {
+        address: String,
+    },
+    RemoveLimitExemption {
+        address: String,
+    },
+    /// Sets the external registry consulted before crediting a transfer or
+    /// mint recipient. `None` stops consulting one.
+    UpdateKycRegistry {
+        registry: Option<String>,
+    },
+    /// Lets transfers credit `address` in permissioned mode.
+    AddToAllowlist {
+        address: String,
+    },
+    RemoveFromAllowlist {
+        address: String,
+    },
+    /// Blocks `address` from both sending and receiving tokens.
+    Blacklist {
+        address: String,
+    },
+    Unblacklist {
+        address: String,
+    },
+    /// Moves `amount` out of the frozen account `address` into `recipient`.
+    /// Admin only; `reason` must not be empty.
+    Clawback {
+        address: String,
+        recipient: String,
+        amount: Uint128,
+        reason: String,
+    },
+    /// Freezes the whole account of every address in `addresses`. All
+    /// addresses are validated before any is frozen.
+    FreezeBatch {
+        addresses: Vec<String>,
+        reason: Option<String>,
+    },
+    /// Lifts any freeze on every address in `addresses`.
+    UnfreezeBatch {
+        addresses: Vec<String>,
+    },
+    /// Restricts minting to addresses on the mint allowlist when enabled.
+    /// Transfers are not affected.
+    SetMintAllowlistEnabled {
+        enabled: bool,
+    },
+    AddToMintAllowlist {
+        address: String,
+    },
+    RemoveFromMintAllowlist {
+        address: String,
+    },
+    /// Limits how much a single address can receive in one transfer,
+    /// independently of the holder cap. The fee treasury and limit-exempt
+    /// addresses are not limited. Zero disables the limit.
+    UpdateMaxReceivePerTx {
+        amount: Uint128,
+    },
+    /// Caps the number of holders, or lifts the cap when `max_holders` is
+    /// `None`. With `minter_bypasses_holder_cap`, mints may still add
+    /// holders beyond it. Admin only.
+    UpdateHolderCap {
+        max_holders: Option<u64>,
+        minter_bypasses_holder_cap: bool,
+    },
+    /// One-time import of balances from a previous token. `proof` identifies
+    /// the source snapshot and is only echoed back in the logs.
+    ImportLegacyBalances {
+        entries: Vec<Cw20Coin>,
+        proof: String,
+    },
+    /// Permanently fixes the current cap. Minters can still be managed
+    /// through `AddMinter`, `SetMinterAllowance`, `ProposeNewMinter` /
+    /// `AcceptMinter` and the scheduled `UpdateMinter` admin action, but the
+    /// cap can no longer change.
+    LockCap {},
+    UpdateDefaultMintRecipient {
+        address: Option<String>,
+    },
+    /// Mints to `contract` and notifies it with a `Cw20ReceiveMsg` in the same
+    /// transaction.
+    MintAndSend {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Blocks all transfers between accounts while still allowing minting.
+    SetSoulbound {
+        enabled: bool,
+    },
+    /// Makes `execute` accept messages it cannot parse as a no-op instead of
+    /// failing with `UnknownMessage`. Off by default.
+    SetIgnoreUnknownMessages {
+        enabled: bool,
+    },
+    /// Burns `amount` from `address` on the issuer's behalf, also while the
+    /// token is soulbound or the account frozen, and from its locked tokens
+    /// once the rest is used up. Admin or minter only.
+    Revoke {
+        address: String,
+        amount: Uint128,
+    },
+    /// Mints tokens that count towards the recipient's balance but can never
+    /// be transferred, unless an admin releases them.
+    MintLocked {
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Makes `amount` of `address`'s locked tokens transferable. Admin only.
+    ReleaseLocked {
+        address: String,
+        amount: Uint128,
+    },
+    /// Transfers at or above this amount carry a `whale_alert` log
+    /// attribute. Zero disables it.
+    UpdateWhaleThreshold {
+        amount: Uint128,
+    },
+    /// Delay between a `Freeze` and the freeze being enforced.
+    UpdateFreezeGraceSeconds {
+        seconds: u64,
+    },
+    /// Lets `spender` move up to `amount` more of the sender's tokens. An
+    /// `expires` value replaces the current expiration.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the allowance of `spender`, removing it once it reaches zero.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Removes the expired allowances `owner` granted, looking at up to
+    /// `limit` spenders after `start_after`. Anyone may call.
+    PruneExpiredAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Removes every allowance granted to `spender`, looking at up to `limit`
+    /// owners after `start_after`, for retiring a compromised or deprecated
+    /// integration. Admin only; repeat from `last_owner` until `revoked` is
+    /// below `limit`.
+    RevokeSpenderAll {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Moves `amount` of the fees collected by the treasury to `to`. Only
+    /// the treasury or an admin may call.
+    ClaimTreasury {
+        to: String,
+        amount: Uint128,
+    },
+    /// Makes `new_treasury` the recipient of transfer fees, moving the
+    /// balance of the previous treasury to it even if that is frozen. Admin
+    /// only.
+    RotateTreasury {
+        new_treasury: String,
+    },
+    /// Freezes `address` and revokes the allowances it granted, up to
+    /// `MAX_LIMIT` per call, for when its key may be compromised. Admin
+    /// only; repeat while the response reports `more`.
+    SecureAccount {
+        address: String,
+    },
+    /// Checks that the balances add up to the total supply, summing up to
+    /// `limit` of them after `start_after` per call. Anyone may call; repeat
+    /// from `last_account` until the `supply_invariant` event reports `ok` or
+    /// `mismatch`. Only one check runs at a time, and it must be resumed from
+    /// exactly where it stopped.
+    VerifyInvariants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Caps what `spender` can move from the sender with `TransferFrom`,
+    /// `SendFrom` and `BurnFrom` to `amount_per_period` every `period`
+    /// seconds, whatever its allowance.
+    SetSpendLimit {
+        spender: String,
+        amount_per_period: Uint128,
+        period: u64,
+    },
+    RemoveSpendLimit {
+        spender: String,
+    },
+    /// Moves `amount` from `owner` to `recipient` using the sender's allowance.
+    TransferFrom {
+        owner: String,
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Destroys `amount` of the sender's tokens, reducing the total supply.
+    Burn {
+        amount: Uint128,
+        memo: Option<String>,
+    },
+    /// Destroys `amount` of `owner`'s tokens using the sender's allowance.
+    BurnFrom {
+        owner: String,
+        amount: Uint128,
+    },
+    /// Transfers `amount` to `contract` and notifies it with a
+    /// `Cw20ReceiveMsg` carrying `msg`.
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+        memo: Option<String>,
+    },
+    /// Like `Send`, but spends `owner`'s tokens using the sender's allowance.
+    SendFrom {
+        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+    },
+    /// Updates the marketing fields that are given. Only the marketing admin
+    /// may call it; an empty string clears a field.
+    UpdateMarketing {
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    },
+    /// Replaces the logo. Embedded logos are limited to 5KB and must be a
+    /// valid SVG or PNG.
+    UploadLogo(Logo),
+    /// Halts transfers, mints, burns and allowance spending. Admin or pauser
+    /// only.
+    Pause {},
+    /// Resumes token movement after `Pause`. Admin or pauser only.
+    Unpause {},
+    /// Sets the share of every transfer that is burned, in basis points.
+    /// Admin only.
+    UpdateBurnRate {
+        burn_bps: u16,
+    },
+    /// Sets the fee on flash mints, in basis points. Admin only.
+    UpdateFlashMintFee {
+        fee_bps: u16,
+    },
+    /// Mints `amount` to `recipient_contract` and calls it with a
+    /// `FlashMintReceiverMsg`. By the end of that call the contract must have
+    /// burned `amount` plus the flash mint fee, or the transaction reverts.
+    FlashMint {
+        amount: Uint128,
+        msg: Binary,
+        recipient_contract: String,
+    },
+    /// Sets the unbonding period, reward epoch length and per-epoch reward
+    /// rate of staking. Admin only.
+    UpdateStakingConfig {
+        unbonding_seconds: u64,
+        epoch_seconds: u64,
+        reward_rate: Decimal,
+    },
+    /// Locks `amount` of the sender's balance as stake.
+    Stake {
+        amount: Uint128,
+    },
+    /// Starts unbonding `amount` of the sender's stake.
+    Unstake {
+        amount: Uint128,
+    },
+    /// Sends `amount` to `recipient`, held by the contract until `release`.
+    TransferLocked {
+        recipient: String,
+        amount: Uint128,
+        release: Expiration,
+    },
+    /// Pays the sender every locked transfer to it whose release has passed.
+    ClaimLocked {},
+    /// Moves `amount` from the sender into an escrow for `recipient` that
+    /// `arbiter` can release until `expires`.
+    CreateEscrow {
+        recipient: String,
+        arbiter: String,
+        amount: Uint128,
+        expires: Expiration,
+    },
+    /// Pays escrow `id` out to its recipient. Arbiter only.
+    ReleaseEscrow {
+        id: u64,
+    },
+    /// Returns an expired escrow to its sender. Anyone may call it.
+    RefundEscrow {
+        id: u64,
+    },
+    /// Locks `amount` from the sender for `recipient` until `expires`,
+    /// claimable by revealing the sha256 preimage of `hash`.
+    CreateSwap {
+        recipient: String,
+        hash: HexBinary,
+        expires: Expiration,
+        amount: Uint128,
+    },
+    /// Pays swap `id` out to its recipient. Anyone holding the preimage may
+    /// call it.
+    ClaimSwap {
+        id: u64,
+        preimage: HexBinary,
+    },
+    /// Returns an expired swap to its sender. Anyone may call it.
+    RefundSwap {
+        id: u64,
+    },
+    /// Moves `amount` from the sender into a stream paying `recipient` at a
+    /// constant rate between `start` and `end` (seconds).
+    CreateStream {
+        recipient: String,
+        amount: Uint128,
+        start: u64,
+        end: u64,
+    },
+    /// Pays out what stream `id` has accrued so far. Recipient only.
+    WithdrawFromStream {
+        id: u64,
+    },
+    /// Ends stream `id`, paying the recipient what has accrued and returning
+    /// the rest. Sender only.
+    CancelStream {
+        id: u64,
+    },
+    /// Lets `merchant` pull `amount` from the sender once every `period`
+    /// seconds, starting at `start` or right away.
+    CreateSubscription {
+        merchant: String,
+        amount: Uint128,
+        period: u64,
+        start: Option<u64>,
+    },
+    /// Pulls the payment due on subscription `id`. Merchant only.
+    CollectSubscription {
+        id: u64,
+    },
+    /// Ends subscription `id`. Subscriber or merchant only.
+    CancelSubscription {
+        id: u64,
+    },
+    /// Sends `amount` to `remote_address` on the chain at the other end of
+    /// ICS-20 `channel`. `timeout` is in seconds from now.
+    IbcTransfer {
+        channel: String,
+        remote_address: String,
+        amount: Uint128,
+        timeout: Option<u64>,
+    },
+    /// Mints the wrapped native coins sent along 1:1 to the sender.
+    Deposit {},
+    /// Burns `amount` of the sender's tokens and sends back as many of the
+    /// wrapped native coins.
+    Withdraw {
+        amount: Uint128,
+    },
+    /// Leaves the balance of `address` out of the circulating supply.
+    AddNonCirculating {
+        address: String,
+    },
+    RemoveNonCirculating {
+        address: String,
+    },
+    /// Sends `asset` held by the contract to `recipient`. Admin only, for
+    /// coins or tokens sent to the contract by mistake.
+    RecoverFunds {
+        asset: Asset,
+        recipient: String,
+    },
+    /// Sets the pair and denom used by `BuybackAndBurn`, or turns buybacks
+    /// off when `None`. Admin only.
+    UpdateBuyback {
+        buyback: Option<BuybackConfig>,
+    },
+    /// Adds the coins of the buyback denom sent along to the treasury.
+    FundTreasury {},
+    /// Swaps `amount` of the treasury, or all of it, for this token through
+    /// the buyback pair and burns what comes back, less the caller's bounty.
+    /// Admin only unless buybacks are permissionless.
+    BuybackAndBurn {
+        amount: Option<Uint128>,
+        max_spread: Option<Decimal>,
+    },
+    /// Unlocks the sender's tokens whose unbonding period has passed.
+    ClaimUnbonded {},
+    /// Distributes `amount` of the sender's tokens and any native coins sent
+    /// along to holders, pro rata to their balance. Anyone may call, but
+    /// native coins must be of a denom added with `AddRewardDenom`.
+    DistributeRewards {
+        amount: Option<Uint128>,
+    },
+    /// Allows holder rewards to be paid in native `denom`. Admin only; at
+    /// most `MAX_DIVIDEND_DENOMS` denoms may be allowed.
+    AddRewardDenom {
+        denom: String,
+    },
+    /// Stops new distributions in `denom`. Rewards already distributed in it
+    /// can still be claimed. Admin only.
+    RemoveRewardDenom {
+        denom: String,
+    },
+    /// Keeps the latest balance changes of `address` for `RecentActivity`,
+    /// for up to `MAX_WATCHED_ACCOUNTS` accounts. Admin only.
+    WatchAccount {
+        address: String,
+    },
+    /// Stops watching `address` and drops its recorded changes. Admin only.
+    UnwatchAccount {
+        address: String,
+    },
+    /// Mints the sender's accrued staking rewards, as far as the cap allows,
+    /// and pays out the rewards distributed to it as a holder. Rewards that
+    /// do not fit under the cap stay owed.
+    ClaimRewards {},
+    /// Pays out the next inflation epoch once it has begun. Anyone may call.
+    AdvanceEpoch {},
+    /// Sets the token value of one share, switching the contract to share
+    /// mode if needed. Admin or oracle only; refused for wrapped tokens.
+    UpdateExchangeRate {
+        rate: Decimal,
+    },
+    /// Mints tokens to the sender for the reserve coin sent, priced along the
+    /// bonding curve.
+    Buy {},
+    /// Burns `amount` of the sender's tokens and returns the reserve they are
+    /// worth along the bonding curve. Only tokens issued through the curve
+    /// can be sold.
+    Sell {
+        amount: Uint128,
+    },
+    /// Scales every balance, and so the supply, by `numerator / denominator`
+    /// at once through the exchange rate. Admin only; refused for wrapped
+    /// tokens.
+    Rebase {
+        numerator: Uint128,
+        denominator: Uint128,
+    },
+    /// Mints `amount` to `recipient` under a vesting schedule with a cliff
+    /// and linear release, times in seconds. Minter only.
+    CreateVesting {
+        recipient: String,
+        amount: Uint128,
+        start: u64,
+        cliff: u64,
+        end: u64,
+    },
+    /// Unlocks the sender's tokens that have vested so far.
+    ClaimVested {},
+    /// Starts an airdrop of up to `total_amount` tokens to the leaves of the
+    /// Merkle tree with root `merkle_root`. Admin only.
+    RegisterAirdrop {
+        merkle_root: HexBinary,
+        total_amount: Uint128,
+        expiration: Expiration,
+    },
+    /// Claims the sender's airdrop allocation of `amount`, proven by the
+    /// sibling hashes in `proof`.
+    Claim {
+        amount: Uint128,
+        proof: Vec<HexBinary>,
+    },
+    /// Closes the airdrop once it has expired, releasing what was never
+    /// claimed. Admin only.
+    ClawbackAirdrop {},
+    /// Gives `role` to `address`. Admin only.
+    GrantRole {
+        role: Role,
+        address: String,
+    },
+    /// Takes `role` away from `address`. Admin only.
+    RevokeRole {
+        role: Role,
+        address: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Returns the current balance of `address` as a `BalanceResponse`.
+    Balance { address: String },
+    /// Returns the current balance of each of `addresses`, in order, as a
+    /// `BalancesResponse`. At most `MAX_BALANCES_QUERY` addresses.
+    Balances { addresses: Vec<String> },
+    /// Returns the balance of `address` at the start of block `height` as a
+    /// `BalanceResponse`.
+    BalanceAt { address: String, height: u64 },
+    /// Returns the total supply at the start of block `height` as a
+    /// `TotalSupplyResponse`.
+    TotalSupplyAt { height: u64 },
+    /// Returns own plus delegated voting power of `address` at the start of
+    /// block `height` as a `VotingPowerResponse`.
+    VotingPowerAt { address: String, height: u64 },
+    /// Returns name, symbol, decimals and total supply as a `TokenInfoResponse`.
+    TokenInfo {},
+    /// Returns the minter and its cap as a `MinterResponse`.
+    Minter {},
+    /// Returns every minter with its remaining allowance as a
+    /// `MintersResponse`, paginated by address.
+    Minters {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns whether a freeze on `address` is in effect as a `FrozenResponse`.
+    Frozen { address: String },
+    /// Returns how much of the balance of `address` is frozen as a
+    /// `FrozenAmountResponse`.
+    FrozenAmount { address: String },
+    /// Returns the anti-whale limits as `TransferLimits`.
+    TransferLimits {},
+    /// Returns what `address` may still transfer in the current rate limit
+    /// window as a `TransferQuotaResponse`.
+    TransferQuota { address: String },
+    /// Returns whether `address` is exempt from the transfer limits as a
+    /// `LimitExemptResponse`.
+    LimitExempt { address: String },
+    /// Returns the configured KYC registry as a `KycRegistryResponse`.
+    KycRegistry {},
+    /// Returns the receiver allowlist as an `AllowlistResponse`, paginated by
+    /// address, and whether permissioned mode is on.
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns whether `address` is blacklisted as a `BlacklistedResponse`.
+    Blacklisted { address: String },
+    /// Returns the freeze on `address` together with who placed it, why and
+    /// when, as a `FreezeInfoResponse`.
+    FreezeInfo { address: String },
+    /// Returns recorded privileged actions, oldest first, as an
+    /// `AuditLogResponse`, paginated by entry id.
+    AuditLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns up to `limit` of the latest balance changes of a watched
+    /// `address`, newest first, as a `RecentActivityResponse`.
+    RecentActivity { address: String, limit: Option<u32> },
+    /// Returns every unexpired freeze as an `AllFrozenAccountsResponse`,
+    /// paginated by address.
+    AllFrozenAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns holder addresses as an `AllAccountsResponse`, paginated by
+    /// `start_after` and `limit`.
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the raw state of every account with a balance entry, its
+    /// balance, freeze and granted allowances, as an `ExportStateResponse`,
+    /// paginated by address. Meant for rebuilding state off-chain or seeding
+    /// a genesis file.
+    ExportState {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the amount `spender` may move from `owner` and its expiration
+    /// as an `AllowanceResponse`, empty once it has expired.
+    Allowance { owner: String, spender: String },
+    /// Returns the spend limit of `spender` on `owner` and what it can still
+    /// move this period as a `SpendLimitResponse`.
+    SpendLimit { owner: String, spender: String },
+    /// Returns every allowance granted by `owner` as an
+    /// `AllAllowancesResponse`, paginated by spender address.
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns every allowance granted to `spender` as an
+    /// `AllSpenderAllowancesResponse`, paginated by owner address.
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns what a transfer of `amount` from `from` to `to` would deliver,
+    /// charge and leave each side with, or why it would fail, as a
+    /// `PreviewTransferResponse`. The KYC registry is not consulted.
+    PreviewTransfer {
+        from: String,
+        to: String,
+        amount: Uint128,
+    },
+    /// Returns the supply invariants as a `HealthCheckResponse`, for
+    /// monitoring to alert on.
+    HealthCheck {},
+    /// Returns the allowances that expire within `within_seconds` from now
+    /// as an `AllowanceEntriesResponse`, looking at up to `limit` allowances
+    /// after `start_after`. Only allowances expiring at a time are matched.
+    ExpiringAllowances {
+        within_seconds: u64,
+        start_after: Option<AllowanceKey>,
+        limit: Option<u32>,
+    },
+    /// Returns the allowances that are zero or have expired, for pruning
+    /// with `PruneExpiredAllowances`, as an `AllowanceEntriesResponse`,
+    /// looking at up to `limit` allowances after `start_after`.
+    StaleAllowances {
+        start_after: Option<AllowanceKey>,
+        limit: Option<u32>,
+    },
+    /// Returns project, description, logo and marketing admin as a
+    /// `MarketingInfoResponse`.
+    MarketingInfo {},
+    /// Returns the embedded logo as a `DownloadLogoResponse`.
+    DownloadLogo {},
+    /// Returns the transfer fee configuration as an `Option<FeeConfig>`.
+    FeeConfig {},
+    /// Returns the holder cap as an `Option<HolderCap>`.
+    HolderCap {},
+    /// Returns the share of every transfer that is burned as a
+    /// `BurnRateResponse`.
+    BurnRate {},
+    /// Returns the buyback configuration and the treasury it draws on as a
+    /// `TreasuryResponse`.
+    Treasury {},
+    /// Returns the fee on flash mints as a `FlashMintFeeResponse`.
+    FlashMintFee {},
+    /// Returns transfers locked for `address` that it has not claimed as a
+    /// `LockedTransfersResponse`, paginated by id.
+    LockedTransfers {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns open ICS-20 channels and the amount each escrows as a
+    /// `ChannelInfoResponse`, paginated by channel id.
+    ChannelInfo {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the total supply less the balances of non-circulating
+    /// accounts as a `CirculatingSupplyResponse`.
+    CirculatingSupply {},
+    /// Returns the number of addresses with a non-zero balance as a
+    /// `HolderCountResponse`.
+    HolderCount {},
+    /// Returns the largest balances, largest first, as a
+    /// `TopHoldersResponse`.
+    TopHolders { limit: Option<u32> },
+    /// Returns the wrapped native denom as a `WrappedDenomResponse`.
+    WrappedDenom {},
+    /// Returns escrow `id` as an `Escrow`.
+    Escrow { id: u64 },
+    /// Returns swap `id` as a `Swap`.
+    Swap { id: u64 },
+    /// Returns stream `id` and what its recipient can withdraw now as a
+    /// `StreamResponse`.
+    Stream { id: u64 },
+    /// Returns subscription `id` as a `Subscription`.
+    Subscription { id: u64 },
+    /// Returns the subscriptions of `subscriber` as a
+    /// `SubscriptionsResponse`, paginated by id.
+    SubscriptionsBySubscriber {
+        subscriber: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the subscriptions paying `merchant` as a
+    /// `SubscriptionsResponse`, paginated by id.
+    SubscriptionsByMerchant {
+        merchant: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the open streams sent by `sender` as a `StreamsResponse`,
+    /// paginated by id.
+    StreamsBySender {
+        sender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the open streams paying `recipient` as a `StreamsResponse`,
+    /// paginated by id.
+    StreamsByRecipient {
+        recipient: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns the stake and unbonding entries of `address` as a
+    /// `StakedBalanceResponse`.
+    StakedBalance { address: String },
+    /// Returns the staking rewards and holder rewards `address` could claim
+    /// now as a `PendingRewardsResponse`.
+    PendingRewards { address: String },
+    /// Returns the native denoms holder rewards may be distributed in as a
+    /// `RewardDenomsResponse`.
+    RewardDenoms {},
+    /// Returns the vesting schedule of `address` and what it can claim now
+    /// as a `VestingInfoResponse`.
+    VestingInfo { address: String },
+    /// Returns the registered hooks as a `HooksResponse`.
+    Hooks {},
+    /// Returns whether a failing `Receive` of `contract` is tolerated as a
+    /// `ReceiveFailureToleratedResponse`.
+    ReceiveFailureTolerated { contract: String },
+    /// Returns the nonce `address` must sign its next authorization with as
+    /// a `NonceResponse`.
+    Nonce { address: String },
+    /// Returns the mint deadline and whether minters may still mint as a
+    /// `MintWindowResponse`.
+    MintWindow {},
+    /// Returns the inflation schedule and the next epoch to be paid out as
+    /// an `InflationInfoResponse`.
+    InflationInfo {},
+    /// Returns the exchange rate, which is also the rebase scaling index, and
+    /// the supply in shares and tokens as an `ExchangeRateResponse`.
+    ExchangeRate {},
+    /// Returns the bonding curve with its reserve, curve supply and spot
+    /// price as a `CurveInfoResponse`.
+    CurveInfo {},
+    /// Returns the shares of `address` and their current token value as a
+    /// `SharesResponse`.
+    Shares { address: String },
+    /// Returns the active airdrop, if any, as an `Option<AirdropConfig>`.
+    Airdrop {},
+    /// Returns the proposed but not yet accepted minter as a
+    /// `PendingMinterResponse`.
+    PendingMinter {},
+    /// Returns the admins, the primary minter and any pending minter
+    /// handover as a `RolesResponse`.
+    Roles {},
+    /// Returns the co-signers and the proposals still short of approvals as
+    /// a `MultisigResponse`, paginated by id.
+    Multisig {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns admin actions waiting out the timelock as a
+    /// `PendingAdminActionsResponse`, paginated by id.
+    PendingAdminActions {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TotalSupplyResponse {
+    pub total_supply: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenResponse {
+    pub frozen: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenAmountResponse {
+    pub amount: Uint128,
+}
+
+/// A freeze as reported by `AllFrozenAccounts`. `amount` is `None` when the
+/// whole account is frozen; the freeze is enforced from `active_from`
+/// (seconds) until `expires`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenAccount {
+    pub address: String,
+    pub amount: Option<Uint128>,
+    pub active_from: u64,
+    pub expires: Expiration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllFrozenAccountsResponse {
+    pub accounts: Vec<FrozenAccount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RecentActivityResponse {
+    pub entries: Vec<ActivityEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+/// All fields are `None` without a spend limit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpendLimitResponse {
+    pub limit: Option<RateLimit>,
+    pub remaining: Option<Uint128>,
+    pub resets_at: Option<u64>,
+}
+
+/// When `blocked` gives the reason the transfer would fail, nothing is
+/// delivered or charged and the balances are the current ones.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PreviewTransferResponse {
+    pub received: Uint128,
+    pub fee: Uint128,
+    pub burned: Uint128,
+    pub from_balance: Uint128,
+    pub to_balance: Uint128,
+    pub blocked: Option<String>,
+}
+
+/// `reserve_backs_supply` compares the wrapped coins the contract holds with
+/// the supply, and is always set for tokens that do not wrap a denom.
+/// `frozen_le_supply` compares the amounts held by partial freezes with the
+/// supply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HealthCheckResponse {
+    pub supply_le_cap: bool,
+    pub reserve_backs_supply: bool,
+    pub frozen_le_supply: bool,
+}
+
+/// Identifies an allowance when paging through those of every owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowanceKey {
+    pub owner: String,
+    pub spender: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowanceEntry {
+    pub owner: String,
+    pub spender: String,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+/// `allowances` are the matches among the allowances looked at; continue
+/// from `last` until it is `None`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowanceEntriesResponse {
+    pub allowances: Vec<AllowanceEntry>,
+    pub last: Option<AllowanceKey>,
+}
+
+/// `frozen` is the stored freeze, whether or not it is active yet or still.
+/// At most `MAX_EXPORT_ALLOWANCES` allowances are embedded; when
+/// `more_allowances` is set the rest are paged with `AllAllowances`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AccountExport {
+    pub address: String,
+    pub balance: Uint128,
+    pub frozen: Option<FreezeInfo>,
+    pub allowances: Vec<AllowanceInfo>,
+    pub more_allowances: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExportStateResponse {
+    pub accounts: Vec<AccountExport>,
+}
+
+/// `remaining` and `resets_at` (seconds) are `None` when transfers from the
+/// address are not rate limited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TransferQuotaResponse {
+    pub remaining: Option<Uint128>,
+    pub resets_at: Option<u64>,
+}
+
+/// A minter as reported by `Minters`. `allowance` is `None` when it may
+/// mint without limit below the cap.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterEntry {
+    pub address: String,
+    pub allowance: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintersResponse {
+    pub minters: Vec<MinterEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalancesResponse {
+    pub balances: Vec<Cw20Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CirculatingSupplyResponse {
+    pub circulating_supply: Uint128,
+    pub total_supply: Uint128,
+    pub non_circulating: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HolderCountResponse {
+    pub count: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TopHoldersResponse {
+    pub holders: Vec<Cw20Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WrappedDenomResponse {
+    pub denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChannelEscrow {
+    pub info: ChannelInfo,
+    pub escrowed: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChannelInfoResponse {
+    pub channels: Vec<ChannelEscrow>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedTransfersResponse {
+    pub transfers: Vec<LockedTransfer>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreamResponse {
+    pub stream: Stream,
+    pub withdrawable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StreamsResponse {
+    pub streams: Vec<Stream>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubscriptionsResponse {
+    pub subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LimitExemptResponse {
+    pub exempt: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct KycRegistryResponse {
+    pub registry: Option<String>,
+}
+
+/// Query sent to the KYC registry contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum KycQueryMsg {
+    IsVerified { address: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IsVerifiedResponse {
+    pub verified: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistResponse {
+    pub permissioned: bool,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlacklistedResponse {
+    pub blacklisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreezeInfoResponse {
+    pub freeze: Option<FreezeInfo>,
+    pub record: Option<FreezeRecord>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BurnRateResponse {
+    pub burn_bps: u16,
+}
+
+/// `balance` is the treasury in the buyback denom, `None` without a buyback.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TreasuryResponse {
+    pub buyback: Option<BuybackConfig>,
+    pub balance: Option<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashMintFeeResponse {
+    pub fee_bps: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedBalanceResponse {
+    pub staked: Uint128,
+    pub unbonding: Vec<Unbonding>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardDenomsResponse {
+    pub denoms: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRewardsResponse {
+    pub rewards: Uint128,
+    /// Holder rewards by denom; those in this token are under
+    /// `cw20:<contract address>`.
+    pub dividends: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingInfoResponse {
+    pub schedule: Option<VestingSchedule>,
+    pub vested: Uint128,
+    pub claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingMinterResponse {
+    pub pending_minter: Option<String>,
+}
+
+/// At most `MAX_LIMIT` admins are listed. The admin role is granted and
+/// revoked directly, so unlike the minter it has no pending handover.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RolesResponse {
+    pub admins: Vec<String>,
+    pub minter: Option<String>,
+    pub pending_minter: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HooksResponse {
+    pub hooks: Vec<Hook>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReceiveFailureToleratedResponse {
+    pub tolerated: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintWindowResponse {
+    pub deadline: Option<Expiration>,
+    pub open: bool,
+}
+
+/// `rate` is `None` outside share mode, where shares and tokens are the same.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExchangeRateResponse {
+    pub rate: Option<Decimal>,
+    pub total_shares: Uint128,
+    pub total_value: Uint128,
+}
+
+/// `spot_price` is the reserve for the next token unit, `None` without a
+/// curve.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CurveInfoResponse {
+    pub curve: Option<BondingCurve>,
+    pub reserve: Uint128,
+    pub supply: Uint128,
+    pub spot_price: Option<Decimal256>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SharesResponse {
+    pub shares: Uint128,
+    pub value: Uint128,
+}
+
+/// `next_epoch_at` and `next_amount` are unset without inflation.
+/// `next_amount` is estimated from the current supply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InflationInfoResponse {
+    pub config: Option<InflationConfig>,
+    pub epochs_minted: u64,
+    pub next_epoch_at: Option<u64>,
+    pub next_amount: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultisigResponse {
+    pub config: Option<MultisigConfig>,
+    pub proposals: Vec<MultisigProposal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingAdminActionsResponse {
+    pub delay: u64,
+    pub actions: Vec<ScheduledAction>,
+}
+
+/// Emergency actions chain governance can take through `sudo`, without the
+/// contract's own keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    Pause {},
+    Unpause {},
+    /// Freezes `address`, e.g. once sanctioned, until `expires` or for good.
+    Freeze {
+        address: String,
+        expires: Option<Expiration>,
+        reason: Option<String>,
+    },
+    /// Grants the admin role to `new_admin`, revoking it from `old_admin`,
+    /// e.g. when its key was lost.
+    RotateAdmin {
+        old_admin: Option<String>,
+        new_admin: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrateMsg {
+    /// Upgrades an earlier deployment of this contract.
+    Upgrade {},
+    /// Converts a stock cw20-base deployment, keeping its balances,
+    /// allowances, supply and minter. The minter is granted every role.
+    /// Each run moves at most `limit` balances and allowances, up to
+    /// `MAX_MIGRATION_BATCH`; repeat it until the response reports
+    /// `done`. Execute messages are refused in between.
+    FromCw20Base { limit: Option<u32> },
+}
@@ -0,0 +1,38 @@
+//! Verification of authorizations signed off-chain.
+//!
+//! The signer signs the sha256 of the JSON payload with the secp256k1 key of
+//! its account. Payloads name the chain and this contract, so a signature
+//! cannot be replayed elsewhere, and carry the signer's nonce, so it cannot
+//! be replayed here. The public key travels with the signature and must hash
+//! to the signer's address, `ripemd160(sha256(public_key))` as for Cosmos SDK
+//! accounts.
+
+use cosmwasm_std::{to_vec, Addr, Api, Binary};
+use ripemd::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+
+/// Checks that `signature` over `payload` was made with the key of `signer`.
+pub fn verify<T: Serialize>(
+    api: &dyn Api,
+    signer: &Addr,
+    payload: &T,
+    public_key: &Binary,
+    signature: &Binary,
+) -> Result<(), ContractError> {
+    let canonical = api.addr_canonicalize(signer.as_str())?;
+    let key_hash = Ripemd160::digest(Sha256::digest(public_key.as_slice()));
+    if canonical.as_slice() != key_hash.as_slice() {
+        return Err(ContractError::InvalidSignature {});
+    }
+    let hash = Sha256::digest(to_vec(payload)?);
+    let valid = api
+        .secp256k1_verify(&hash, signature, public_key)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(())
+}
@@ -0,0 +1,5199 @@
+use cosmwasm_std::{
+    from_slice, Addr, Binary, BlockInfo, Coin, Decimal, HexBinary, IbcEndpoint, Order,
+    OverflowError, OverflowOperation, StdError, StdResult, Storage, Uint128,
+};
+use cw20::{
+    AllowanceInfo, AllowanceResponse, DownloadLogoResponse, EmbeddedLogo, Expiration, Logo,
+    LogoInfo, MarketingInfoResponse, MinterResponse, SpenderAllowanceInfo, TokenInfoResponse,
+};
+use cw_storage_plus::{Bound, Item, Map, SnapshotItem, SnapshotMap, Strategy};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::curve;
+use crate::error::ContractError;
+use crate::math;
+use crate::merkle;
+
+// Balances and supply are checkpointed every block so historical values can
+// be read back at any height. The primary keys are the same as before, so
+// current values need no migration.
+pub const BALANCES: SnapshotMap<&str, Uint128> = SnapshotMap::new(
+    "balances",
+    "balances__checkpoints",
+    "balances__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply",
+    "total_supply__checkpoints",
+    "total_supply__changelog",
+    Strategy::EveryBlock,
+);
+pub const MINTER: Item<MinterResponse> = Item::new("minter");
+// Holders of the `Minter` role and what each may still mint. The primary
+// minter in `MINTER` is one of them.
+pub const MINTERS: Map<&str, MinterInfo> = Map::new("minters");
+// Most a minter may mint per UTC day, whatever its allowance, and what it
+// minted on the last day it minted.
+pub const MINT_DAILY_QUOTAS: Map<&str, Uint128> = Map::new("mint_daily_quotas");
+pub const MINT_DAY_USAGE: Map<&str, WindowUsage> = Map::new("mint_day_usage");
+pub const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+// The mint cap. It is only ever raised, through a timelocked
+// `AdminAction::IncreaseCap`; the `cap` stored with `MINTER` is not consulted.
+pub const CAP: Item<Uint128> = Item::new("cap");
+// Admin actions waiting out the timelock, by id.
+pub const TIMELOCK_DELAY: Item<u64> = Item::new("timelock_delay");
+pub const SCHEDULED_ACTIONS: Map<u64, ScheduledAction> = Map::new("scheduled_actions");
+pub const SCHEDULED_ACTION_SEQ: Item<u64> = Item::new("scheduled_action_seq");
+// M-of-N co-signers for minting and the actions they have yet to approve.
+pub const MULTISIG: Item<MultisigConfig> = Item::new("multisig");
+pub const MULTISIG_PROPOSALS: Map<u64, MultisigProposal> = Map::new("multisig_proposals");
+pub const MULTISIG_PROPOSAL_SEQ: Item<u64> = Item::new("multisig_proposal_seq");
+/// Delay between scheduling and executing an admin action unless configured
+/// at instantiation: two days.
+pub const DEFAULT_TIMELOCK_DELAY: u64 = 2 * 24 * 60 * 60;
+pub const FROZEN_BALANCES: Map<&str, FreezeInfo> = Map::new("frozen_balances");
+pub const FREEZE_RECORDS: Map<&str, FreezeRecord> = Map::new("freeze_records");
+// Sum of the amounts held by partial freezes. A whole-account freeze never
+// holds more than the balance, so it is not counted.
+pub const FROZEN_TOTAL: Item<Uint128> = Item::new("frozen_total");
+pub const BLACKLIST: Map<&str, bool> = Map::new("blacklist");
+// In permissioned mode, transfers may only credit allowlisted addresses.
+pub const PERMISSIONED: Item<bool> = Item::new("permissioned");
+pub const ALLOWLIST: Map<&str, bool> = Map::new("allowlist");
+// Contract asked whether a recipient is verified before transfers and mints.
+pub const KYC_REGISTRY: Item<String> = Item::new("kyc_registry");
+pub const MINT_ALLOWLIST: Map<&str, bool> = Map::new("mint_allowlist");
+pub const MINT_ALLOWLIST_ENABLED: Item<bool> = Item::new("mint_allowlist_enabled");
+pub const MAX_RECEIVE_PER_TX: Item<Uint128> = Item::new("max_receive_per_tx");
+pub const HOLDER_CAP: Item<HolderCap> = Item::new("holder_cap");
+pub const TRANSFER_LIMITS: Item<TransferLimits> = Item::new("transfer_limits");
+pub const LIMIT_EXEMPT: Map<&str, bool> = Map::new("limit_exempt");
+pub const RATE_LIMIT: Item<RateLimit> = Item::new("rate_limit");
+pub const TRANSFER_WINDOWS: Map<&str, WindowUsage> = Map::new("transfer_windows");
+// Per (owner, spender) caps on what the spender can move per period, on top
+// of the allowance, and what it has moved in the current one.
+pub const SPEND_LIMITS: Map<(&str, &str), RateLimit> = Map::new("spend_limits");
+pub const SPEND_WINDOWS: Map<(&str, &str), WindowUsage> = Map::new("spend_windows");
+// Locked transfers keyed by (recipient, id). The tokens are held in the
+// contract's own balance until claimed.
+pub const LOCKED_TRANSFERS: Map<(&str, u64), LockedTransfer> = Map::new("locked_transfers");
+pub const LOCKED_TRANSFER_SEQ: Item<u64> = Item::new("locked_transfer_seq");
+// Escrows by id, also held in the contract's own balance.
+pub const ESCROWS: Map<u64, Escrow> = Map::new("escrows");
+pub const ESCROW_SEQ: Item<u64> = Item::new("escrow_seq");
+// Hashed-timelock swaps by id, also held in the contract's own balance.
+pub const SWAPS: Map<u64, Swap> = Map::new("swaps");
+pub const SWAP_SEQ: Item<u64> = Item::new("swap_seq");
+// Payment streams by id, held in the contract's own balance, and their ids
+// by sender and by recipient.
+pub const STREAMS: Map<u64, Stream> = Map::new("streams");
+pub const STREAMS_BY_SENDER: Map<(&str, u64), bool> = Map::new("streams_by_sender");
+pub const STREAMS_BY_RECIPIENT: Map<(&str, u64), bool> = Map::new("streams_by_recipient");
+pub const STREAM_SEQ: Item<u64> = Item::new("stream_seq");
+// Recurring payments merchants may pull, by id, and their ids by subscriber
+// and by merchant.
+pub const SUBSCRIPTIONS: Map<u64, Subscription> = Map::new("subscriptions");
+pub const SUBSCRIPTIONS_BY_SUBSCRIBER: Map<(&str, u64), bool> =
+    Map::new("subscriptions_by_subscriber");
+pub const SUBSCRIPTIONS_BY_MERCHANT: Map<(&str, u64), bool> = Map::new("subscriptions_by_merchant");
+pub const SUBSCRIPTION_SEQ: Item<u64> = Item::new("subscription_seq");
+// Open ICS-20 channels and how much of the supply each holds in escrow on
+// behalf of the counterparty chain.
+pub const CHANNELS: Map<&str, ChannelInfo> = Map::new("channels");
+pub const CHANNEL_ESCROW: Map<&str, Uint128> = Map::new("channel_escrow");
+// Native denom backing the token 1:1, if it wraps one.
+pub const WRAPPED_DENOM: Item<String> = Item::new("wrapped_denom");
+// Non-zero balances indexed by (balance, address) for ranking holders, and
+// their count. Both are kept up to date by `set_balance`.
+pub const HOLDERS_BY_BALANCE: Map<(u128, &str), bool> = Map::new("holders_by_balance");
+pub const HOLDER_COUNT: Item<u64> = Item::new("holder_count");
+// Treasury, team and similar accounts left out of the circulating supply.
+pub const NON_CIRCULATING: Map<&str, bool> = Map::new("non_circulating");
+pub const IMPORT_DONE: Item<bool> = Item::new("import_done");
+pub const CAP_LOCKED: Item<bool> = Item::new("cap_locked");
+// Set once by `renounce_minter`; never cleared.
+pub const MINTING_RENOUNCED: Item<bool> = Item::new("minting_renounced");
+// Set at instantiation only; minters cannot mint once it has passed.
+pub const MINT_DEADLINE: Item<Expiration> = Item::new("mint_deadline");
+pub const DEFAULT_MINT_RECIPIENT: Item<String> = Item::new("default_mint_recipient");
+pub const SOULBOUND: Item<bool> = Item::new("soulbound");
+// When set, `execute` accepts messages it cannot parse as a no-op
+// instead of rejecting them.
+pub const IGNORE_UNKNOWN_MESSAGES: Item<bool> = Item::new("ignore_unknown_messages");
+pub const LOCKED_BALANCES: Map<&str, Uint128> = Map::new("locked_balances");
+pub const WHALE_THRESHOLD: Item<Uint128> = Item::new("whale_threshold");
+pub const FREEZE_GRACE_SECONDS: Item<u64> = Item::new("freeze_grace_seconds");
+pub const ALLOWANCES: Map<(&str, &str), AllowanceResponse> = Map::new("allowances");
+// The same allowances keyed by (spender, owner), for listing what a spender
+// was granted.
+pub const ALLOWANCES_SPENDER: Map<(&str, &str), AllowanceResponse> = Map::new("allowances_spender");
+// Next nonce each owner must sign with, for replay protection of signed
+// authorizations.
+pub const NONCES: Map<&str, u64> = Map::new("nonces");
+// Contracts notified of every balance change.
+pub const HOOKS: Item<Vec<Hook>> = Item::new("hooks");
+// Contracts whose failing `Receive` is tolerated by refunding the tokens sent
+// to them instead of reverting the whole transaction.
+pub const TOLERANT_RECEIVERS: Map<&str, bool> = Map::new("tolerant_receivers");
+// What `reply` needs to handle a failed submessage, by reply id. Entries only
+// matter within the transaction that dispatched them.
+pub const REPLY_CONTEXTS: Map<u64, ReplyContext> = Map::new("reply_contexts");
+pub const REPLY_SEQ: Item<u64> = Item::new("reply_seq");
+pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
+pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
+pub const LOGO: Item<Logo> = Item::new("logo");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const PENDING_MINTER: Item<String> = Item::new("pending_minter");
+/// Delegator to delegate. Checkpointed so past voting power can be rebuilt.
+pub const DELEGATES: SnapshotMap<&str, String> = SnapshotMap::new(
+    "delegates",
+    "delegates__checkpoints",
+    "delegates__changelog",
+    Strategy::EveryBlock,
+);
+/// Sum of the balances delegated to each delegate.
+pub const DELEGATED_POWER: SnapshotMap<&str, Uint128> = SnapshotMap::new(
+    "delegated_power",
+    "delegated_power__checkpoints",
+    "delegated_power__changelog",
+    Strategy::EveryBlock,
+);
+pub const FEE_CONFIG: Item<FeeConfig> = Item::new("fee_config");
+/// Share of every transfer that is burned, in basis points.
+pub const BURN_BPS: Item<u16> = Item::new("burn_bps");
+pub const FLASH_MINT_FEE_BPS: Item<u16> = Item::new("flash_mint_fee_bps");
+pub const BUYBACK: Item<BuybackConfig> = Item::new("buyback");
+// Native coins set aside for buybacks, by denom.
+pub const TREASURY: Map<&str, Uint128> = Map::new("treasury");
+pub const STAKING_CONFIG: Item<StakingConfig> = Item::new("staking_config");
+pub const STAKES: Map<&str, StakeInfo> = Map::new("stakes");
+// Staking rewards accrued but not minted yet, either because they were
+// settled by a stake change or because the cap left no room for them.
+pub const OWED_REWARDS: Map<&str, Uint128> = Map::new("owed_rewards");
+pub const TOTAL_STAKED: Item<Uint128> = Item::new("total_staked");
+// Inflation paid to stakers so far, per staked token.
+pub const STAKER_REWARD_INDEX: Item<Decimal> = Item::new("staker_reward_index");
+// Rewards distributed to holders pro rata to their balance: per denom the
+// amount paid so far per eligible token and what is still unclaimed, and per
+// holder and denom what was settled up to which index. Tokens held by the
+// contract itself, recorded as `DIVIDEND_CUSTODIAN`, earn nothing.
+pub const DIVIDEND_INDEX: Map<&str, Decimal> = Map::new("dividend_index");
+pub const DIVIDEND_POOL: Map<&str, Uint128> = Map::new("dividend_pool");
+pub const DIVIDEND_POSITIONS: Map<(&str, &str), DividendPosition> = Map::new("dividend_positions");
+pub const DIVIDEND_CUSTODIAN: Item<String> = Item::new("dividend_custodian");
+/// Every balance change settles each reward denom, so their number is kept
+/// small.
+pub const MAX_DIVIDEND_DENOMS: u32 = 10;
+// Native denoms the admin allows rewards to be distributed in, so nobody can
+// fill the denom slots with worthless coins every balance change then settles.
+pub const REWARD_DENOMS: Map<&str, bool> = Map::new("reward_denoms");
+// Token value of one share. Once set, the contract runs in share mode:
+// stored balances, the supply and transfer amounts are all shares, and their
+// value follows the rate. Rebases scale it, so it doubles as the scaling
+// index.
+pub const EXCHANGE_RATE: Item<Decimal> = Item::new("exchange_rate");
+pub const INFLATION: Item<InflationConfig> = Item::new("inflation");
+pub const BONDING_CURVE: Item<BondingCurve> = Item::new("bonding_curve");
+// Reserve paid into the curve and tokens issued through it. Only these count
+// for the curve price, so tokens minted otherwise cannot drain the reserve.
+pub const CURVE_RESERVE: Item<Uint128> = Item::new("curve_reserve");
+pub const CURVE_SUPPLY: Item<Uint128> = Item::new("curve_supply");
+pub const INFLATION_EPOCHS: Item<InflationEpochs> = Item::new("inflation_epochs");
+pub const UNBONDING: Map<&str, Vec<Unbonding>> = Map::new("unbonding");
+pub const VESTING: Map<&str, VestingSchedule> = Map::new("vesting");
+pub const AIRDROP: Item<AirdropConfig> = Item::new("airdrop");
+pub const AIRDROP_STAGE: Item<u32> = Item::new("airdrop_stage");
+/// Keyed by (airdrop stage, address).
+pub const AIRDROP_CLAIMED: Map<(u32, &str), bool> = Map::new("airdrop_claimed");
+/// Keyed by (role, address).
+pub const ROLES: Map<(&str, &str), bool> = Map::new("roles");
+// Append-only log of privileged actions keyed by sequence number. Only the
+// latest `AUDIT_LOG_CAPACITY` entries are kept.
+pub const AUDIT_LOG: Map<u64, AuditEntry> = Map::new("audit_log");
+pub const AUDIT_LOG_NEXT: Item<u64> = Item::new("audit_log_next");
+pub const AUDIT_LOG_CAPACITY: u64 = 1000;
+// Accounts whose balance changes are kept in `ACTIVITY`, keyed by (account,
+// sequence number). Only the latest `ACTIVITY_CAPACITY` per account are kept.
+pub const WATCHED: Map<&str, bool> = Map::new("watched");
+pub const ACTIVITY: Map<(&str, u64), ActivityEntry> = Map::new("activity");
+pub const ACTIVITY_NEXT: Map<&str, u64> = Map::new("activity_next");
+pub const ACTIVITY_CAPACITY: u64 = 20;
+/// Every execute message compares the balance of each watched account, so
+/// their number is kept small.
+pub const MAX_WATCHED_ACCOUNTS: u32 = 20;
+// Set while a cw20-base deployment is converted in batches. Execute messages
+// are refused until the last batch clears it.
+pub const CW20_BASE_MIGRATION: Item<bool> = Item::new("cw20_base_migration");
+// most balances and allowances a single `FromCw20Base` migration moves
+pub const MAX_MIGRATION_BATCH: u32 = 500;
+// `VerifyInvariants` pass in progress, carried between pages.
+pub const INVARIANT_CHECK: Item<InvariantCheck> = Item::new("invariant_check");
+
+// settings for pagination
+pub const MAX_LIMIT: u32 = 30;
+pub const DEFAULT_LIMIT: u32 = 10;
+// most addresses a single `Balances` query may ask for
+pub const MAX_BALANCES_QUERY: usize = 100;
+pub const MAX_MEMO_LENGTH: usize = 256;
+// most allowances `ExportState` embeds per account
+pub const MAX_EXPORT_ALLOWANCES: usize = 30;
+
+/// Permissions an address can hold. Each gates its own group of execute
+/// branches; `Admin` also grants and revokes the others.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    Admin,
+    Minter,
+    Freezer,
+    Pauser,
+    Compliance,
+    Oracle,
+}
+
+impl Role {
+    pub const ALL: [Role; 6] = [
+        Role::Admin,
+        Role::Minter,
+        Role::Freezer,
+        Role::Pauser,
+        Role::Compliance,
+        Role::Oracle,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Admin => "admin",
+            Role::Minter => "minter",
+            Role::Freezer => "freezer",
+            Role::Pauser => "pauser",
+            Role::Compliance => "compliance",
+            Role::Oracle => "oracle",
+        }
+    }
+}
+
+/// Sensitive admin operations. They only take effect once scheduled and
+/// left pending for the timelock delay.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminAction {
+    /// Replaces the minter. The cap is left alone.
+    UpdateMinter { minter: String },
+    /// Replaces the transfer fee configuration.
+    UpdateFeeConfig {
+        fee_bps: u16,
+        treasury: String,
+        exempt: Vec<String>,
+        #[serde(default)]
+        tiers: Vec<FeeTier>,
+    },
+    /// Raises the mint cap to `new_cap`, which must be above the current cap.
+    IncreaseCap { new_cap: Uint128 },
+    /// Changes the delay of actions scheduled from then on.
+    UpdateTimelockDelay { seconds: u64 },
+}
+
+/// Co-signers of which `threshold` must approve a `MultisigAction`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultisigConfig {
+    pub signers: Vec<String>,
+    pub threshold: u32,
+}
+
+/// Minting operations carried out on behalf of the multisig.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MultisigAction {
+    Mint { recipient: String, amount: Uint128 },
+}
+
+/// A proposed `MultisigAction` and the signers that approved it so far.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultisigProposal {
+    pub id: u64,
+    pub action: MultisigAction,
+    pub approvals: Vec<String>,
+}
+
+/// An admin action that can be executed from `executable_at` (seconds).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ScheduledAction {
+    pub id: u64,
+    pub action: AdminAction,
+    pub executable_at: u64,
+}
+
+/// What a minter may still mint; `None` is bounded only by the cap.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct MinterInfo {
+    pub allowance: Option<Uint128>,
+}
+
+/// Fee charged on transfers, in basis points of the amount sent, and paid to
+/// `treasury`. Transfers from or to an `exempt` address are not charged.
+/// `tiers`, sorted by `min_amount`, replace `fee_bps` for transfers of at
+/// least their `min_amount`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeConfig {
+    pub fee_bps: u16,
+    pub treasury: String,
+    pub exempt: Vec<String>,
+    #[serde(default)]
+    pub tiers: Vec<FeeTier>,
+}
+
+impl FeeConfig {
+    /// Rate charged on a transfer of `amount`: that of the highest tier it
+    /// reaches, or `fee_bps` below every tier.
+    pub fn bps_for(&self, amount: Uint128) -> u16 {
+        self.tiers
+            .iter()
+            .rev()
+            .find(|tier| amount >= tier.min_amount)
+            .map_or(self.fee_bps, |tier| tier.fee_bps)
+    }
+
+    /// The highest rate any transfer can be charged.
+    pub fn max_bps(&self) -> u16 {
+        self.tiers
+            .iter()
+            .map(|tier| tier.fee_bps)
+            .fold(self.fee_bps, u16::max)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FeeTier {
+    pub min_amount: Uint128,
+    pub fee_bps: u16,
+}
+
+/// A freeze on an account, enforced from `active_from` (seconds) until
+/// `expires`. `amount` freezes only that much of the balance; `None` freezes
+/// the whole account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreezeInfo {
+    pub amount: Option<Uint128>,
+    pub active_from: u64,
+    #[serde(default)]
+    pub expires: Expiration,
+}
+
+impl FreezeInfo {
+    pub fn is_active(&self, block: &BlockInfo) -> bool {
+        block.time.seconds() >= self.active_from && !self.expires.is_expired(block)
+    }
+}
+
+/// Who placed the current freeze on an account, why, and when.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FreezeRecord {
+    pub freezer: Addr,
+    pub reason: Option<String>,
+    pub height: u64,
+    pub time: u64,
+}
+
+/// A change to the balance of a watched account. `delta` is how much it
+/// moved, in the direction given by `credit`, leaving `balance`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ActivityEntry {
+    pub height: u64,
+    pub action: String,
+    pub delta: Uint128,
+    pub credit: bool,
+    pub balance: Uint128,
+}
+
+/// A privileged action as recorded in the audit log. `payload` is the
+/// JSON-encoded execute message, or sudo message when `actor` is
+/// `governance`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub actor: Addr,
+    pub action: String,
+    pub height: u64,
+    pub time: u64,
+    pub payload: Binary,
+}
+
+/// How the amount of a transfer was split between the recipient, the
+/// treasury fee and the burn.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransferOutcome {
+    pub sent: Uint128,
+    pub received: Uint128,
+    pub fee: Uint128,
+    pub burned: Uint128,
+}
+
+/// Most addresses that may hold a balance at once. A transfer or mint that
+/// would add a holder beyond `max_holders` is refused, unless it is a mint
+/// and `minter_bypasses_holder_cap` is set, so the issuer can still onboard
+/// holders while secondary transfers cannot.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HolderCap {
+    pub max_holders: u64,
+    pub minter_bypasses_holder_cap: bool,
+}
+
+/// Anti-whale limits. `max_transfer_amount` bounds a single transfer and
+/// `max_wallet_balance` the balance a transfer or mint may leave a recipient
+/// with. `None` disables a limit; exempt addresses are not checked.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TransferLimits {
+    pub max_transfer_amount: Option<Uint128>,
+    pub max_wallet_balance: Option<Uint128>,
+}
+
+/// At most `limit` may be transferred out of an account per window of
+/// `window_seconds`. Windows are fixed epochs of block time.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RateLimit {
+    pub window_seconds: u64,
+    pub limit: Uint128,
+}
+
+/// Supply check spread over several `VerifyInvariants` calls: the balances
+/// summed so far and the address the next page must start after.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InvariantCheck {
+    pub sum: Uint128,
+    pub last_account: String,
+}
+
+/// Amount an account has transferred out during window `epoch`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WindowUsage {
+    pub epoch: u64,
+    pub used: Uint128,
+}
+
+/// Staking parameters. Stakers earn `reward_rate` of their stake for every
+/// full `epoch_seconds` staked, minted on claim.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingConfig {
+    pub unbonding_seconds: u64,
+    pub epoch_seconds: u64,
+    pub reward_rate: Decimal,
+}
+
+/// Staked tokens stay in the holder's balance but cannot be moved.
+/// Rewards accrue from `reward_from`; inflation paid to stakers accrues
+/// from the staker reward index at `pool_index`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakeInfo {
+    pub amount: Uint128,
+    pub reward_from: u64,
+    #[serde(default)]
+    pub pool_index: Decimal,
+}
+
+/// Rewards of one denom owed to a holder up to `index`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct DividendPosition {
+    pub index: Decimal,
+    pub owed: Uint128,
+}
+
+/// Where inflation goes: an address, or pro rata to current stakers.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InflationRecipient {
+    Address { address: String },
+    Stakers {},
+}
+
+/// Mints `rate_per_epoch` of the total supply every `epoch_seconds`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InflationConfig {
+    pub rate_per_epoch: Decimal,
+    pub epoch_seconds: u64,
+    pub recipient: InflationRecipient,
+}
+
+/// Treasury coins of `denom` are swapped for this token through the DEX
+/// `pair` and burned. When `permissionless`, anyone may trigger a buyback and
+/// is paid `bounty_bps` of the tokens bought; otherwise only the admin can.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BuybackConfig {
+    pub pair: String,
+    pub denom: String,
+    pub permissionless: bool,
+    pub bounty_bps: u16,
+}
+
+/// Price of a token unit, in reserve units, as a function of the curve
+/// supply.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CurveType {
+    /// `intercept + slope * supply`.
+    Linear { slope: Decimal, intercept: Decimal },
+    /// `base`, growing by `growth` every `step` token units.
+    Exponential {
+        base: Decimal,
+        growth: Decimal,
+        step: Uint128,
+    },
+}
+
+/// Tokens are bought with and sold for `reserve_denom` along `curve`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BondingCurve {
+    pub reserve_denom: String,
+    pub curve: CurveType,
+}
+
+/// Epoch `n` of inflation begins at `started_at + n * epoch_seconds`;
+/// `minted` epochs have been paid out.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct InflationEpochs {
+    pub started_at: u64,
+    pub minted: u64,
+}
+
+/// Unstaked tokens that stay locked until `release_at`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Unbonding {
+    pub amount: Uint128,
+    pub release_at: u64,
+}
+
+/// Tokens sent to `recipient` that it can claim once `release` has passed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedTransfer {
+    pub id: u64,
+    pub sender: String,
+    pub amount: Uint128,
+    pub release: Expiration,
+}
+
+/// Tokens held for `recipient` until `arbiter` releases them. Once
+/// `expires` has passed they can only be refunded to `sender`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Escrow {
+    pub id: u64,
+    pub sender: String,
+    pub recipient: String,
+    pub arbiter: String,
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
+/// Hashed-timelock swap: anyone revealing the sha256 preimage of `hash`
+/// before `expires` pays `amount` to `recipient`; afterwards it can only be
+/// refunded to `sender`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Swap {
+    pub id: u64,
+    pub sender: String,
+    pub recipient: String,
+    pub hash: HexBinary,
+    pub amount: Uint128,
+    pub expires: Expiration,
+}
+
+/// `amount` paid from `sender` to `recipient` at a constant rate between
+/// `start` and `end` (seconds). `withdrawn` has already been paid out.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stream {
+    pub id: u64,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub withdrawn: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+impl Stream {
+    /// How much of the stream has accrued to the recipient at `time`.
+    pub fn streamed_at(&self, time: u64) -> Uint128 {
+        let elapsed = time.clamp(self.start, self.end) - self.start;
+        pro_rata(self.amount, elapsed, self.end - self.start)
+    }
+
+    /// Accrued at `time` but not withdrawn yet.
+    pub fn withdrawable_at(&self, time: u64) -> Uint128 {
+        self.streamed_at(time) - self.withdrawn
+    }
+}
+
+/// Lets `merchant` pull `amount` from `subscriber` once per `period`
+/// (seconds), the next time from `next_collection`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Subscription {
+    pub id: u64,
+    pub subscriber: String,
+    pub merchant: String,
+    pub amount: Uint128,
+    pub period: u64,
+    pub next_collection: u64,
+}
+
+/// A contract notified of every balance change. When `tolerate_failure` is
+/// set, an error in the notification is ignored rather than reverting the
+/// transaction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Hook {
+    pub address: String,
+    pub tolerate_failure: bool,
+}
+
+/// Submessage dispatched at `height` that `reply` has to handle.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReplyContext {
+    pub height: u64,
+    pub kind: ReplyKind,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplyKind {
+    /// Notification of `hook`.
+    Hook { hook: String },
+    /// Flash mint of `amount` to `borrower` for `fee`, when the supply was
+    /// `supply_before`.
+    FlashMint {
+        borrower: String,
+        amount: Uint128,
+        fee: Uint128,
+        supply_before: Uint128,
+    },
+    /// Swap of `spent` treasury coins for tokens, while the contract held
+    /// `balance_before`, triggered by `caller`.
+    Buyback {
+        caller: String,
+        spent: Coin,
+        balance_before: Uint128,
+        bounty_bps: u16,
+    },
+    /// `Receive` call of `contract` after `amount` was sent to it, from
+    /// `refund_to`, or minted when `None`.
+    Receive {
+        contract: String,
+        refund_to: Option<String>,
+        amount: Uint128,
+    },
+}
+
+/// An ICS-20 channel connected to this contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChannelInfo {
+    pub id: String,
+    pub counterparty_endpoint: IbcEndpoint,
+    pub connection_id: String,
+}
+
+/// Tokens minted to a holder that become transferable over time: nothing
+/// before `cliff`, then linearly from `start` until all of `total` is vested
+/// at `end`. Vested tokens stay locked until claimed, and `released` counts
+/// what has been claimed so far. Times are in seconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    pub total: Uint128,
+    pub released: Uint128,
+    pub start: u64,
+    pub cliff: u64,
+    pub end: u64,
+}
+
+impl VestingSchedule {
+    pub fn vested_at(&self, time: u64) -> Uint128 {
+        if time < self.cliff {
+            Uint128::zero()
+        } else if time >= self.end {
+            self.total
+        } else {
+            pro_rata(self.total, time - self.start, self.end - self.start)
+        }
+    }
+}
+
+/// The active airdrop. Claims mint directly to the claimant, so tokens that
+/// are never claimed are never minted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AirdropConfig {
+    pub stage: u32,
+    pub merkle_root: HexBinary,
+    pub total_amount: Uint128,
+    pub claimed_amount: Uint128,
+    pub expiration: Expiration,
+}
+
+/// Token metadata persisted at instantiation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokenInfo {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+pub fn add_balance(balance: Uint128, amount: Uint128) -> Result<Uint128, ContractError> {
+    math::add_balance(balance.u128(), amount.u128())
+        .map(Uint128::from)
+        .ok_or(ContractError::Overflow {})
+}
+
+/// `bps` basis points of `amount`, rounded down.
+pub fn bps_of(amount: Uint128, bps: u16) -> StdResult<Uint128> {
+    math::bps_of(amount.u128(), bps)
+        .map(Uint128::new)
+        .ok_or_else(|| mul_overflow(amount, bps))
+}
+
+/// The share of `amount` that `part` out of `total` earns, rounded down.
+/// Nothing is owed over an empty period.
+fn pro_rata(amount: Uint128, part: u64, total: u64) -> Uint128 {
+    math::pro_rata(amount.u128(), part.into(), total.into()).map_or(Uint128::zero(), Uint128::new)
+}
+
+/// How far a reward index moves when `amount` is distributed over
+/// `eligible` units.
+pub fn index_step(amount: Uint128, eligible: Uint128) -> StdResult<Decimal> {
+    math::index_step(amount.u128(), eligible.u128())
+        .map(Decimal::raw)
+        .ok_or_else(|| mul_overflow(amount, eligible))
+}
+
+/// What `balance` earned while a reward index moved from `from` to `to`.
+pub fn accrued(balance: Uint128, from: Decimal, to: Decimal) -> StdResult<Uint128> {
+    let step = to - from;
+    math::accrued(balance.u128(), step.atomics().u128())
+        .map(Uint128::new)
+        .ok_or_else(|| mul_overflow(balance, step))
+}
+
+fn mul_overflow(a: impl ToString, b: impl ToString) -> StdError {
+    StdError::overflow(OverflowError::new(OverflowOperation::Mul, a, b))
+}
+
+pub fn sub_balance(balance: Uint128, amount: Uint128) -> Result<Uint128, ContractError> {
+    math::sub_balance(balance.u128(), amount.u128())
+        .map(Uint128::from)
+        .ok_or(ContractError::InsufficientFunds {
+            available: balance,
+            required: amount,
+        })
+}
+
+/// Largest embedded logo accepted, in bytes.
+pub const LOGO_SIZE_CAP: usize = 5 * 1024;
+
+/// The eight byte signature every PNG file starts with.
+const PNG_HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+/// Checks an embedded logo against the size cap and, since the blob is
+/// served as-is to wallets, that it at least looks like the claimed format.
+/// Url logos are not fetched and so are accepted unchecked.
+fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+    let data = match logo {
+        Logo::Url(_) => return Ok(()),
+        Logo::Embedded(EmbeddedLogo::Svg(data) | EmbeddedLogo::Png(data)) => data.as_slice(),
+    };
+    if data.len() > LOGO_SIZE_CAP {
+        return Err(ContractError::LogoTooBig {});
+    }
+    match logo {
+        Logo::Embedded(EmbeddedLogo::Svg(_)) => {
+            let start = data
+                .iter()
+                .position(|c| !c.is_ascii_whitespace())
+                .unwrap_or(data.len());
+            let text = &data[start..];
+            if !text.starts_with(b"<?xml ") && !text.starts_with(b"<svg") {
+                return Err(ContractError::InvalidXmlPreamble {});
+            }
+        }
+        _ => {
+            if !data.starts_with(&PNG_HEADER) {
+                return Err(ContractError::InvalidPngHeader {});
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Namespaces of the single values the original contract kept in
+/// `cosmwasm_storage` singletons. Per-address data could not be stored in
+/// that layout, so only these need converting.
+const LEGACY_SINGLETONS: &[&str] = &[
+    "total_supply",
+    "minter",
+    "cap",
+    "mint_allowlist_enabled",
+    "max_receive_per_tx",
+    "import_done",
+    "cap_locked",
+    "default_mint_recipient",
+    "soulbound",
+    "whale_threshold",
+    "freeze_grace_seconds",
+    "token_info",
+];
+
+/// Key a `cosmwasm_storage` singleton used for `namespace`: the namespace
+/// with a two byte big-endian length prefix.
+fn legacy_singleton_key(namespace: &str) -> Vec<u8> {
+    let mut key = (namespace.len() as u16).to_be_bytes().to_vec();
+    key.extend_from_slice(namespace.as_bytes());
+    key
+}
+
+/// Moves values written by the singleton-based layout to the `Item` keys
+/// used now. Both layouts serialize values as JSON, so the raw bytes are
+/// copied as-is. Returns the number of converted keys.
+pub fn migrate_singleton_layout(storage: &mut dyn Storage) -> StdResult<u32> {
+    let mut converted = 0;
+    for namespace in LEGACY_SINGLETONS {
+        let legacy_key = legacy_singleton_key(namespace);
+        if let Some(value) = storage.get(&legacy_key) {
+            if storage.get(namespace.as_bytes()).is_none() {
+                storage.set(namespace.as_bytes(), &value);
+            }
+            storage.remove(&legacy_key);
+            converted += 1;
+        }
+    }
+    Ok(converted)
+}
+
+/// Rewrites freezes stored as a bare activation time, from before partial
+/// freezes existed, as whole-account `FreezeInfo` entries. Returns the number
+/// of converted entries.
+pub fn migrate_frozen_balances(storage: &mut dyn Storage) -> StdResult<u32> {
+    const LEGACY: Map<&str, u64> = Map::new("frozen_balances");
+    let legacy = LEGACY
+        .range_raw(storage, None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((key, value)) => from_slice::<u64>(&value)
+                .ok()
+                .map(|active_from| Ok((key, active_from))),
+            Err(err) => Some(Err(err)),
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (key, active_from) in &legacy {
+        let address = String::from_utf8(key.clone())?;
+        FROZEN_BALANCES.save(
+            storage,
+            &address,
+            &FreezeInfo {
+                amount: None,
+                active_from: *active_from,
+                expires: Expiration::Never {},
+            },
+        )?;
+    }
+    Ok(legacy.len() as u32)
+}
+
+/// Moves `Minter` role grants from the generic role map into `MINTERS`,
+/// with no allowance limit. Returns the number of minters moved.
+pub fn migrate_minter_roles(storage: &mut dyn Storage) -> StdResult<u32> {
+    let minters = ROLES
+        .prefix(Role::Minter.as_str())
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for address in &minters {
+        ROLES.remove(storage, (Role::Minter.as_str(), address));
+        MINTERS.save(storage, address, &MinterInfo::default())?;
+    }
+    Ok(minters.len() as u32)
+}
+
+/// Token info as stored by stock cw20-base, which keeps the supply and the
+/// minter next to the metadata.
+#[derive(Serialize, Deserialize)]
+struct Cw20BaseTokenInfo {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: Uint128,
+    mint: Option<Cw20BaseMinterData>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cw20BaseMinterData {
+    minter: Addr,
+    cap: Option<Uint128>,
+}
+
+/// Rewrites the storage of a stock cw20-base deployment into this layout:
+/// token info is split into metadata, supply, minter and cap, and balances
+/// and allowances move to the keys used here. Marketing info and the logo
+/// share their keys and types with cw20-base and stay as they are.
+///
+/// Token info is converted on the first call. Each call then moves at most
+/// `limit` (capped at `MAX_MIGRATION_BATCH`) balances and allowances,
+/// indexing holders and spenders as it goes. Moved entries are removed from
+/// the legacy maps, so the next call resumes where this one stopped.
+/// `CW20_BASE_MIGRATION` stays set until nothing is left to move. Returns
+/// the number of balances and allowances moved and whether the conversion
+/// is done.
+pub fn migrate_from_cw20_base(
+    storage: &mut dyn Storage,
+    height: u64,
+    limit: Option<u32>,
+) -> StdResult<(u32, u32, bool)> {
+    const LEGACY_BALANCES: Map<&str, Uint128> = Map::new("balance");
+    const LEGACY_ALLOWANCES: Map<(&str, &str), AllowanceResponse> = Map::new("allowance");
+    const LEGACY_ALLOWANCES_SPENDER: Map<(&str, &str), AllowanceResponse> =
+        Map::new("allowance_spender");
+
+    if CW20_BASE_MIGRATION.may_load(storage)?.is_none() {
+        convert_cw20_base_token_info(storage, height)?;
+        HOLDER_COUNT.save(storage, &0)?;
+        CW20_BASE_MIGRATION.save(storage, &true)?;
+    }
+
+    let limit = limit
+        .unwrap_or(MAX_MIGRATION_BATCH)
+        .min(MAX_MIGRATION_BATCH) as usize;
+    let balances = LEGACY_BALANCES
+        .range(storage, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut holders = HOLDER_COUNT.load(storage)?;
+    for (address, balance) in &balances {
+        LEGACY_BALANCES.remove(storage, address);
+        BALANCES.save(storage, address, balance, height)?;
+        if !balance.is_zero() {
+            HOLDERS_BY_BALANCE.save(storage, (balance.u128(), address), &true)?;
+            holders += 1;
+        }
+    }
+    HOLDER_COUNT.save(storage, &holders)?;
+
+    let allowances = LEGACY_ALLOWANCES
+        .range(storage, None, None, Order::Ascending)
+        .take(limit - balances.len())
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((owner, spender), allowance) in &allowances {
+        LEGACY_ALLOWANCES.remove(storage, (owner, spender));
+        LEGACY_ALLOWANCES_SPENDER.remove(storage, (spender, owner));
+        ALLOWANCES.save(storage, (owner, spender), allowance)?;
+        ALLOWANCES_SPENDER.save(storage, (spender, owner), allowance)?;
+    }
+
+    let done = LEGACY_BALANCES.is_empty(storage) && LEGACY_ALLOWANCES.is_empty(storage);
+    if done {
+        CW20_BASE_MIGRATION.remove(storage);
+    }
+    Ok((balances.len() as u32, allowances.len() as u32, done))
+}
+
+fn convert_cw20_base_token_info(storage: &mut dyn Storage, height: u64) -> StdResult<()> {
+    const LEGACY_TOKEN_INFO: Item<Cw20BaseTokenInfo> = Item::new("token_info");
+
+    // shares its key with `TOKEN_INFO`, so read it before overwriting
+    let info = LEGACY_TOKEN_INFO.load(storage)?;
+    TOKEN_INFO.save(
+        storage,
+        &TokenInfo {
+            name: info.name,
+            symbol: info.symbol,
+            decimals: info.decimals,
+        },
+    )?;
+    TOTAL_SUPPLY.save(storage, &info.total_supply, height)?;
+    if let Some(mint) = info.mint {
+        MINTER.save(
+            storage,
+            &MinterResponse {
+                minter: mint.minter.into_string(),
+                cap: mint.cap,
+            },
+        )?;
+        if let Some(cap) = mint.cap {
+            CAP.save(storage, &cap)?;
+        }
+    }
+    Ok(())
+}
+
+/// Builds the holder index for deployments from before it existed. Does
+/// nothing once a holder count is stored. Returns the number of holders
+/// indexed.
+pub fn index_holders(storage: &mut dyn Storage) -> StdResult<u32> {
+    if HOLDER_COUNT.may_load(storage)?.is_some() {
+        return Ok(0);
+    }
+    let holders = BALANCES
+        .range(storage, None, None, Order::Ascending)
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(_, balance)| !balance.is_zero())
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    for (address, balance) in &holders {
+        HOLDERS_BY_BALANCE.save(storage, (balance.u128(), address), &true)?;
+    }
+    HOLDER_COUNT.save(storage, &(holders.len() as u64))?;
+    Ok(holders.len() as u32)
+}
+
+/// Fills the spender index of allowances granted before it existed. Does
+/// nothing once it holds any entry. Returns the number of allowances indexed.
+pub fn index_spender_allowances(storage: &mut dyn Storage) -> StdResult<u32> {
+    if !ALLOWANCES_SPENDER.is_empty(storage) {
+        return Ok(0);
+    }
+    let allowances = ALLOWANCES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((owner, spender), allowance) in &allowances {
+        ALLOWANCES_SPENDER.save(storage, (spender, owner), allowance)?;
+    }
+    Ok(allowances.len() as u32)
+}
+
+/// Totals the stakes of deployments from before `TOTAL_STAKED` existed.
+pub fn index_total_staked(storage: &mut dyn Storage) -> StdResult<Uint128> {
+    if let Some(total) = TOTAL_STAKED.may_load(storage)? {
+        return Ok(total);
+    }
+    let total = STAKES
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stake)| stake.amount))
+        .sum::<StdResult<Uint128>>()?;
+    TOTAL_STAKED.save(storage, &total)?;
+    Ok(total)
+}
+
+/// Read-only access to the contract storage, usable from queries.
+pub struct ReadonlyState<'a> {
+    pub storage: &'a dyn Storage,
+}
+
+impl<'a> ReadonlyState<'a> {
+    pub fn new(storage: &'a dyn Storage) -> Self {
+        Self { storage }
+    }
+
+    pub fn cap(&self) -> StdResult<Option<Uint128>> {
+        CAP.may_load(self.storage)
+    }
+
+    pub fn cap_locked(&self) -> StdResult<bool> {
+        Ok(CAP_LOCKED.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn multisig(&self) -> StdResult<Option<MultisigConfig>> {
+        MULTISIG.may_load(self.storage)
+    }
+
+    pub fn multisig_proposal(&self, id: u64) -> StdResult<MultisigProposal> {
+        MULTISIG_PROPOSALS.load(self.storage, id)
+    }
+
+    /// Proposals still short of approvals, by id.
+    pub fn multisig_proposals(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<MultisigProposal>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        MULTISIG_PROPOSALS
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, proposal)| proposal))
+            .collect()
+    }
+
+    pub fn timelock_delay(&self) -> StdResult<u64> {
+        Ok(TIMELOCK_DELAY
+            .may_load(self.storage)?
+            .unwrap_or(DEFAULT_TIMELOCK_DELAY))
+    }
+
+    pub fn scheduled_action(&self, id: u64) -> StdResult<ScheduledAction> {
+        SCHEDULED_ACTIONS.load(self.storage, id)
+    }
+
+    /// Actions scheduled but not yet executed or cancelled, by id.
+    pub fn scheduled_actions(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<ScheduledAction>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        SCHEDULED_ACTIONS
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, action)| action))
+            .collect()
+    }
+
+    /// The minter record, reporting the cap from `CAP`.
+    pub fn minter(&self) -> StdResult<Option<MinterResponse>> {
+        let cap = self.cap()?;
+        Ok(MINTER
+            .may_load(self.storage)?
+            .map(|minter| MinterResponse { cap, ..minter }))
+    }
+
+    pub fn mint_deadline(&self) -> StdResult<Option<Expiration>> {
+        MINT_DEADLINE.may_load(self.storage)
+    }
+
+    pub fn minting_renounced(&self) -> StdResult<bool> {
+        Ok(MINTING_RENOUNCED
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn minter_info(&self, address: &str) -> StdResult<Option<MinterInfo>> {
+        MINTERS.may_load(self.storage, address)
+    }
+
+    /// What `minter` may still mint today and when the day ends, if it has a
+    /// daily quota.
+    pub fn mint_quota(&self, minter: &str, block_time: u64) -> StdResult<Option<(Uint128, u64)>> {
+        let quota = match MINT_DAILY_QUOTAS.may_load(self.storage, minter)? {
+            Some(quota) => quota,
+            None => return Ok(None),
+        };
+        let day = block_time / SECONDS_PER_DAY;
+        let used = MINT_DAY_USAGE
+            .may_load(self.storage, minter)?
+            .filter(|usage| usage.epoch == day)
+            .map_or(Uint128::zero(), |usage| usage.used);
+        Ok(Some((
+            quota.saturating_sub(used),
+            (day + 1) * SECONDS_PER_DAY,
+        )))
+    }
+
+    /// Minters and their allowances, paginated by address.
+    pub fn minters(
+        &self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(String, MinterInfo)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        MINTERS
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn airdrop(&self) -> StdResult<Option<AirdropConfig>> {
+        AIRDROP.may_load(self.storage)
+    }
+
+    pub fn pending_minter(&self) -> StdResult<Option<String>> {
+        PENDING_MINTER.may_load(self.storage)
+    }
+
+    pub fn paused(&self) -> StdResult<bool> {
+        Ok(PAUSED.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// Whether `address` holds `role`. Minters are kept in `MINTERS`
+    /// together with their allowance, every other role in `ROLES`.
+    pub fn has_role(&self, role: Role, address: &str) -> StdResult<bool> {
+        if role == Role::Minter {
+            return Ok(MINTERS.has(self.storage, address));
+        }
+        Ok(ROLES
+            .may_load(self.storage, (role.as_str(), address))?
+            .unwrap_or_default())
+    }
+
+    /// Up to `MAX_LIMIT` holders of `role`, ordered by address.
+    pub fn role_holders(&self, role: Role) -> StdResult<Vec<String>> {
+        let holders = match role {
+            Role::Minter => MINTERS.keys(self.storage, None, None, Order::Ascending),
+            _ => ROLES
+                .prefix(role.as_str())
+                .keys(self.storage, None, None, Order::Ascending),
+        };
+        holders.take(MAX_LIMIT as usize).collect()
+    }
+
+    /// Whether any address currently holds `role`.
+    pub fn role_assigned(&self, role: Role) -> bool {
+        if role == Role::Minter {
+            return MINTERS
+                .keys_raw(self.storage, None, None, Order::Ascending)
+                .next()
+                .is_some();
+        }
+        ROLES
+            .prefix(role.as_str())
+            .keys_raw(self.storage, None, None, Order::Ascending)
+            .next()
+            .is_some()
+    }
+
+    pub fn balance(&self, address: &str) -> StdResult<Uint128> {
+        Ok(BALANCES
+            .may_load(self.storage, address)?
+            .unwrap_or_default())
+    }
+
+    /// Balance of `address` at the start of block `height`.
+    pub fn balance_at(&self, address: &str, height: u64) -> StdResult<Uint128> {
+        Ok(BALANCES
+            .may_load_at_height(self.storage, address, height)?
+            .unwrap_or_default())
+    }
+
+    /// Total supply at the start of block `height`.
+    pub fn total_supply_at(&self, height: u64) -> StdResult<Uint128> {
+        Ok(TOTAL_SUPPLY
+            .may_load_at_height(self.storage, height)?
+            .unwrap_or_default())
+    }
+
+    pub fn delegate(&self, address: &str) -> StdResult<Option<String>> {
+        DELEGATES.may_load(self.storage, address)
+    }
+
+    /// Voting power of `address` at the start of block `height`: its own
+    /// balance unless it had delegated it away, plus everything delegated
+    /// to it.
+    pub fn voting_power_at(&self, address: &str, height: u64) -> StdResult<Uint128> {
+        let own = match DELEGATES.may_load_at_height(self.storage, address, height)? {
+            Some(_) => Uint128::zero(),
+            None => self.balance_at(address, height)?,
+        };
+        let delegated = DELEGATED_POWER
+            .may_load_at_height(self.storage, address, height)?
+            .unwrap_or_default();
+        Ok(own + delegated)
+    }
+
+    /// Holder addresses in ascending order, starting after `start_after`.
+    pub fn all_accounts(
+        &self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<String>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        BALANCES
+            .keys(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect()
+    }
+
+    /// Freezes that have not expired at `block`, including those still in
+    /// their grace period, paginated by address.
+    pub fn all_frozen_accounts(
+        &self,
+        block: &BlockInfo,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(String, FreezeInfo)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        FROZEN_BALANCES
+            .range(self.storage, start, None, Order::Ascending)
+            .filter(|item| {
+                item.as_ref()
+                    .map_or(true, |(_, freeze)| !freeze.expires.is_expired(block))
+            })
+            .take(limit)
+            .collect()
+    }
+
+    /// Retained audit entries in the order they were recorded, paginated by
+    /// entry id.
+    pub fn audit_log(
+        &self,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<AuditEntry>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        AUDIT_LOG
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, entry)| entry))
+            .collect()
+    }
+
+    /// The latest balance changes of `address`, newest first, if it is or
+    /// was watched.
+    pub fn recent_activity(
+        &self,
+        address: &str,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<ActivityEntry>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(ACTIVITY_CAPACITY as u32) as usize;
+        ACTIVITY
+            .prefix(address)
+            .range(self.storage, None, None, Order::Descending)
+            .take(limit)
+            .map(|item| item.map(|(_, entry)| entry))
+            .collect()
+    }
+
+    /// Each watched account with its current balance.
+    pub fn watched_balances(&self) -> StdResult<Vec<(String, Uint128)>> {
+        WATCHED
+            .keys(self.storage, None, None, Order::Ascending)
+            .map(|address| {
+                let address = address?;
+                let balance = self.balance(&address)?;
+                Ok((address, balance))
+            })
+            .collect()
+    }
+
+    pub fn total_supply(&self) -> StdResult<Uint128> {
+        Ok(TOTAL_SUPPLY.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn token_info(&self) -> StdResult<TokenInfoResponse> {
+        let info = TOKEN_INFO.load(self.storage)?;
+        Ok(TokenInfoResponse {
+            name: info.name,
+            symbol: info.symbol,
+            decimals: info.decimals,
+            total_supply: self.total_supply()?,
+        })
+    }
+
+    pub fn marketing_info(&self) -> StdResult<MarketingInfoResponse> {
+        Ok(MARKETING_INFO.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn marketing_allowed(&self, sender: &str) -> StdResult<bool> {
+        Ok(self
+            .marketing_info()?
+            .marketing
+            .map_or(false, |marketing| marketing == sender))
+    }
+
+    /// The embedded logo with its mime type. Url logos have nothing to
+    /// download and are reported as not found.
+    pub fn download_logo(&self) -> StdResult<DownloadLogoResponse> {
+        match LOGO.may_load(self.storage)? {
+            Some(Logo::Embedded(EmbeddedLogo::Svg(data))) => Ok(DownloadLogoResponse {
+                mime_type: "image/svg+xml".to_string(),
+                data,
+            }),
+            Some(Logo::Embedded(EmbeddedLogo::Png(data))) => Ok(DownloadLogoResponse {
+                mime_type: "image/png".to_string(),
+                data,
+            }),
+            _ => Err(StdError::not_found("logo")),
+        }
+    }
+
+    pub fn freeze_info(&self, address: &str) -> StdResult<Option<FreezeInfo>> {
+        FROZEN_BALANCES.may_load(self.storage, address)
+    }
+
+    pub fn is_blacklisted(&self, address: &str) -> StdResult<bool> {
+        Ok(BLACKLIST.has(self.storage, address))
+    }
+
+    /// Audit metadata of the freeze on `address`. Freezes placed before
+    /// records were kept have none.
+    pub fn freeze_record(&self, address: &str) -> StdResult<Option<FreezeRecord>> {
+        FREEZE_RECORDS.may_load(self.storage, address)
+    }
+
+    /// The freeze on `address` if it is in effect at `block`: past its
+    /// grace period and not yet expired.
+    pub fn active_freeze(&self, address: &str, block: &BlockInfo) -> StdResult<Option<FreezeInfo>> {
+        Ok(self
+            .freeze_info(address)?
+            .filter(|freeze| freeze.is_active(block)))
+    }
+
+    /// Whether the whole account is frozen at `block`. Partial freezes are
+    /// reported by `frozen_amount` instead.
+    pub fn is_frozen(&self, address: &str, block: &BlockInfo) -> StdResult<bool> {
+        Ok(self
+            .active_freeze(address, block)?
+            .map_or(false, |freeze| freeze.amount.is_none()))
+    }
+
+    pub fn frozen_total(&self) -> StdResult<Uint128> {
+        Ok(FROZEN_TOTAL.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// How much of `address`'s balance is frozen at `block`.
+    pub fn frozen_amount(&self, address: &str, block: &BlockInfo) -> StdResult<Uint128> {
+        match self.active_freeze(address, block)? {
+            Some(FreezeInfo {
+                amount: Some(amount),
+                ..
+            }) => Ok(amount),
+            Some(FreezeInfo { amount: None, .. }) => self.balance(address),
+            None => Ok(Uint128::zero()),
+        }
+    }
+
+    pub fn mint_allowed_to(&self, address: &str) -> StdResult<bool> {
+        if !MINT_ALLOWLIST_ENABLED
+            .may_load(self.storage)?
+            .unwrap_or_default()
+        {
+            return Ok(true);
+        }
+        Ok(MINT_ALLOWLIST
+            .may_load(self.storage, address)?
+            .unwrap_or_default())
+    }
+
+    pub fn kyc_registry(&self) -> StdResult<Option<String>> {
+        KYC_REGISTRY.may_load(self.storage)
+    }
+
+    pub fn permissioned(&self) -> StdResult<bool> {
+        Ok(PERMISSIONED.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// Whether transfers may credit `address`. Always true outside
+    /// permissioned mode.
+    pub fn may_receive(&self, address: &str) -> StdResult<bool> {
+        Ok(!self.permissioned()? || ALLOWLIST.has(self.storage, address))
+    }
+
+    pub fn allowlist(
+        &self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<String>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        ALLOWLIST
+            .keys(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect()
+    }
+
+    pub fn transfer_limits(&self) -> StdResult<TransferLimits> {
+        Ok(TRANSFER_LIMITS.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn rate_limit(&self) -> StdResult<Option<RateLimit>> {
+        RATE_LIMIT.may_load(self.storage)
+    }
+
+    /// What `address` may still transfer in the current window and when the
+    /// window ends, or `None` if transfers from it are not rate limited.
+    pub fn transfer_quota(
+        &self,
+        address: &str,
+        block_time: u64,
+    ) -> StdResult<Option<(Uint128, u64)>> {
+        let rate_limit = match self.rate_limit()? {
+            Some(rate_limit) if !self.limit_exempt(address)? => rate_limit,
+            _ => return Ok(None),
+        };
+        let epoch = block_time / rate_limit.window_seconds;
+        let used = TRANSFER_WINDOWS
+            .may_load(self.storage, address)?
+            .filter(|usage| usage.epoch == epoch)
+            .map_or(Uint128::zero(), |usage| usage.used);
+        Ok(Some((
+            rate_limit.limit.saturating_sub(used),
+            (epoch + 1) * rate_limit.window_seconds,
+        )))
+    }
+
+    pub fn spend_limit(&self, owner: &str, spender: &str) -> StdResult<Option<RateLimit>> {
+        SPEND_LIMITS.may_load(self.storage, (owner, spender))
+    }
+
+    /// What `spender` can still move from `owner` in the current period and
+    /// when it resets, if a spend limit is set.
+    pub fn spend_quota(
+        &self,
+        owner: &str,
+        spender: &str,
+        block_time: u64,
+    ) -> StdResult<Option<(Uint128, u64)>> {
+        let limit = match self.spend_limit(owner, spender)? {
+            Some(limit) => limit,
+            None => return Ok(None),
+        };
+        let epoch = block_time / limit.window_seconds;
+        let used = SPEND_WINDOWS
+            .may_load(self.storage, (owner, spender))?
+            .filter(|usage| usage.epoch == epoch)
+            .map_or(Uint128::zero(), |usage| usage.used);
+        Ok(Some((
+            limit.limit.saturating_sub(used),
+            (epoch + 1) * limit.window_seconds,
+        )))
+    }
+
+    pub fn limit_exempt(&self, address: &str) -> StdResult<bool> {
+        Ok(LIMIT_EXEMPT.has(self.storage, address))
+    }
+
+    /// The fee treasury and limit-exempt addresses such as pools may receive
+    /// more than `max_receive_per_tx` at once.
+    fn receive_limit_exempt(&self, address: &str) -> StdResult<bool> {
+        let treasury = self
+            .fee_config()?
+            .map_or(false, |config| config.treasury == address);
+        Ok(treasury || self.limit_exempt(address)?)
+    }
+
+    pub fn holder_cap(&self) -> StdResult<Option<HolderCap>> {
+        HOLDER_CAP.may_load(self.storage)
+    }
+
+    /// Refuses to credit `amount` to `recipient` if that would add a holder
+    /// beyond the holder cap. `minting` tells whether the credit is a mint.
+    pub fn ensure_holder_room(
+        &self,
+        recipient: &str,
+        amount: Uint128,
+        minting: bool,
+    ) -> Result<(), ContractError> {
+        let cap = match self.holder_cap()? {
+            Some(cap) if !(minting && cap.minter_bypasses_holder_cap) => cap,
+            _ => return Ok(()),
+        };
+        if !amount.is_zero()
+            && self.balance(recipient)?.is_zero()
+            && self.holder_count()? >= cap.max_holders
+        {
+            return Err(ContractError::HolderCapReached {
+                max: cap.max_holders,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn max_receive_per_tx(&self) -> StdResult<Uint128> {
+        Ok(MAX_RECEIVE_PER_TX
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn default_mint_recipient(&self) -> StdResult<Option<String>> {
+        DEFAULT_MINT_RECIPIENT.may_load(self.storage)
+    }
+
+    pub fn soulbound(&self) -> StdResult<bool> {
+        Ok(SOULBOUND.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn ignore_unknown_messages(&self) -> StdResult<bool> {
+        Ok(IGNORE_UNKNOWN_MESSAGES
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn locked_balance(&self, address: &str) -> StdResult<Uint128> {
+        Ok(LOCKED_BALANCES
+            .may_load(self.storage, address)?
+            .unwrap_or_default())
+    }
+
+    pub fn vesting(&self, address: &str) -> StdResult<Option<VestingSchedule>> {
+        VESTING.may_load(self.storage, address)
+    }
+
+    /// Part of `address`'s balance that cannot be moved at `block`:
+    /// locked tokens, vesting tokens that have not been claimed yet, staked
+    /// or unbonding tokens, and frozen tokens. It can exceed the balance.
+    pub fn unspendable(&self, address: &str, block: &BlockInfo) -> StdResult<Uint128> {
+        let unreleased = self.vesting(address)?.map_or(Uint128::zero(), |schedule| {
+            schedule.total - schedule.released
+        });
+        // a partial freeze may exceed the balance, so the holds saturate
+        // instead of overflowing: nothing is spendable either way
+        let unbonding = self
+            .unbonding(address)?
+            .iter()
+            .fold(Uint128::zero(), |sum, entry| {
+                sum.saturating_add(entry.amount)
+            });
+        Ok(self
+            .locked_balance(address)?
+            .saturating_add(unreleased)
+            .saturating_add(self.staked(address)?)
+            .saturating_add(unbonding)
+            .saturating_add(self.frozen_amount(address, block)?))
+    }
+
+    pub fn fee_config(&self) -> StdResult<Option<FeeConfig>> {
+        FEE_CONFIG.may_load(self.storage)
+    }
+
+    pub fn buyback(&self) -> StdResult<Option<BuybackConfig>> {
+        BUYBACK.may_load(self.storage)
+    }
+
+    pub fn treasury(&self, denom: &str) -> StdResult<Uint128> {
+        Ok(TREASURY.may_load(self.storage, denom)?.unwrap_or_default())
+    }
+
+    pub fn flash_mint_fee_bps(&self) -> StdResult<u16> {
+        Ok(FLASH_MINT_FEE_BPS
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn burn_bps(&self) -> StdResult<u16> {
+        Ok(BURN_BPS.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// Fee rate charged on a transfer of `amount` from `sender` to
+    /// `recipient`, in basis points.
+    pub fn transfer_fee_bps(
+        &self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+    ) -> StdResult<u16> {
+        Ok(match self.fee_config()? {
+            Some(config)
+                if !config
+                    .exempt
+                    .iter()
+                    .any(|address| address == sender || address == recipient) =>
+            {
+                config.bps_for(amount)
+            }
+            _ => 0,
+        })
+    }
+
+    pub fn staking_config(&self) -> StdResult<Option<StakingConfig>> {
+        STAKING_CONFIG.may_load(self.storage)
+    }
+
+    pub fn total_staked(&self) -> StdResult<Uint128> {
+        Ok(TOTAL_STAKED.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn staker_reward_index(&self) -> StdResult<Decimal> {
+        Ok(STAKER_REWARD_INDEX
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    /// Native denoms the admin allows as holder rewards.
+    pub fn reward_denoms(&self) -> StdResult<Vec<String>> {
+        REWARD_DENOMS
+            .keys(self.storage, None, None, Order::Ascending)
+            .collect()
+    }
+
+    pub fn reward_denom_allowed(&self, denom: &str) -> StdResult<bool> {
+        Ok(REWARD_DENOMS
+            .may_load(self.storage, denom)?
+            .unwrap_or_default())
+    }
+
+    /// Reward denoms with the amount paid so far per eligible token.
+    pub fn dividend_denoms(&self) -> StdResult<Vec<(String, Decimal)>> {
+        DIVIDEND_INDEX
+            .range(self.storage, None, None, Order::Ascending)
+            .collect()
+    }
+
+    /// Rewards of `denom` deposited but not yet claimed.
+    pub fn dividend_pool(&self, denom: &str) -> StdResult<Uint128> {
+        Ok(DIVIDEND_POOL
+            .may_load(self.storage, denom)?
+            .unwrap_or_default())
+    }
+
+    /// Rewards `address` could claim now, by denom.
+    pub fn pending_dividends(&self, address: &str) -> StdResult<Vec<Coin>> {
+        if DIVIDEND_CUSTODIAN.may_load(self.storage)?.as_deref() == Some(address) {
+            return Ok(vec![]);
+        }
+        let balance = self.balance(address)?;
+        let mut pending = vec![];
+        for (denom, index) in self.dividend_denoms()? {
+            let position = DIVIDEND_POSITIONS
+                .may_load(self.storage, (address, &denom))?
+                .unwrap_or_default();
+            let amount = position
+                .owed
+                .checked_add(accrued(balance, position.index, index)?)?;
+            if !amount.is_zero() {
+                pending.push(Coin { denom, amount });
+            }
+        }
+        Ok(pending)
+    }
+
+    pub fn exchange_rate(&self) -> StdResult<Option<Decimal>> {
+        EXCHANGE_RATE.may_load(self.storage)
+    }
+
+    /// Token value of `shares` at the current exchange rate, rounded down.
+    /// Outside share mode one share is one token.
+    pub fn share_value(&self, shares: Uint128) -> StdResult<Uint128> {
+        Ok(match self.exchange_rate()? {
+            Some(rate) => shares * rate,
+            None => shares,
+        })
+    }
+
+    pub fn bonding_curve(&self) -> StdResult<Option<BondingCurve>> {
+        BONDING_CURVE.may_load(self.storage)
+    }
+
+    pub fn curve_reserve(&self) -> StdResult<Uint128> {
+        Ok(CURVE_RESERVE.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn curve_supply(&self) -> StdResult<Uint128> {
+        Ok(CURVE_SUPPLY.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn inflation(&self) -> StdResult<Option<InflationConfig>> {
+        INFLATION.may_load(self.storage)
+    }
+
+    pub fn inflation_epochs(&self) -> StdResult<InflationEpochs> {
+        Ok(INFLATION_EPOCHS.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// When the next unpaid epoch begins and what it would mint at the
+    /// current supply.
+    pub fn next_inflation(&self) -> StdResult<Option<(u64, Uint128)>> {
+        let config = match self.inflation()? {
+            Some(config) => config,
+            None => return Ok(None),
+        };
+        let epochs = self.inflation_epochs()?;
+        let next_epoch_at = epochs.started_at + (epochs.minted + 1) * config.epoch_seconds;
+        Ok(Some((
+            next_epoch_at,
+            self.total_supply()? * config.rate_per_epoch,
+        )))
+    }
+
+    pub fn staked(&self, address: &str) -> StdResult<Uint128> {
+        Ok(STAKES
+            .may_load(self.storage, address)?
+            .map_or(Uint128::zero(), |stake| stake.amount))
+    }
+
+    /// Transfers locked for `recipient` that it has not claimed yet,
+    /// paginated by id.
+    pub fn locked_transfers(
+        &self,
+        recipient: &str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<LockedTransfer>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        LOCKED_TRANSFERS
+            .prefix(recipient)
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| item.map(|(_, transfer)| transfer))
+            .collect()
+    }
+
+    pub fn escrow(&self, id: u64) -> StdResult<Escrow> {
+        ESCROWS.load(self.storage, id)
+    }
+
+    pub fn swap(&self, id: u64) -> StdResult<Swap> {
+        SWAPS.load(self.storage, id)
+    }
+
+    pub fn stream(&self, id: u64) -> StdResult<Stream> {
+        STREAMS.load(self.storage, id)
+    }
+
+    /// Open streams sent by `sender`, paginated by id.
+    pub fn streams_by_sender(
+        &self,
+        sender: &str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Stream>> {
+        self.streams_in(STREAMS_BY_SENDER, sender, start_after, limit)
+    }
+
+    /// Open streams paying `recipient`, paginated by id.
+    pub fn streams_by_recipient(
+        &self,
+        recipient: &str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Stream>> {
+        self.streams_in(STREAMS_BY_RECIPIENT, recipient, start_after, limit)
+    }
+
+    pub fn subscription(&self, id: u64) -> StdResult<Subscription> {
+        SUBSCRIPTIONS.load(self.storage, id)
+    }
+
+    /// Subscriptions of `subscriber`, paginated by id.
+    pub fn subscriptions_by_subscriber(
+        &self,
+        subscriber: &str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Subscription>> {
+        self.subscriptions_in(SUBSCRIPTIONS_BY_SUBSCRIBER, subscriber, start_after, limit)
+    }
+
+    /// Subscriptions paying `merchant`, paginated by id.
+    pub fn subscriptions_by_merchant(
+        &self,
+        merchant: &str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Subscription>> {
+        self.subscriptions_in(SUBSCRIPTIONS_BY_MERCHANT, merchant, start_after, limit)
+    }
+
+    fn subscriptions_in<'k>(
+        &self,
+        index: Map<'k, (&'k str, u64), bool>,
+        address: &'k str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Subscription>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        index
+            .prefix(address)
+            .keys(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| self.subscription(id?))
+            .collect()
+    }
+
+    fn streams_in<'k>(
+        &self,
+        index: Map<'k, (&'k str, u64), bool>,
+        address: &'k str,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<Stream>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        index
+            .prefix(address)
+            .keys(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|id| self.stream(id?))
+            .collect()
+    }
+
+    pub fn non_circulating(&self) -> StdResult<Vec<String>> {
+        NON_CIRCULATING
+            .keys(self.storage, None, None, Order::Ascending)
+            .collect()
+    }
+
+    /// Total supply less the balances of the non-circulating accounts.
+    pub fn circulating_supply(&self) -> StdResult<Uint128> {
+        let mut held = Uint128::zero();
+        for address in self.non_circulating()? {
+            held += self.balance(&address)?;
+        }
+        Ok(self.total_supply()?.saturating_sub(held))
+    }
+
+    /// Whether a cw20-base deployment still has balances or allowances left
+    /// to convert.
+    pub fn migration_in_progress(&self) -> StdResult<bool> {
+        Ok(CW20_BASE_MIGRATION
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn holder_count(&self) -> StdResult<u64> {
+        Ok(HOLDER_COUNT.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    /// The `limit` largest balances, largest first.
+    pub fn top_holders(&self, limit: Option<u32>) -> StdResult<Vec<(String, Uint128)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        HOLDERS_BY_BALANCE
+            .keys(self.storage, None, None, Order::Descending)
+            .take(limit)
+            .map(|item| item.map(|(balance, address)| (address, Uint128::new(balance))))
+            .collect()
+    }
+
+    pub fn wrapped_denom(&self) -> StdResult<Option<String>> {
+        WRAPPED_DENOM.may_load(self.storage)
+    }
+
+    pub fn channel(&self, id: &str) -> StdResult<Option<ChannelInfo>> {
+        CHANNELS.may_load(self.storage, id)
+    }
+
+    pub fn channel_escrow(&self, id: &str) -> StdResult<Uint128> {
+        Ok(CHANNEL_ESCROW
+            .may_load(self.storage, id)?
+            .unwrap_or_default())
+    }
+
+    /// Open channels with the amount each escrows, paginated by channel id.
+    pub fn channels(
+        &self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<(ChannelInfo, Uint128)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        CHANNELS
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                let (id, info) = item?;
+                Ok((info, self.channel_escrow(&id)?))
+            })
+            .collect()
+    }
+
+    pub fn unbonding(&self, address: &str) -> StdResult<Vec<Unbonding>> {
+        Ok(UNBONDING
+            .may_load(self.storage, address)?
+            .unwrap_or_default())
+    }
+
+    /// Rewards owed to `address`, including those accrued since its stake
+    /// last changed, and the number of full epochs accrued since then.
+    pub fn pending_rewards(&self, address: &str, block_time: u64) -> StdResult<(Uint128, u64)> {
+        let owed = OWED_REWARDS
+            .may_load(self.storage, address)?
+            .unwrap_or_default();
+        let (config, stake) = match (
+            self.staking_config()?,
+            STAKES.may_load(self.storage, address)?,
+        ) {
+            (Some(config), Some(stake)) => (config, stake),
+            _ => return Ok((owed, 0)),
+        };
+        let epochs = block_time.saturating_sub(stake.reward_from) / config.epoch_seconds;
+        let rewards = (stake.amount * config.reward_rate).checked_mul(Uint128::from(epochs))?;
+        let pooled = accrued(stake.amount, stake.pool_index, self.staker_reward_index()?)?;
+        Ok((rewards.checked_add(pooled)?.checked_add(owed)?, epochs))
+    }
+
+    pub fn whale_threshold(&self) -> StdResult<Uint128> {
+        Ok(WHALE_THRESHOLD.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn freeze_grace_seconds(&self) -> StdResult<u64> {
+        Ok(FREEZE_GRACE_SECONDS
+            .may_load(self.storage)?
+            .unwrap_or_default())
+    }
+
+    pub fn hooks(&self) -> StdResult<Vec<Hook>> {
+        Ok(HOOKS.may_load(self.storage)?.unwrap_or_default())
+    }
+
+    pub fn receive_failure_tolerated(&self, contract: &str) -> StdResult<bool> {
+        Ok(TOLERANT_RECEIVERS
+            .may_load(self.storage, contract)?
+            .unwrap_or(false))
+    }
+
+    pub fn nonce(&self, address: &str) -> StdResult<u64> {
+        Ok(NONCES.may_load(self.storage, address)?.unwrap_or_default())
+    }
+
+    pub fn allowance(&self, owner: &str, spender: &str) -> StdResult<AllowanceResponse> {
+        Ok(ALLOWANCES
+            .may_load(self.storage, (owner, spender))?
+            .unwrap_or_default())
+    }
+
+    /// The first `MAX_EXPORT_ALLOWANCES` allowances granted by `owner`,
+    /// expired ones included, and whether more follow.
+    pub fn allowances_granted(&self, owner: &str) -> StdResult<(Vec<AllowanceInfo>, bool)> {
+        let mut allowances = ALLOWANCES
+            .prefix(owner)
+            .range(self.storage, None, None, Order::Ascending)
+            .take(MAX_EXPORT_ALLOWANCES + 1)
+            .map(|item| {
+                item.map(|(spender, allowance)| AllowanceInfo {
+                    spender,
+                    allowance: allowance.allowance,
+                    expires: allowance.expires,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let more = allowances.len() > MAX_EXPORT_ALLOWANCES;
+        allowances.truncate(MAX_EXPORT_ALLOWANCES);
+        Ok((allowances, more))
+    }
+
+    /// Allowances granted to `spender`, ordered by owner address.
+    pub fn all_spender_allowances(
+        &self,
+        spender: &str,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<SpenderAllowanceInfo>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        ALLOWANCES_SPENDER
+            .prefix(spender)
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(owner, allowance)| SpenderAllowanceInfo {
+                    owner,
+                    allowance: allowance.allowance,
+                    expires: allowance.expires,
+                })
+            })
+            .collect()
+    }
+
+    /// The allowance of `spender` over the tokens of `owner`, or none once it
+    /// has expired at `block`.
+    pub fn active_allowance(
+        &self,
+        owner: &str,
+        spender: &str,
+        block: &BlockInfo,
+    ) -> StdResult<AllowanceResponse> {
+        let allowance = self.allowance(owner, spender)?;
+        if allowance.expires.is_expired(block) {
+            return Ok(AllowanceResponse::default());
+        }
+        Ok(allowance)
+    }
+
+    /// Up to `limit` allowances of every owner after `start_after`, ordered
+    /// by owner and then spender address.
+    pub fn allowances_page(
+        &self,
+        start_after: Option<(&str, &str)>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<((String, String), AllowanceResponse)>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        ALLOWANCES
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect()
+    }
+
+    /// Allowances granted by `owner`, ordered by spender address.
+    pub fn all_allowances(
+        &self,
+        owner: &str,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> StdResult<Vec<AllowanceInfo>> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(Bound::exclusive);
+        ALLOWANCES
+            .prefix(owner)
+            .range(self.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|item| {
+                item.map(|(spender, allowance)| AllowanceInfo {
+                    spender,
+                    allowance: allowance.allowance,
+                    expires: allowance.expires,
+                })
+            })
+            .collect()
+    }
+
+    /// Works out the fee and burn share of a transfer of `amount`.
+    pub fn split(
+        &self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+    ) -> Result<TransferOutcome, ContractError> {
+        let fee_bps = self.transfer_fee_bps(sender, recipient, amount)?;
+        let (fee, burned) = math::split(amount.u128(), fee_bps, self.burn_bps()?)
+            .ok_or(ContractError::InvalidFee {})?;
+        let (fee, burned) = (Uint128::new(fee), Uint128::new(burned));
+        Ok(TransferOutcome {
+            sent: amount,
+            received: sub_balance(sub_balance(amount, fee)?, burned)?,
+            fee,
+            burned,
+        })
+    }
+
+    /// Refuses a transfer out of `sender` while the token is paused or
+    /// soulbound, or `sender` is frozen or blacklisted.
+    pub fn ensure_can_send(&self, sender: &str, block: &BlockInfo) -> Result<(), ContractError> {
+        if self.paused()? {
+            return Err(ContractError::Paused {});
+        }
+        if self.soulbound()? {
+            return Err(ContractError::NonTransferable {});
+        }
+        if self.is_frozen(sender, block)? {
+            return Err(ContractError::AccountFrozen {
+                address: sender.to_string(),
+            });
+        }
+        self.ensure_not_blacklisted(sender)
+    }
+
+    pub fn ensure_within_transfer_limit(
+        &self,
+        sender: &str,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        match self.transfer_limits()?.max_transfer_amount {
+            Some(max) if amount > max && !self.limit_exempt(sender)? => {
+                Err(ContractError::MaxTransferExceeded { max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn ensure_within_wallet_limit(
+        &self,
+        address: &str,
+        new_balance: Uint128,
+    ) -> Result<(), ContractError> {
+        match self.transfer_limits()?.max_wallet_balance {
+            Some(max) if new_balance > max && !self.limit_exempt(address)? => {
+                Err(ContractError::MaxWalletBalanceExceeded { max })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn ensure_not_blacklisted(&self, address: &str) -> Result<(), ContractError> {
+        if self.is_blacklisted(address)? {
+            return Err(ContractError::AccountBlacklisted {
+                address: address.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `sender` can spend `amount` of its unlocked, unfrozen
+    /// balance, and returns that balance.
+    pub fn ensure_spendable(
+        &self,
+        sender: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        let sender_balance = self.balance(sender)?;
+        if sender_balance < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: sender_balance,
+                required: amount,
+            });
+        }
+        let transferable = sender_balance.saturating_sub(self.unspendable(sender, block)?);
+        if transferable < amount {
+            return Err(ContractError::LockedTokens {
+                transferable,
+                required: amount,
+            });
+        }
+        Ok(sender_balance)
+    }
+
+    /// Checks that `recipient` may be credited `amount`, and returns the
+    /// balance it would then hold.
+    pub fn ensure_can_receive(
+        &self,
+        recipient: &str,
+        amount: Uint128,
+    ) -> Result<Uint128, ContractError> {
+        self.ensure_not_blacklisted(recipient)?;
+        if !self.may_receive(recipient)? {
+            return Err(ContractError::NotOnAllowlist {
+                address: recipient.to_string(),
+            });
+        }
+        let max_receive = self.max_receive_per_tx()?;
+        if !max_receive.is_zero()
+            && amount > max_receive
+            && !self.receive_limit_exempt(recipient)?
+        {
+            return Err(ContractError::ReceiveLimitExceeded { max: max_receive });
+        }
+        self.ensure_holder_room(recipient, amount, false)?;
+        let new_balance = add_balance(self.balance(recipient)?, amount)?;
+        self.ensure_within_wallet_limit(recipient, new_balance)?;
+        Ok(new_balance)
+    }
+
+    /// Checks `amount` against what `sender` may still send this window, and
+    /// returns that when it has a quota.
+    pub fn ensure_within_quota(
+        &self,
+        sender: &str,
+        amount: Uint128,
+        block_time: u64,
+    ) -> Result<Option<Uint128>, ContractError> {
+        match self.transfer_quota(sender, block_time)? {
+            Some((remaining, _)) if amount > remaining => {
+                Err(ContractError::RateLimitExceeded { remaining })
+            }
+            quota => Ok(quota.map(|(remaining, _)| remaining)),
+        }
+    }
+
+    /// What a transfer of `amount` from `sender` to `recipient` would do,
+    /// running the checks of `State::transfer` without storing anything.
+    pub fn preview_transfer(
+        &self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<TransferOutcome, ContractError> {
+        self.ensure_can_send(sender, block)?;
+        if sender == recipient || amount.is_zero() {
+            return Ok(TransferOutcome {
+                sent: amount,
+                received: amount,
+                ..TransferOutcome::default()
+            });
+        }
+        self.ensure_within_transfer_limit(sender, amount)?;
+        self.ensure_within_quota(sender, amount, block.time.seconds())?;
+        self.ensure_spendable(sender, amount, block)?;
+        let outcome = self.split(sender, recipient, amount)?;
+        self.ensure_can_receive(recipient, outcome.received)?;
+        Ok(outcome)
+    }
+}
+
+/// Typed access to the contract storage. Every key lives in its own
+/// `Item`/`Map` namespace, so per-address entries no longer collide.
+pub struct State<'a> {
+    pub storage: &'a mut dyn Storage,
+}
+
+impl<'a> State<'a> {
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        Self { storage }
+    }
+
+    /// Borrows the state read-only for the getters on `ReadonlyState`.
+    pub fn read(&self) -> ReadonlyState<'_> {
+        ReadonlyState::new(&*self.storage)
+    }
+
+    pub fn update_cap(&mut self, new_cap: Uint128) -> Result<(), ContractError> {
+        if self.read().cap_locked()? && self.read().cap()? != Some(new_cap) {
+            return Err(ContractError::CapLocked {});
+        }
+        CAP.save(self.storage, &new_cap)?;
+        Ok(())
+    }
+
+    pub fn update_timelock_delay(&mut self, seconds: u64) -> Result<(), ContractError> {
+        TIMELOCK_DELAY.save(self.storage, &seconds)?;
+        Ok(())
+    }
+
+    fn ensure_cap_increase(&self, new_cap: Uint128) -> Result<(), ContractError> {
+        self.ensure_minting_allowed()?;
+        if self.read().cap_locked()? {
+            return Err(ContractError::CapLocked {});
+        }
+        match self.read().cap()? {
+            Some(cap) if new_cap > cap => Ok(()),
+            // tokens without a cap have nothing to raise
+            _ => Err(ContractError::InvalidCapIncrease {}),
+        }
+    }
+
+    /// Raises the cap to `new_cap` and returns the previous cap.
+    pub fn increase_cap(&mut self, new_cap: Uint128) -> Result<Uint128, ContractError> {
+        self.ensure_cap_increase(new_cap)?;
+        let previous = self.read().cap()?.unwrap_or_default();
+        self.update_cap(new_cap)?;
+        Ok(previous)
+    }
+
+    /// Queues `action` to be executable once the timelock delay has passed.
+    /// Actions that could not succeed right now are refused up front.
+    pub fn schedule_admin_action(
+        &mut self,
+        action: AdminAction,
+        now: u64,
+    ) -> Result<ScheduledAction, ContractError> {
+        match &action {
+            AdminAction::UpdateMinter { .. } => self.ensure_minting_allowed()?,
+            AdminAction::IncreaseCap { new_cap } => self.ensure_cap_increase(*new_cap)?,
+            AdminAction::UpdateFeeConfig {
+                fee_bps,
+                treasury,
+                exempt,
+                tiers,
+            } => self.ensure_valid_fee_config(&FeeConfig {
+                fee_bps: *fee_bps,
+                treasury: treasury.clone(),
+                exempt: exempt.clone(),
+                tiers: tiers.clone(),
+            })?,
+            AdminAction::UpdateTimelockDelay { .. } => {}
+        }
+        let id = SCHEDULED_ACTION_SEQ
+            .may_load(self.storage)?
+            .unwrap_or_default()
+            + 1;
+        SCHEDULED_ACTION_SEQ.save(self.storage, &id)?;
+        let scheduled = ScheduledAction {
+            id,
+            action,
+            executable_at: now + self.read().timelock_delay()?,
+        };
+        SCHEDULED_ACTIONS.save(self.storage, id, &scheduled)?;
+        Ok(scheduled)
+    }
+
+    /// Replaces the co-signers, or removes the multisig when `None`. Open
+    /// proposals stay open, but only approvals of current signers count.
+    pub fn update_multisig(&mut self, config: Option<MultisigConfig>) -> Result<(), ContractError> {
+        match config {
+            Some(config) => {
+                if config.threshold == 0 || config.threshold as usize > config.signers.len() {
+                    return Err(ContractError::InvalidThreshold {});
+                }
+                MULTISIG.save(self.storage, &config)?
+            }
+            None => MULTISIG.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    fn ensure_signer(&self, address: &str) -> Result<MultisigConfig, ContractError> {
+        let config = self
+            .read()
+            .multisig()?
+            .ok_or(ContractError::NoMultisig {})?;
+        if !config.signers.iter().any(|signer| signer == address) {
+            return Err(ContractError::Unauthorized {});
+        }
+        Ok(config)
+    }
+
+    /// Opens a proposal for `action`, approved by `proposer`.
+    pub fn propose_multisig_action(
+        &mut self,
+        proposer: &str,
+        action: MultisigAction,
+    ) -> Result<(MultisigProposal, bool), ContractError> {
+        self.ensure_signer(proposer)?;
+        let id = MULTISIG_PROPOSAL_SEQ
+            .may_load(self.storage)?
+            .unwrap_or_default()
+            + 1;
+        MULTISIG_PROPOSAL_SEQ.save(self.storage, &id)?;
+        let proposal = MultisigProposal {
+            id,
+            action,
+            approvals: vec![],
+        };
+        MULTISIG_PROPOSALS.save(self.storage, id, &proposal)?;
+        self.approve_multisig_action(id, proposer)
+    }
+
+    /// Adds the approval of `signer` to proposal `id` and returns it with
+    /// whether the threshold is now reached. A proposal that reached it is
+    /// removed, for the caller to carry out.
+    pub fn approve_multisig_action(
+        &mut self,
+        id: u64,
+        signer: &str,
+    ) -> Result<(MultisigProposal, bool), ContractError> {
+        let config = self.ensure_signer(signer)?;
+        let mut proposal = self.read().multisig_proposal(id)?;
+        if proposal.approvals.iter().any(|approval| approval == signer) {
+            return Err(ContractError::AlreadyApproved {});
+        }
+        proposal.approvals.push(signer.to_string());
+        let approved = proposal
+            .approvals
+            .iter()
+            .filter(|approval| config.signers.contains(approval))
+            .count();
+        let ready = approved >= config.threshold as usize;
+        if ready {
+            MULTISIG_PROPOSALS.remove(self.storage, id);
+        } else {
+            MULTISIG_PROPOSALS.save(self.storage, id, &proposal)?;
+        }
+        Ok((proposal, ready))
+    }
+
+    /// Drops scheduled action `id` without executing it.
+    pub fn cancel_admin_action(&mut self, id: u64) -> Result<ScheduledAction, ContractError> {
+        let scheduled = self.read().scheduled_action(id)?;
+        SCHEDULED_ACTIONS.remove(self.storage, id);
+        Ok(scheduled)
+    }
+
+    /// Removes scheduled action `id` from the queue once its delay has
+    /// passed, for the caller to carry out.
+    pub fn take_admin_action(&mut self, id: u64, now: u64) -> Result<AdminAction, ContractError> {
+        let scheduled = self.read().scheduled_action(id)?;
+        if now < scheduled.executable_at {
+            return Err(ContractError::ActionNotReady {
+                executable_at: scheduled.executable_at,
+            });
+        }
+        SCHEDULED_ACTIONS.remove(self.storage, id);
+        Ok(scheduled.action)
+    }
+
+    pub fn lock_cap(&mut self) -> Result<(), ContractError> {
+        CAP_LOCKED.save(self.storage, &true)?;
+        Ok(())
+    }
+
+    /// Replaces the minter record and moves the `Minter` role from the
+    /// previous minter to the new one. The cap is left alone.
+    pub fn update_minter(&mut self, minter: String) -> Result<(), ContractError> {
+        if let Some(previous) = self.read().minter()? {
+            self.revoke_role(Role::Minter, &previous.minter)?;
+        }
+        self.grant_role(Role::Minter, &minter)?;
+        let cap = self.read().cap()?;
+        MINTER.save(self.storage, &MinterResponse { minter, cap })?;
+        Ok(())
+    }
+
+    pub fn update_fee_config(&mut self, config: FeeConfig) -> Result<(), ContractError> {
+        self.ensure_valid_fee_config(&config)?;
+        FEE_CONFIG.save(self.storage, &config)?;
+        Ok(())
+    }
+
+    /// Refuses tiers that are not sorted by strictly increasing
+    /// `min_amount`, and rates that together with the burn rate could take
+    /// more than a transfer.
+    fn ensure_valid_fee_config(&self, config: &FeeConfig) -> Result<(), ContractError> {
+        if config
+            .tiers
+            .windows(2)
+            .any(|pair| pair[0].min_amount >= pair[1].min_amount)
+        {
+            return Err(ContractError::InvalidFeeTiers {});
+        }
+        if u32::from(config.max_bps()) + u32::from(self.read().burn_bps()?) > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        Ok(())
+    }
+
+    /// Sets the pair and denom buybacks swap through, or turns buybacks off
+    /// when `None`. The caller bounty cannot exceed the whole purchase.
+    pub fn update_buyback(&mut self, config: Option<BuybackConfig>) -> Result<(), ContractError> {
+        match config {
+            Some(config) => {
+                if config.bounty_bps > 10_000 {
+                    return Err(ContractError::InvalidFee {});
+                }
+                BUYBACK.save(self.storage, &config)?
+            }
+            None => BUYBACK.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    /// Adds `amount` of the buyback denom, just received, to the treasury.
+    pub fn fund_treasury(&mut self, denom: &str, amount: Uint128) -> Result<(), ContractError> {
+        let balance = add_balance(self.read().treasury(denom)?, amount)?;
+        TREASURY.save(self.storage, denom, &balance)?;
+        Ok(())
+    }
+
+    /// Takes `amount` of the buyback denom, or all of it when `None`, out of
+    /// the treasury to be swapped.
+    pub fn spend_treasury(
+        &mut self,
+        denom: &str,
+        amount: Option<Uint128>,
+    ) -> Result<Uint128, ContractError> {
+        let available = self.read().treasury(denom)?;
+        let amount = amount.unwrap_or(available);
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if amount > available {
+            return Err(ContractError::InsufficientTreasury { available });
+        }
+        TREASURY.save(self.storage, denom, &(available - amount))?;
+        Ok(amount)
+    }
+
+    pub fn update_flash_mint_fee(&mut self, fee_bps: u16) -> Result<(), ContractError> {
+        if fee_bps > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        FLASH_MINT_FEE_BPS.save(self.storage, &fee_bps)?;
+        Ok(())
+    }
+
+    /// Mints `amount` to `borrower` for the length of the transaction and
+    /// returns the fee it must burn on top. The supply it started from is
+    /// kept for `ensure_flash_mint_repaid`.
+    pub fn flash_mint(
+        &mut self,
+        borrower: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(Uint128, ReplyKind), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let fee = bps_of(amount, self.read().flash_mint_fee_bps()?)?;
+        let supply_before = self.read().total_supply()?;
+        self.mint(borrower, amount, height)?;
+        Ok((
+            fee,
+            ReplyKind::FlashMint {
+                borrower: borrower.to_string(),
+                amount,
+                fee,
+                supply_before,
+            },
+        ))
+    }
+
+    /// Checks that the flash minted tokens and the fee have been burned, that
+    /// is, the supply is down by at least `fee` from `supply_before`.
+    pub fn ensure_flash_mint_repaid(
+        &self,
+        supply_before: Uint128,
+        fee: Uint128,
+    ) -> Result<(), ContractError> {
+        let target = supply_before.saturating_sub(fee);
+        let supply = self.read().total_supply()?;
+        if supply > target {
+            return Err(ContractError::FlashMintNotRepaid {
+                outstanding: supply - target,
+            });
+        }
+        Ok(())
+    }
+
+    /// Sets the share of every transfer that is burned. Zero turns burning
+    /// off.
+    pub fn update_burn_bps(&mut self, burn_bps: u16) -> Result<(), ContractError> {
+        let fee_bps = self
+            .read()
+            .fee_config()?
+            .map_or(0, |config| config.max_bps());
+        if u32::from(fee_bps) + u32::from(burn_bps) > 10_000 {
+            return Err(ContractError::InvalidFee {});
+        }
+        BURN_BPS.save(self.storage, &burn_bps)?;
+        Ok(())
+    }
+
+    pub fn update_staking_config(&mut self, config: StakingConfig) -> Result<(), ContractError> {
+        if config.epoch_seconds == 0 {
+            return Err(ContractError::InvalidStakingConfig {});
+        }
+        STAKING_CONFIG.save(self.storage, &config)?;
+        Ok(())
+    }
+
+    /// Locks `amount` of `address`'s spendable balance as stake. Rewards
+    /// earned so far are paid out first so they are not rated on the new
+    /// amount.
+    pub fn stake(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if self.read().paused()? {
+            return Err(ContractError::Paused {});
+        }
+        if self.read().staking_config()?.is_none() {
+            return Err(ContractError::StakingDisabled {});
+        }
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let spendable = self
+            .read()
+            .balance(address)?
+            .saturating_sub(self.read().unspendable(address, block)?);
+        if spendable < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: spendable,
+                required: amount,
+            });
+        }
+        self.accrue_rewards(address, block)?;
+        let stake = StakeInfo {
+            amount: add_balance(self.read().staked(address)?, amount)?,
+            reward_from: STAKES
+                .may_load(self.storage, address)?
+                .map_or(block.time.seconds(), |stake| stake.reward_from),
+            pool_index: self.read().staker_reward_index()?,
+        };
+        STAKES.save(self.storage, address, &stake)?;
+        let total = add_balance(self.read().total_staked()?, amount)?;
+        TOTAL_STAKED.save(self.storage, &total)?;
+        Ok(())
+    }
+
+    /// Moves `amount` of stake into unbonding, where it stays locked for the
+    /// configured unbonding period.
+    pub fn unstake(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        let config = self
+            .read()
+            .staking_config()?
+            .ok_or(ContractError::StakingDisabled {})?;
+        let staked = self.read().staked(address)?;
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if staked < amount {
+            return Err(ContractError::InsufficientStake {
+                available: staked,
+                required: amount,
+            });
+        }
+        self.accrue_rewards(address, block)?;
+        let mut stake = STAKES.load(self.storage, address)?;
+        stake.amount = sub_balance(stake.amount, amount)?;
+        if stake.amount.is_zero() {
+            STAKES.remove(self.storage, address);
+        } else {
+            STAKES.save(self.storage, address, &stake)?;
+        }
+        let total = sub_balance(self.read().total_staked()?, amount)?;
+        TOTAL_STAKED.save(self.storage, &total)?;
+        let mut unbonding = self.read().unbonding(address)?;
+        unbonding.push(Unbonding {
+            amount,
+            release_at: block.time.seconds() + config.unbonding_seconds,
+        });
+        UNBONDING.save(self.storage, address, &unbonding)?;
+        Ok(())
+    }
+
+    /// Releases unbonding entries whose period has passed and returns the
+    /// amount that became spendable.
+    pub fn claim_unbonded(
+        &mut self,
+        address: &str,
+        block_time: u64,
+    ) -> Result<Uint128, ContractError> {
+        let (released, pending): (Vec<_>, Vec<_>) = self
+            .read()
+            .unbonding(address)?
+            .into_iter()
+            .partition(|entry| entry.release_at <= block_time);
+        let released: Uint128 = released.iter().map(|entry| entry.amount).sum();
+        if released.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        if pending.is_empty() {
+            UNBONDING.remove(self.storage, address);
+        } else {
+            UNBONDING.save(self.storage, address, &pending)?;
+        }
+        Ok(released)
+    }
+
+    /// Mints the rewards owed to `address`, as far as the cap leaves room
+    /// for them; the rest stays owed. Returns the amount minted.
+    pub fn claim_rewards(
+        &mut self,
+        address: &str,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        let owed = self.accrue_rewards(address, block)?;
+        let headroom = match self.read().cap()? {
+            Some(cap) => cap.saturating_sub(self.read().total_supply()?),
+            None => owed,
+        };
+        let rewards = owed.min(headroom);
+        if rewards.is_zero() {
+            return Ok(rewards);
+        }
+        let remaining = owed - rewards;
+        if remaining.is_zero() {
+            OWED_REWARDS.remove(self.storage, address);
+        } else {
+            OWED_REWARDS.save(self.storage, address, &remaining)?;
+        }
+        self.mint(address, rewards, block.height)?;
+        Ok(rewards)
+    }
+
+    /// Records the rewards earned in full epochs so far plus the inflation
+    /// paid to stakers since the last settlement as owed, and moves the
+    /// reward start forward. Minting is left to `claim_rewards`, so a
+    /// refused mint can never hold a stake change back. Returns the total
+    /// owed.
+    fn accrue_rewards(
+        &mut self,
+        address: &str,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        let (owed, epochs) = self.read().pending_rewards(address, block.time.seconds())?;
+        if let Some(stake) = STAKES.may_load(self.storage, address)? {
+            let epoch_seconds = self
+                .read()
+                .staking_config()?
+                .map_or(0, |config| config.epoch_seconds);
+            let stake = StakeInfo {
+                reward_from: stake.reward_from + epochs * epoch_seconds,
+                pool_index: self.read().staker_reward_index()?,
+                ..stake
+            };
+            STAKES.save(self.storage, address, &stake)?;
+        }
+        if !owed.is_zero() {
+            OWED_REWARDS.save(self.storage, address, &owed)?;
+        }
+        Ok(owed)
+    }
+
+    /// Moves `amount` from `depositor` into the custody of the contract at
+    /// `custodian` and distributes it to holders as rewards of `denom`.
+    pub fn deposit_dividends(
+        &mut self,
+        depositor: &str,
+        custodian: &str,
+        denom: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        self.read().ensure_can_send(depositor, block)?;
+        self.debit(depositor, amount, block)?;
+        self.hold(custodian, amount, block.height)?;
+        self.distribute_dividends(custodian, denom, amount)
+    }
+
+    /// Distributes `amount` of `denom`, already held by the contract at
+    /// `custodian`, to holders pro rata to their current balance.
+    pub fn distribute_dividends(
+        &mut self,
+        custodian: &str,
+        denom: &str,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        DIVIDEND_CUSTODIAN.save(self.storage, &custodian.to_string())?;
+        let eligible = sub_balance(self.read().total_supply()?, self.read().balance(custodian)?)?;
+        if eligible.is_zero() {
+            return Err(ContractError::NoRewardRecipients {});
+        }
+        let index = match DIVIDEND_INDEX.may_load(self.storage, denom)? {
+            Some(index) => index,
+            None if self.read().dividend_denoms()?.len() as u32 >= MAX_DIVIDEND_DENOMS => {
+                return Err(ContractError::TooManyRewardDenoms {
+                    max: MAX_DIVIDEND_DENOMS,
+                })
+            }
+            None => Decimal::zero(),
+        };
+        let index = index
+            .checked_add(index_step(amount, eligible)?)
+            .map_err(StdError::from)?;
+        DIVIDEND_INDEX.save(self.storage, denom, &index)?;
+        let pool = add_balance(self.read().dividend_pool(denom)?, amount)?;
+        DIVIDEND_POOL.save(self.storage, denom, &pool)?;
+        Ok(())
+    }
+
+    /// Settles and clears the rewards of `address`. Rewards in this token,
+    /// `own_denom`, are paid out of the contract's balance at `custodian`
+    /// and their amount returned; native coins are returned for the caller
+    /// to send.
+    pub fn claim_dividends(
+        &mut self,
+        address: &str,
+        custodian: &str,
+        own_denom: &str,
+        height: u64,
+    ) -> Result<(Uint128, Vec<Coin>), ContractError> {
+        let pending = self.read().pending_dividends(address)?;
+        for (denom, index) in self.read().dividend_denoms()? {
+            DIVIDEND_POSITIONS.save(
+                self.storage,
+                (address, &denom),
+                &DividendPosition {
+                    index,
+                    owed: Uint128::zero(),
+                },
+            )?;
+        }
+        let mut claimed_own = Uint128::zero();
+        let mut native = vec![];
+        for coin in pending {
+            let pool = sub_balance(self.read().dividend_pool(&coin.denom)?, coin.amount)?;
+            DIVIDEND_POOL.save(self.storage, &coin.denom, &pool)?;
+            if coin.denom == own_denom {
+                self.release_held(custodian, coin.amount, height)?;
+                self.credit(address, coin.amount, height)?;
+                claimed_own = coin.amount;
+            } else {
+                native.push(coin);
+            }
+        }
+        Ok((claimed_own, native))
+    }
+
+    /// Records the rewards `address` earned on `balance`, its balance until
+    /// now, before that balance changes.
+    fn settle_dividends(&mut self, address: &str, balance: Uint128) -> Result<(), ContractError> {
+        if DIVIDEND_CUSTODIAN.may_load(self.storage)?.as_deref() == Some(address) {
+            return Ok(());
+        }
+        for (denom, index) in self.read().dividend_denoms()? {
+            let position = DIVIDEND_POSITIONS
+                .may_load(self.storage, (address, &denom))?
+                .unwrap_or_default();
+            if position.index == index {
+                continue;
+            }
+            let owed = add_balance(position.owed, accrued(balance, position.index, index)?)?;
+            DIVIDEND_POSITIONS.save(
+                self.storage,
+                (address, &denom),
+                &DividendPosition { index, owed },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Sets the token value of one share, switching to share mode if the
+    /// contract is not in it yet.
+    pub fn update_exchange_rate(&mut self, rate: Decimal) -> Result<(), ContractError> {
+        if rate.is_zero() {
+            return Err(ContractError::InvalidExchangeRate {});
+        }
+        // deposits and withdrawals move coins 1:1 with balances
+        if self.read().wrapped_denom()?.is_some() {
+            return Err(ContractError::WrappedShareMode {});
+        }
+        EXCHANGE_RATE.save(self.storage, &rate)?;
+        Ok(())
+    }
+
+    /// Scales every balance by `numerator / denominator` through the
+    /// exchange rate, starting from one if the contract is not in share mode
+    /// yet. Returns the previous and the new rate.
+    pub fn rebase(
+        &mut self,
+        numerator: Uint128,
+        denominator: Uint128,
+    ) -> Result<(Decimal, Decimal), ContractError> {
+        if numerator.is_zero() || denominator.is_zero() {
+            return Err(ContractError::InvalidRebase {});
+        }
+        let previous = self.read().exchange_rate()?.unwrap_or_else(Decimal::one);
+        let rate = previous
+            .checked_mul(Decimal::from_ratio(numerator, denominator))
+            .map_err(StdError::from)?;
+        self.update_exchange_rate(rate)?;
+        Ok((previous, rate))
+    }
+
+    pub fn set_bonding_curve(&mut self, curve: BondingCurve) -> Result<(), ContractError> {
+        let valid = match &curve.curve {
+            CurveType::Linear { slope, intercept } => !slope.is_zero() || !intercept.is_zero(),
+            CurveType::Exponential { base, step, .. } => !base.is_zero() && !step.is_zero(),
+        };
+        if !valid {
+            return Err(ContractError::InvalidCurve {});
+        }
+        BONDING_CURVE.save(self.storage, &curve)?;
+        Ok(())
+    }
+
+    /// Mints to `buyer` as many tokens as `paid` reserve buys along the
+    /// curve. All of `paid` goes into the reserve.
+    pub fn buy(
+        &mut self,
+        buyer: &str,
+        paid: Uint128,
+        height: u64,
+    ) -> Result<Uint128, ContractError> {
+        let config = self
+            .read()
+            .bonding_curve()?
+            .ok_or(ContractError::NoBondingCurve {})?;
+        let supply = self.read().curve_supply()?;
+        let amount = Uint128::new(curve::tokens_for(&config.curve, supply.u128(), paid.u128()));
+        if amount.is_zero() {
+            return Err(ContractError::PaymentTooSmall {});
+        }
+        self.mint(buyer, amount, height)?;
+        let reserve = add_balance(self.read().curve_reserve()?, paid)?;
+        CURVE_RESERVE.save(self.storage, &reserve)?;
+        CURVE_SUPPLY.save(self.storage, &add_balance(supply, amount)?)?;
+        Ok(amount)
+    }
+
+    /// Burns `amount` of `seller` back into the curve and returns the reserve
+    /// it is worth.
+    pub fn sell(
+        &mut self,
+        seller: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        let config = self
+            .read()
+            .bonding_curve()?
+            .ok_or(ContractError::NoBondingCurve {})?;
+        let supply = self.read().curve_supply()?;
+        if amount > supply {
+            return Err(ContractError::InsufficientCurveSupply { available: supply });
+        }
+        let refund = curve::sell_refund(&config.curve, supply.u128(), amount.u128())
+            .ok_or(ContractError::CurveOverflow {})?;
+        self.burn(seller, amount, block)?;
+        let reserve = sub_balance(self.read().curve_reserve()?, Uint128::new(refund))?;
+        CURVE_RESERVE.save(self.storage, &reserve)?;
+        CURVE_SUPPLY.save(self.storage, &(supply - amount))?;
+        Ok(Uint128::new(refund))
+    }
+
+    /// Starts inflation, with its first epoch ending `epoch_seconds` after
+    /// `now`.
+    pub fn set_inflation(
+        &mut self,
+        config: InflationConfig,
+        now: u64,
+    ) -> Result<(), ContractError> {
+        if config.epoch_seconds == 0 || config.rate_per_epoch > Decimal::one() {
+            return Err(ContractError::InvalidInflation {});
+        }
+        INFLATION.save(self.storage, &config)?;
+        INFLATION_EPOCHS.save(
+            self.storage,
+            &InflationEpochs {
+                started_at: now,
+                minted: 0,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Pays out the next inflation epoch once it has begun. Missed epochs
+    /// are caught up one call at a time. Inflation for stakers is added to
+    /// the staker reward index and minted when they claim; with nobody
+    /// staked the epoch pays nothing. Returns the recipient, if any, and the
+    /// amount.
+    pub fn advance_epoch(
+        &mut self,
+        block: &BlockInfo,
+    ) -> Result<(Option<String>, Uint128), ContractError> {
+        let config = self
+            .read()
+            .inflation()?
+            .ok_or(ContractError::InflationDisabled {})?;
+        let (next_epoch_at, amount) = self
+            .read()
+            .next_inflation()?
+            .ok_or(ContractError::InflationDisabled {})?;
+        if block.time.seconds() < next_epoch_at {
+            return Err(ContractError::EpochNotStarted { next_epoch_at });
+        }
+        INFLATION_EPOCHS.update(self.storage, |mut epochs| -> StdResult<_> {
+            epochs.minted += 1;
+            Ok(epochs)
+        })?;
+        match config.recipient {
+            InflationRecipient::Address { address } => {
+                self.mint(&address, amount, block.height)?;
+                Ok((Some(address), amount))
+            }
+            InflationRecipient::Stakers {} => {
+                let total = self.read().total_staked()?;
+                if total.is_zero() {
+                    return Ok((None, Uint128::zero()));
+                }
+                let index = self
+                    .read()
+                    .staker_reward_index()?
+                    .checked_add(index_step(amount, total)?)
+                    .map_err(StdError::from)?;
+                STAKER_REWARD_INDEX.save(self.storage, &index)?;
+                Ok((None, amount))
+            }
+        }
+    }
+
+    /// Mints `amount` to `recipient` under a vesting schedule. An address can
+    /// have one schedule at a time.
+    pub fn create_vesting(
+        &mut self,
+        recipient: &str,
+        amount: Uint128,
+        start: u64,
+        cliff: u64,
+        end: u64,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if !(start <= cliff && cliff <= end && start < end) {
+            return Err(ContractError::InvalidVestingSchedule {});
+        }
+        if self.read().vesting(recipient)?.is_some() {
+            return Err(ContractError::VestingExists {
+                address: recipient.to_string(),
+            });
+        }
+        self.mint(recipient, amount, height)?;
+        VESTING.save(
+            self.storage,
+            recipient,
+            &VestingSchedule {
+                total: amount,
+                released: Uint128::zero(),
+                start,
+                cliff,
+                end,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Unlocks whatever has vested for `address` since the last claim and
+    /// returns that amount. Fully released schedules are removed.
+    pub fn claim_vested(
+        &mut self,
+        address: &str,
+        block_time: u64,
+    ) -> Result<Uint128, ContractError> {
+        let mut schedule = self
+            .read()
+            .vesting(address)?
+            .ok_or(ContractError::NothingToClaim {})?;
+        let claimable = sub_balance(schedule.vested_at(block_time), schedule.released)?;
+        if claimable.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        schedule.released += claimable;
+        if schedule.released == schedule.total {
+            VESTING.remove(self.storage, address);
+        } else {
+            VESTING.save(self.storage, address, &schedule)?;
+        }
+        Ok(claimable)
+    }
+
+    /// Starts a new airdrop stage. Only one airdrop can be active at a time;
+    /// the previous one has to be clawed back first.
+    pub fn register_airdrop(
+        &mut self,
+        merkle_root: HexBinary,
+        total_amount: Uint128,
+        expiration: Expiration,
+        block: &BlockInfo,
+    ) -> Result<u32, ContractError> {
+        if merkle_root.len() != 32 {
+            return Err(ContractError::InvalidMerkleRoot {});
+        }
+        if expiration.is_expired(block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        if self.read().airdrop()?.is_some() {
+            return Err(ContractError::AirdropActive {});
+        }
+        let stage = AIRDROP_STAGE.may_load(self.storage)?.unwrap_or_default() + 1;
+        AIRDROP_STAGE.save(self.storage, &stage)?;
+        AIRDROP.save(
+            self.storage,
+            &AirdropConfig {
+                stage,
+                merkle_root,
+                total_amount,
+                claimed_amount: Uint128::zero(),
+                expiration,
+            },
+        )?;
+        Ok(stage)
+    }
+
+    /// Mints `amount` to `address` if `proof` shows it is in the airdrop tree
+    /// and it has not claimed in this stage yet.
+    pub fn claim_airdrop(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        proof: &[HexBinary],
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        let mut airdrop = self.read().airdrop()?.ok_or(ContractError::NoAirdrop {})?;
+        if airdrop.expiration.is_expired(block) {
+            return Err(ContractError::AirdropExpired {});
+        }
+        if AIRDROP_CLAIMED.has(self.storage, (airdrop.stage, address)) {
+            return Err(ContractError::AlreadyClaimed {});
+        }
+        let root: [u8; 32] = airdrop
+            .merkle_root
+            .to_array()
+            .map_err(|_| ContractError::InvalidMerkleRoot {})?;
+        let proof = proof
+            .iter()
+            .map(|hash| hash.to_array())
+            .collect::<StdResult<Vec<[u8; 32]>>>()
+            .map_err(|_| ContractError::InvalidProof {})?;
+        if !merkle::verify(&root, merkle::leaf(address, amount.u128()), &proof) {
+            return Err(ContractError::InvalidProof {});
+        }
+        airdrop.claimed_amount = add_balance(airdrop.claimed_amount, amount)?;
+        if airdrop.claimed_amount > airdrop.total_amount {
+            return Err(ContractError::AllocationExceeded {});
+        }
+        self.mint(address, amount, block.height)?;
+        AIRDROP_CLAIMED.save(self.storage, (airdrop.stage, address), &true)?;
+        AIRDROP.save(self.storage, &airdrop)?;
+        Ok(())
+    }
+
+    /// Closes an expired airdrop. Unclaimed tokens were never minted, so
+    /// closing it is all it takes to reclaim them. Returns the unclaimed
+    /// amount.
+    pub fn clawback_airdrop(&mut self, block: &BlockInfo) -> Result<Uint128, ContractError> {
+        let airdrop = self.read().airdrop()?.ok_or(ContractError::NoAirdrop {})?;
+        if !airdrop.expiration.is_expired(block) {
+            return Err(ContractError::AirdropNotExpired {});
+        }
+        AIRDROP.remove(self.storage);
+        Ok(sub_balance(airdrop.total_amount, airdrop.claimed_amount)?)
+    }
+
+    /// Records `address` as the next minter. Nothing changes until that
+    /// address calls `accept_minter`, so a mistyped address cannot take over.
+    pub fn propose_minter(&mut self, address: &str) -> Result<(), ContractError> {
+        self.ensure_minting_allowed()?;
+        PENDING_MINTER.save(self.storage, &address.to_string())?;
+        Ok(())
+    }
+
+    /// Hands the minter record and role over to the pending minter, keeping
+    /// the current cap. Only the pending minter itself may accept.
+    pub fn accept_minter(&mut self, sender: &str) -> Result<(), ContractError> {
+        let pending = self
+            .read()
+            .pending_minter()?
+            .ok_or(ContractError::NoPendingMinter {})?;
+        if pending != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        if let Some(previous) = self.read().minter()? {
+            self.revoke_role(Role::Minter, &previous.minter)?;
+        }
+        self.grant_role(Role::Minter, &pending)?;
+        let cap = self.read().cap()?;
+        MINTER.save(
+            self.storage,
+            &MinterResponse {
+                minter: pending,
+                cap,
+            },
+        )?;
+        PENDING_MINTER.remove(self.storage);
+        Ok(())
+    }
+
+    /// Halts every token movement until `unpause` is called. Queries and
+    /// configuration changes keep working.
+    pub fn pause(&mut self) -> Result<(), ContractError> {
+        PAUSED.save(self.storage, &true)?;
+        Ok(())
+    }
+
+    pub fn unpause(&mut self) -> Result<(), ContractError> {
+        PAUSED.save(self.storage, &false)?;
+        Ok(())
+    }
+
+    /// Grants `role` to `address`. A new minter may mint without limit
+    /// until `set_minter_allowance` says otherwise; granting the role again
+    /// keeps the allowance.
+    pub fn grant_role(&mut self, role: Role, address: &str) -> Result<(), ContractError> {
+        if role == Role::Minter {
+            self.ensure_minting_allowed()?;
+            if !MINTERS.has(self.storage, address) {
+                MINTERS.save(self.storage, address, &MinterInfo::default())?;
+            }
+            return Ok(());
+        }
+        ROLES.save(self.storage, (role.as_str(), address), &true)?;
+        Ok(())
+    }
+
+    pub fn revoke_role(&mut self, role: Role, address: &str) -> Result<(), ContractError> {
+        if role == Role::Minter {
+            MINTERS.remove(self.storage, address);
+            return Ok(());
+        }
+        ROLES.remove(self.storage, (role.as_str(), address));
+        Ok(())
+    }
+
+    pub fn set_minter_allowance(
+        &mut self,
+        address: &str,
+        allowance: Option<Uint128>,
+    ) -> Result<(), ContractError> {
+        self.ensure_minting_allowed()?;
+        MINTERS.save(self.storage, address, &MinterInfo { allowance })?;
+        Ok(())
+    }
+
+    fn ensure_minting_allowed(&self) -> Result<(), ContractError> {
+        if self.read().minting_renounced()? {
+            return Err(ContractError::MintingRenounced {});
+        }
+        Ok(())
+    }
+
+    /// Removes the minter record, every minter and any pending handover, and
+    /// rules out minting for good: the supply is fixed from here on.
+    pub fn renounce_minter(&mut self) -> Result<(), ContractError> {
+        self.ensure_minting_allowed()?;
+        let minters = MINTERS
+            .keys(self.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for minter in &minters {
+            MINTERS.remove(self.storage, minter);
+        }
+        MINTER.remove(self.storage);
+        PENDING_MINTER.remove(self.storage);
+        MINTING_RENOUNCED.save(self.storage, &true)?;
+        Ok(())
+    }
+
+    pub fn set_mint_deadline(&mut self, deadline: Expiration) -> Result<(), ContractError> {
+        MINT_DEADLINE.save(self.storage, &deadline)?;
+        Ok(())
+    }
+
+    pub fn ensure_mint_window_open(&self, block: &BlockInfo) -> Result<(), ContractError> {
+        match self.read().mint_deadline()? {
+            Some(deadline) if deadline.is_expired(block) => {
+                Err(ContractError::MintWindowClosed { deadline })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks the mint window and takes `amount` off what `minter` may still
+    /// mint.
+    pub fn consume_mint_allowance(
+        &mut self,
+        minter: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        self.ensure_mint_window_open(block)?;
+        let info = self
+            .read()
+            .minter_info(minter)?
+            .ok_or(ContractError::Unauthorized {})?;
+        if let Some(remaining) = info.allowance {
+            if amount > remaining {
+                return Err(ContractError::MintAllowanceExceeded { remaining });
+            }
+        }
+        let time = block.time.seconds();
+        if let Some((remaining, resets_at)) = self.read().mint_quota(minter, time)? {
+            if amount > remaining {
+                return Err(ContractError::MintQuotaExceeded {
+                    remaining,
+                    resets_at,
+                });
+            }
+            let quota = MINT_DAILY_QUOTAS.load(self.storage, minter)?;
+            MINT_DAY_USAGE.save(
+                self.storage,
+                minter,
+                &WindowUsage {
+                    epoch: time / SECONDS_PER_DAY,
+                    used: quota - remaining + amount,
+                },
+            )?;
+        }
+        if let Some(remaining) = info.allowance {
+            self.set_minter_allowance(minter, Some(remaining - amount))?;
+        }
+        Ok(())
+    }
+
+    /// Bounds what `minter` may mint per UTC day, or lifts the bound when
+    /// `None`.
+    pub fn set_mint_daily_quota(
+        &mut self,
+        minter: &str,
+        quota: Option<Uint128>,
+    ) -> Result<(), ContractError> {
+        match quota {
+            Some(quota) => MINT_DAILY_QUOTAS.save(self.storage, minter, &quota)?,
+            None => MINT_DAILY_QUOTAS.remove(self.storage, minter),
+        }
+        Ok(())
+    }
+
+    /// Gives every role to the current minter when no admin exists, which is
+    /// the case for deployments made before roles were introduced. Without it
+    /// nobody could manage such a contract after migrating.
+    pub fn seed_roles_from_minter(&mut self) -> Result<bool, ContractError> {
+        if self.read().role_assigned(Role::Admin) {
+            return Ok(false);
+        }
+        let minter = match self.read().minter()? {
+            Some(minter) => minter.minter,
+            None => return Ok(false),
+        };
+        for role in Role::ALL {
+            self.grant_role(role, &minter)?;
+        }
+        Ok(true)
+    }
+
+    pub fn mint(
+        &mut self,
+        recipient: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        if self.read().paused()? {
+            return Err(ContractError::Paused {});
+        }
+        self.ensure_minting_allowed()?;
+        if !self.read().mint_allowed_to(recipient)? {
+            return Err(ContractError::NotOnMintAllowlist {
+                address: recipient.to_string(),
+            });
+        }
+        self.read().ensure_not_blacklisted(recipient)?;
+        let cap = self.read().cap()?;
+        let supply = self.read().total_supply()?;
+        if cap.map_or(false, |cap| {
+            !math::within_cap(supply.u128(), amount.u128(), cap.u128())
+        }) {
+            return Err(ContractError::CapExceeded {});
+        }
+        self.read().ensure_holder_room(recipient, amount, true)?;
+        let balance = add_balance(self.read().balance(recipient)?, amount)?;
+        self.read().ensure_within_wallet_limit(recipient, balance)?;
+        self.set_balance(recipient, balance, height)?;
+        let supply = add_balance(supply, amount)?;
+        TOTAL_SUPPLY.save(self.storage, &supply, height)?;
+        Ok(())
+    }
+
+    /// Moves `amount` from `sender` to `recipient`, less the transfer fee,
+    /// which goes to the treasury, and the burn share, which leaves the
+    /// supply.
+    ///
+    /// A transfer to self is a no-op: no balances change and no charge of any
+    /// kind is applied, so any future fee or burn logic must stay below the
+    /// `sender == recipient` early return.
+    pub fn transfer(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<TransferOutcome, ContractError> {
+        self.read().ensure_can_send(sender, block)?;
+        let untouched = TransferOutcome {
+            sent: amount,
+            received: amount,
+            ..TransferOutcome::default()
+        };
+        if sender == recipient {
+            return Ok(untouched);
+        }
+        if amount.is_zero() {
+            return Ok(untouched);
+        }
+        self.read().ensure_within_transfer_limit(sender, amount)?;
+        self.record_outflow(sender, amount, block.time.seconds())?;
+        self.debit(sender, amount, block)?;
+        let outcome = self.read().split(sender, recipient, amount)?;
+        self.credit(recipient, outcome.received, block.height)?;
+        self.settle_charges(outcome.fee, outcome.burned, block.height)?;
+        Ok(outcome)
+    }
+
+    /// Moves `amount` from `sender` into the custody of the contract at
+    /// `custodian`, to be claimed by `recipient` once `release` has passed.
+    /// The transfer is charged like a regular one; only what the recipient
+    /// would receive is locked. Returns the new entry and the charges.
+    pub fn transfer_locked(
+        &mut self,
+        sender: &str,
+        recipient: &str,
+        custodian: &str,
+        amount: Uint128,
+        release: Expiration,
+        block: &BlockInfo,
+    ) -> Result<(LockedTransfer, TransferOutcome), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if release.is_expired(block) || matches!(release, Expiration::Never {}) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        self.read().ensure_can_send(sender, block)?;
+        self.read().ensure_within_transfer_limit(sender, amount)?;
+        self.record_outflow(sender, amount, block.time.seconds())?;
+        self.debit(sender, amount, block)?;
+        let outcome = self.read().split(sender, recipient, amount)?;
+        self.settle_charges(outcome.fee, outcome.burned, block.height)?;
+        self.hold(custodian, outcome.received, block.height)?;
+        let id = LOCKED_TRANSFER_SEQ
+            .may_load(self.storage)?
+            .unwrap_or_default()
+            + 1;
+        LOCKED_TRANSFER_SEQ.save(self.storage, &id)?;
+        let locked = LockedTransfer {
+            id,
+            sender: sender.to_string(),
+            amount: outcome.received,
+            release,
+        };
+        LOCKED_TRANSFERS.save(self.storage, (recipient, id), &locked)?;
+        Ok((locked, outcome))
+    }
+
+    /// Pays out every locked transfer to `recipient` whose release has
+    /// passed. Returns the total claimed.
+    pub fn claim_locked(
+        &mut self,
+        recipient: &str,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Uint128, ContractError> {
+        let released = LOCKED_TRANSFERS
+            .prefix(recipient)
+            .range(self.storage, None, None, Order::Ascending)
+            .filter(|item| {
+                item.as_ref()
+                    .map_or(true, |(_, transfer)| transfer.release.is_expired(block))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        let mut total = Uint128::zero();
+        for (id, transfer) in released {
+            LOCKED_TRANSFERS.remove(self.storage, (recipient, id));
+            total = add_balance(total, transfer.amount)?;
+        }
+        if total.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        self.release_held(custodian, total, block.height)?;
+        self.credit(recipient, total, block.height)?;
+        Ok(total)
+    }
+
+    /// Moves `escrow.amount` from its sender into the custody of the contract
+    /// at `custodian`. Charges are only taken on release, so a refund returns
+    /// the full amount. Returns the escrow as stored, with its id.
+    pub fn create_escrow(
+        &mut self,
+        mut escrow: Escrow,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Escrow, ContractError> {
+        if escrow.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if escrow.expires.is_expired(block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        self.read().ensure_can_send(&escrow.sender, block)?;
+        self.read()
+            .ensure_within_transfer_limit(&escrow.sender, escrow.amount)?;
+        self.record_outflow(&escrow.sender, escrow.amount, block.time.seconds())?;
+        self.debit(&escrow.sender, escrow.amount, block)?;
+        self.hold(custodian, escrow.amount, block.height)?;
+        escrow.id = ESCROW_SEQ.may_load(self.storage)?.unwrap_or_default() + 1;
+        ESCROW_SEQ.save(self.storage, &escrow.id)?;
+        ESCROWS.save(self.storage, escrow.id, &escrow)?;
+        Ok(escrow)
+    }
+
+    /// Pays escrow `id` out to its recipient, less the usual transfer
+    /// charges. Only its arbiter may do so, and only before it expires.
+    pub fn release_escrow(
+        &mut self,
+        id: u64,
+        arbiter: &str,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<(Escrow, TransferOutcome), ContractError> {
+        let escrow = self.read().escrow(id)?;
+        if escrow.arbiter != arbiter {
+            return Err(ContractError::Unauthorized {});
+        }
+        if escrow.expires.is_expired(block) {
+            return Err(ContractError::EscrowExpired {});
+        }
+        ESCROWS.remove(self.storage, id);
+        self.release_held(custodian, escrow.amount, block.height)?;
+        let outcome = self
+            .read()
+            .split(&escrow.sender, &escrow.recipient, escrow.amount)?;
+        self.credit(&escrow.recipient, outcome.received, block.height)?;
+        self.settle_charges(outcome.fee, outcome.burned, block.height)?;
+        Ok((escrow, outcome))
+    }
+
+    /// Returns the full amount of an expired escrow to its sender.
+    pub fn refund_escrow(
+        &mut self,
+        id: u64,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Escrow, ContractError> {
+        let escrow = self.read().escrow(id)?;
+        if !escrow.expires.is_expired(block) {
+            return Err(ContractError::EscrowNotExpired {});
+        }
+        ESCROWS.remove(self.storage, id);
+        self.release_held(custodian, escrow.amount, block.height)?;
+        self.credit(&escrow.sender, escrow.amount, block.height)?;
+        Ok(escrow)
+    }
+
+    /// Moves `swap.amount` from its sender into the custody of the contract
+    /// at `custodian`. As with escrows, charges are only taken on claim.
+    pub fn create_swap(
+        &mut self,
+        mut swap: Swap,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Swap, ContractError> {
+        if swap.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if swap.hash.len() != 32 {
+            return Err(ContractError::InvalidSwapHash {});
+        }
+        if swap.expires.is_expired(block) || matches!(swap.expires, Expiration::Never {}) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        self.read().ensure_can_send(&swap.sender, block)?;
+        self.read()
+            .ensure_within_transfer_limit(&swap.sender, swap.amount)?;
+        self.record_outflow(&swap.sender, swap.amount, block.time.seconds())?;
+        self.debit(&swap.sender, swap.amount, block)?;
+        self.hold(custodian, swap.amount, block.height)?;
+        swap.id = SWAP_SEQ.may_load(self.storage)?.unwrap_or_default() + 1;
+        SWAP_SEQ.save(self.storage, &swap.id)?;
+        SWAPS.save(self.storage, swap.id, &swap)?;
+        Ok(swap)
+    }
+
+    /// Pays swap `id` out to its recipient if `preimage` hashes to the swap
+    /// hash and the swap has not expired.
+    pub fn claim_swap(
+        &mut self,
+        id: u64,
+        preimage: &[u8],
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<(Swap, TransferOutcome), ContractError> {
+        let swap = self.read().swap(id)?;
+        if swap.expires.is_expired(block) {
+            return Err(ContractError::SwapExpired {});
+        }
+        if Sha256::digest(preimage).as_slice() != swap.hash.as_slice() {
+            return Err(ContractError::InvalidPreimage {});
+        }
+        SWAPS.remove(self.storage, id);
+        self.release_held(custodian, swap.amount, block.height)?;
+        let outcome = self
+            .read()
+            .split(&swap.sender, &swap.recipient, swap.amount)?;
+        self.credit(&swap.recipient, outcome.received, block.height)?;
+        self.settle_charges(outcome.fee, outcome.burned, block.height)?;
+        Ok((swap, outcome))
+    }
+
+    /// Returns the full amount of an expired swap to its sender.
+    pub fn refund_swap(
+        &mut self,
+        id: u64,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Swap, ContractError> {
+        let swap = self.read().swap(id)?;
+        if !swap.expires.is_expired(block) {
+            return Err(ContractError::SwapNotExpired {});
+        }
+        SWAPS.remove(self.storage, id);
+        self.release_held(custodian, swap.amount, block.height)?;
+        self.credit(&swap.sender, swap.amount, block.height)?;
+        Ok(swap)
+    }
+
+    /// Moves `stream.amount` from its sender into the custody of the
+    /// contract at `custodian`. Charges are taken as the recipient
+    /// withdraws, so a cancellation returns the unstreamed part in full.
+    /// Returns the stream as stored, with its id.
+    pub fn create_stream(
+        &mut self,
+        mut stream: Stream,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<Stream, ContractError> {
+        if stream.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if stream.end <= stream.start || stream.end <= block.time.seconds() {
+            return Err(ContractError::InvalidStream {});
+        }
+        self.read().ensure_can_send(&stream.sender, block)?;
+        self.read()
+            .ensure_within_transfer_limit(&stream.sender, stream.amount)?;
+        self.record_outflow(&stream.sender, stream.amount, block.time.seconds())?;
+        self.debit(&stream.sender, stream.amount, block)?;
+        self.hold(custodian, stream.amount, block.height)?;
+        stream.id = STREAM_SEQ.may_load(self.storage)?.unwrap_or_default() + 1;
+        stream.withdrawn = Uint128::zero();
+        STREAM_SEQ.save(self.storage, &stream.id)?;
+        STREAMS.save(self.storage, stream.id, &stream)?;
+        STREAMS_BY_SENDER.save(self.storage, (&stream.sender, stream.id), &true)?;
+        STREAMS_BY_RECIPIENT.save(self.storage, (&stream.recipient, stream.id), &true)?;
+        Ok(stream)
+    }
+
+    /// Pays what stream `id` has accrued so far to its recipient, less the
+    /// usual transfer charges. Only the recipient may withdraw.
+    pub fn withdraw_from_stream(
+        &mut self,
+        id: u64,
+        recipient: &str,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<(Stream, TransferOutcome), ContractError> {
+        let mut stream = self.read().stream(id)?;
+        if stream.recipient != recipient {
+            return Err(ContractError::Unauthorized {});
+        }
+        let amount = stream.withdrawable_at(block.time.seconds());
+        if amount.is_zero() {
+            return Err(ContractError::NothingToClaim {});
+        }
+        stream.withdrawn += amount;
+        if stream.withdrawn == stream.amount {
+            self.remove_stream(&stream);
+        } else {
+            STREAMS.save(self.storage, id, &stream)?;
+        }
+        let outcome = self.pay_from_stream(&stream, amount, custodian, block.height)?;
+        Ok((stream, outcome))
+    }
+
+    /// Ends stream `id`: the recipient is paid what has accrued, as on a
+    /// withdrawal, and the rest goes back to the sender in full. Only the
+    /// sender may cancel. Returns the stream, the recipient's charges and
+    /// the refund.
+    pub fn cancel_stream(
+        &mut self,
+        id: u64,
+        sender: &str,
+        custodian: &str,
+        block: &BlockInfo,
+    ) -> Result<(Stream, TransferOutcome, Uint128), ContractError> {
+        let stream = self.read().stream(id)?;
+        if stream.sender != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        let accrued = stream.withdrawable_at(block.time.seconds());
+        let refund = stream.amount - stream.withdrawn - accrued;
+        self.remove_stream(&stream);
+        let outcome = if accrued.is_zero() {
+            TransferOutcome::default()
+        } else {
+            self.pay_from_stream(&stream, accrued, custodian, block.height)?
+        };
+        if !refund.is_zero() {
+            self.release_held(custodian, refund, block.height)?;
+            self.credit(&stream.sender, refund, block.height)?;
+        }
+        Ok((stream, outcome, refund))
+    }
+
+    fn pay_from_stream(
+        &mut self,
+        stream: &Stream,
+        amount: Uint128,
+        custodian: &str,
+        height: u64,
+    ) -> Result<TransferOutcome, ContractError> {
+        self.release_held(custodian, amount, height)?;
+        let outcome = self
+            .read()
+            .split(&stream.sender, &stream.recipient, amount)?;
+        self.credit(&stream.recipient, outcome.received, height)?;
+        self.settle_charges(outcome.fee, outcome.burned, height)?;
+        Ok(outcome)
+    }
+
+    fn remove_stream(&mut self, stream: &Stream) {
+        STREAMS.remove(self.storage, stream.id);
+        STREAMS_BY_SENDER.remove(self.storage, (&stream.sender, stream.id));
+        STREAMS_BY_RECIPIENT.remove(self.storage, (&stream.recipient, stream.id));
+    }
+
+    /// Stores `subscription`, collectible from `next_collection` onwards.
+    /// Returns it with its id.
+    pub fn create_subscription(
+        &mut self,
+        mut subscription: Subscription,
+    ) -> Result<Subscription, ContractError> {
+        if subscription.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if subscription.period == 0 {
+            return Err(ContractError::InvalidPeriod {});
+        }
+        subscription.id = SUBSCRIPTION_SEQ.may_load(self.storage)?.unwrap_or_default() + 1;
+        SUBSCRIPTION_SEQ.save(self.storage, &subscription.id)?;
+        SUBSCRIPTIONS.save(self.storage, subscription.id, &subscription)?;
+        SUBSCRIPTIONS_BY_SUBSCRIBER.save(
+            self.storage,
+            (&subscription.subscriber, subscription.id),
+            &true,
+        )?;
+        SUBSCRIPTIONS_BY_MERCHANT.save(
+            self.storage,
+            (&subscription.merchant, subscription.id),
+            &true,
+        )?;
+        Ok(subscription)
+    }
+
+    /// Transfers one period's payment of subscription `id` to its merchant,
+    /// like a regular transfer from the subscriber. Only the merchant may
+    /// collect, once per period; periods it let pass are not made up. The
+    /// next collection is due at the start of the following period.
+    pub fn collect_subscription(
+        &mut self,
+        id: u64,
+        merchant: &str,
+        block: &BlockInfo,
+    ) -> Result<(Subscription, TransferOutcome), ContractError> {
+        let mut subscription = self.read().subscription(id)?;
+        if subscription.merchant != merchant {
+            return Err(ContractError::Unauthorized {});
+        }
+        let now = block.time.seconds();
+        if now < subscription.next_collection {
+            return Err(ContractError::PaymentNotDue {
+                next_collection: subscription.next_collection,
+            });
+        }
+        let periods_passed = (now - subscription.next_collection) / subscription.period;
+        subscription.next_collection += (periods_passed + 1) * subscription.period;
+        SUBSCRIPTIONS.save(self.storage, id, &subscription)?;
+        let outcome = self.transfer(
+            &subscription.subscriber,
+            &subscription.merchant,
+            subscription.amount,
+            block,
+        )?;
+        Ok((subscription, outcome))
+    }
+
+    /// Ends subscription `id`. Either its subscriber or its merchant may
+    /// cancel.
+    pub fn cancel_subscription(
+        &mut self,
+        id: u64,
+        sender: &str,
+    ) -> Result<Subscription, ContractError> {
+        let subscription = self.read().subscription(id)?;
+        if subscription.subscriber != sender && subscription.merchant != sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        SUBSCRIPTIONS.remove(self.storage, id);
+        SUBSCRIPTIONS_BY_SUBSCRIBER.remove(self.storage, (&subscription.subscriber, id));
+        SUBSCRIPTIONS_BY_MERCHANT.remove(self.storage, (&subscription.merchant, id));
+        Ok(subscription)
+    }
+
+    /// Allows native rewards in `denom`. The own token is always allowed and
+    /// takes one of the `MAX_DIVIDEND_DENOMS` slots.
+    pub fn add_reward_denom(&mut self, denom: &str) -> Result<(), ContractError> {
+        if self.read().reward_denom_allowed(denom)? {
+            return Ok(());
+        }
+        if self.read().reward_denoms()?.len() as u32 + 1 >= MAX_DIVIDEND_DENOMS {
+            return Err(ContractError::TooManyRewardDenoms {
+                max: MAX_DIVIDEND_DENOMS,
+            });
+        }
+        REWARD_DENOMS.save(self.storage, denom, &true)?;
+        Ok(())
+    }
+
+    pub fn remove_reward_denom(&mut self, denom: &str) -> Result<(), ContractError> {
+        REWARD_DENOMS.remove(self.storage, denom);
+        Ok(())
+    }
+
+    pub fn add_non_circulating(&mut self, address: &str) -> Result<(), ContractError> {
+        NON_CIRCULATING.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    pub fn remove_non_circulating(&mut self, address: &str) -> Result<(), ContractError> {
+        NON_CIRCULATING.remove(self.storage, address);
+        Ok(())
+    }
+
+    pub fn set_wrapped_denom(&mut self, denom: &str) -> Result<(), ContractError> {
+        if self.read().exchange_rate()?.is_some() {
+            return Err(ContractError::WrappedShareMode {});
+        }
+        WRAPPED_DENOM.save(self.storage, &denom.to_string())?;
+        Ok(())
+    }
+
+    pub fn save_channel(&mut self, channel: &ChannelInfo) -> Result<(), ContractError> {
+        CHANNELS.save(self.storage, &channel.id, channel)?;
+        Ok(())
+    }
+
+    /// Moves `amount` from `sender` into the custody of the contract at
+    /// `custodian` and counts it as escrowed for `channel`, before it is sent
+    /// to the counterparty chain.
+    pub fn ibc_escrow(
+        &mut self,
+        channel: &str,
+        sender: &str,
+        custodian: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if self.read().channel(channel)?.is_none() {
+            return Err(ContractError::NoSuchChannel {
+                id: channel.to_string(),
+            });
+        }
+        self.read().ensure_can_send(sender, block)?;
+        self.read().ensure_within_transfer_limit(sender, amount)?;
+        self.record_outflow(sender, amount, block.time.seconds())?;
+        self.debit(sender, amount, block)?;
+        self.hold(custodian, amount, block.height)?;
+        let escrowed = add_balance(self.read().channel_escrow(channel)?, amount)?;
+        CHANNEL_ESCROW.save(self.storage, channel, &escrowed)?;
+        Ok(())
+    }
+
+    /// Pays `amount` escrowed for `channel` out to `recipient`, either when
+    /// it comes back from the counterparty chain or when a send is refunded.
+    /// Every check runs before the first write, so a failure leaves no trace
+    /// even though packet handlers commit state on error acks.
+    pub fn ibc_release(
+        &mut self,
+        channel: &str,
+        recipient: &str,
+        custodian: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let escrowed = self.read().channel_escrow(channel)?;
+        if escrowed < amount {
+            return Err(ContractError::InsufficientEscrow {
+                id: channel.to_string(),
+            });
+        }
+        self.credit(recipient, amount, height)?;
+        self.release_held(custodian, amount, height)?;
+        CHANNEL_ESCROW.save(self.storage, channel, &(escrowed - amount))?;
+        Ok(())
+    }
+
+    /// Adds tokens held on behalf of others to the contract's own balance.
+    /// Custody is not a transfer, so no recipient checks apply.
+    fn hold(&mut self, custodian: &str, amount: Uint128, height: u64) -> Result<(), ContractError> {
+        let balance = add_balance(self.read().balance(custodian)?, amount)?;
+        self.set_balance(custodian, balance, height)
+    }
+
+    /// Takes previously held tokens back out of the contract's own balance.
+    fn release_held(
+        &mut self,
+        custodian: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let balance = sub_balance(self.read().balance(custodian)?, amount)?;
+        self.set_balance(custodian, balance, height)
+    }
+
+    /// Pays `fee` to the treasury and takes `burned` out of the supply. The
+    /// sender has already been debited for both.
+    fn settle_charges(
+        &mut self,
+        fee: Uint128,
+        burned: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        if !fee.is_zero() {
+            if let Some(config) = self.read().fee_config()? {
+                self.credit(&config.treasury, fee, height)?;
+            }
+        }
+        if !burned.is_zero() {
+            let supply = sub_balance(self.read().total_supply()?, burned)?;
+            TOTAL_SUPPLY.save(self.storage, &supply, height)?;
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` of the fees collected by the treasury to `to`, which
+    /// must not be frozen or blacklisted. Returns the treasury.
+    pub fn claim_treasury(
+        &mut self,
+        to: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<String, ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let treasury = self
+            .read()
+            .fee_config()?
+            .ok_or(ContractError::NoFeeConfig {})?
+            .treasury;
+        if self.read().is_frozen(to, block)? {
+            return Err(ContractError::AccountFrozen {
+                address: to.to_string(),
+            });
+        }
+        let available = self.read().balance(&treasury)?;
+        if amount > available {
+            return Err(ContractError::InsufficientTreasury { available });
+        }
+        self.debit(&treasury, amount, block)?;
+        self.credit(to, amount, block.height)?;
+        Ok(treasury)
+    }
+
+    /// Makes `new_treasury` the recipient of transfer fees and moves the
+    /// balance of the previous treasury to it, frozen or not, as `clawback`
+    /// does. Locked, vesting and staked tokens stay put. `new_treasury` must
+    /// be able to receive the balance and must not be frozen. Returns the
+    /// previous treasury and the amount moved.
+    pub fn rotate_treasury(
+        &mut self,
+        new_treasury: &str,
+        block: &BlockInfo,
+    ) -> Result<(String, Uint128), ContractError> {
+        let mut config = self
+            .read()
+            .fee_config()?
+            .ok_or(ContractError::NoFeeConfig {})?;
+        if self.read().is_frozen(new_treasury, block)? {
+            return Err(ContractError::AccountFrozen {
+                address: new_treasury.to_string(),
+            });
+        }
+        let previous = config.treasury.clone();
+        config.treasury = new_treasury.to_string();
+        FEE_CONFIG.save(self.storage, &config)?;
+        if previous == new_treasury {
+            return Ok((previous, Uint128::zero()));
+        }
+        let balance = self.read().balance(&previous)?;
+        let held = self
+            .read()
+            .unspendable(&previous, block)?
+            .saturating_sub(self.read().frozen_amount(&previous, block)?);
+        let moved = balance.saturating_sub(held);
+        if !moved.is_zero() {
+            self.set_balance(&previous, balance - moved, block.height)?;
+            self.credit(new_treasury, moved, block.height)?;
+        }
+        Ok((previous, moved))
+    }
+
+    /// Moves tokens from `sender` to every recipient in `transfers`, debiting
+    /// the sender once for the total. Entries back to the sender are no-ops,
+    /// as with `transfer`. Each entry is charged the transfer fee and burn on
+    /// its own; the outcome holds the totals.
+    pub fn transfer_batch(
+        &mut self,
+        sender: &str,
+        transfers: &[(String, Uint128)],
+        block: &BlockInfo,
+    ) -> Result<TransferOutcome, ContractError> {
+        self.read().ensure_can_send(sender, block)?;
+        let transfers: Vec<_> = transfers
+            .iter()
+            .filter(|(recipient, amount)| recipient != sender && !amount.is_zero())
+            .collect();
+        let mut total = Uint128::zero();
+        for (_, amount) in &transfers {
+            self.read().ensure_within_transfer_limit(sender, *amount)?;
+            total = add_balance(total, *amount)?;
+        }
+        let mut totals = TransferOutcome::default();
+        if total.is_zero() {
+            return Ok(totals);
+        }
+        self.record_outflow(sender, total, block.time.seconds())?;
+        self.debit(sender, total, block)?;
+        for (recipient, amount) in transfers {
+            let outcome = self.read().split(sender, recipient, *amount)?;
+            self.credit(recipient, outcome.received, block.height)?;
+            totals.sent = add_balance(totals.sent, outcome.sent)?;
+            totals.received = add_balance(totals.received, outcome.received)?;
+            totals.fee = add_balance(totals.fee, outcome.fee)?;
+            totals.burned = add_balance(totals.burned, outcome.burned)?;
+        }
+        self.settle_charges(totals.fee, totals.burned, block.height)?;
+        Ok(totals)
+    }
+
+    /// Counts `amount` against `sender`'s quota for the current window.
+    fn record_outflow(
+        &mut self,
+        sender: &str,
+        amount: Uint128,
+        block_time: u64,
+    ) -> Result<(), ContractError> {
+        let remaining = match self
+            .read()
+            .ensure_within_quota(sender, amount, block_time)?
+        {
+            Some(remaining) => remaining,
+            None => return Ok(()),
+        };
+        let rate_limit = RATE_LIMIT.load(self.storage)?;
+        let epoch = block_time / rate_limit.window_seconds;
+        TRANSFER_WINDOWS.save(
+            self.storage,
+            sender,
+            &WindowUsage {
+                epoch,
+                used: rate_limit.limit - remaining + amount,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Takes `amount` out of the unlocked, unfrozen part of `sender`'s
+    /// balance.
+    fn debit(
+        &mut self,
+        sender: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        let sender_balance = self.read().ensure_spendable(sender, amount, block)?;
+        self.set_balance(sender, sub_balance(sender_balance, amount)?, block.height)
+    }
+
+    /// Adds `amount` to `recipient`, enforcing the blacklist, the receiver
+    /// allowlist, the per-transfer receive limit and the maximum wallet
+    /// balance.
+    fn credit(
+        &mut self,
+        recipient: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let new_balance = self.read().ensure_can_receive(recipient, amount)?;
+        self.set_balance(recipient, new_balance, height)
+    }
+
+    /// Stores the balance of `address`, moves the difference into the
+    /// delegated power of its delegate, if it has one, and updates the
+    /// holder index. Every balance change must go through here to keep
+    /// voting power and holder statistics consistent.
+    pub fn set_balance(
+        &mut self,
+        address: &str,
+        balance: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let previous = self.read().balance(address)?;
+        if previous != balance {
+            self.settle_dividends(address, previous)?;
+        }
+        BALANCES.save(self.storage, address, &balance, height)?;
+        self.reindex_holder(address, previous, balance)?;
+        if let Some(delegate) = self.read().delegate(address)? {
+            let power = DELEGATED_POWER
+                .may_load(self.storage, &delegate)?
+                .unwrap_or_default();
+            let power = if balance >= previous {
+                add_balance(power, balance - previous)?
+            } else {
+                sub_balance(power, previous - balance)?
+            };
+            DELEGATED_POWER.save(self.storage, &delegate, &power, height)?;
+        }
+        Ok(())
+    }
+
+    fn reindex_holder(
+        &mut self,
+        address: &str,
+        previous: Uint128,
+        balance: Uint128,
+    ) -> Result<(), ContractError> {
+        if previous == balance {
+            return Ok(());
+        }
+        let mut count = self.read().holder_count()?;
+        if previous.is_zero() {
+            count += 1;
+        } else {
+            HOLDERS_BY_BALANCE.remove(self.storage, (previous.u128(), address));
+        }
+        if balance.is_zero() {
+            count -= 1;
+        } else {
+            HOLDERS_BY_BALANCE.save(self.storage, (balance.u128(), address), &true)?;
+        }
+        HOLDER_COUNT.save(self.storage, &count)?;
+        Ok(())
+    }
+
+    /// Delegates the voting power of `delegator`'s whole balance, present
+    /// and future, to `delegate`. Delegating to oneself undelegates.
+    pub fn delegate(
+        &mut self,
+        delegator: &str,
+        delegate: &str,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let balance = self.read().balance(delegator)?;
+        if let Some(previous) = self.read().delegate(delegator)? {
+            let power = DELEGATED_POWER
+                .may_load(self.storage, &previous)?
+                .unwrap_or_default();
+            DELEGATED_POWER.save(
+                self.storage,
+                &previous,
+                &sub_balance(power, balance)?,
+                height,
+            )?;
+        }
+        if delegator == delegate {
+            DELEGATES.remove(self.storage, delegator, height)?;
+            return Ok(());
+        }
+        let power = DELEGATED_POWER
+            .may_load(self.storage, delegate)?
+            .unwrap_or_default();
+        DELEGATED_POWER.save(
+            self.storage,
+            delegate,
+            &add_balance(power, balance)?,
+            height,
+        )?;
+        DELEGATES.save(self.storage, delegator, &delegate.to_string(), height)?;
+        Ok(())
+    }
+
+    /// Moves `amount` out of a frozen account into `recipient`, ignoring the
+    /// freeze and the pause. Locked, vesting and staked tokens stay put.
+    pub fn clawback(
+        &mut self,
+        address: &str,
+        recipient: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if self.read().active_freeze(address, block)?.is_none() {
+            return Err(ContractError::AccountNotFrozen {
+                address: address.to_string(),
+            });
+        }
+        let balance = self.read().balance(address)?;
+        if balance < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: balance,
+                required: amount,
+            });
+        }
+        let held = self
+            .read()
+            .unspendable(address, block)?
+            .saturating_sub(self.read().frozen_amount(address, block)?);
+        let seizable = balance.saturating_sub(held);
+        if seizable < amount {
+            return Err(ContractError::LockedTokens {
+                transferable: seizable,
+                required: amount,
+            });
+        }
+        self.set_balance(address, sub_balance(balance, amount)?, block.height)?;
+        self.credit(recipient, amount, block.height)
+    }
+
+    /// Removes `amount` from `owner` and the total supply. Frozen accounts
+    /// cannot burn and locked tokens cannot be burned.
+    pub fn burn(
+        &mut self,
+        owner: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if self.read().paused()? {
+            return Err(ContractError::Paused {});
+        }
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if self.read().is_frozen(owner, block)? {
+            return Err(ContractError::AccountFrozen {
+                address: owner.to_string(),
+            });
+        }
+        let balance = self.read().balance(owner)?;
+        if balance < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: balance,
+                required: amount,
+            });
+        }
+        let burnable = balance.saturating_sub(self.read().unspendable(owner, block)?);
+        if burnable < amount {
+            return Err(ContractError::LockedTokens {
+                transferable: burnable,
+                required: amount,
+            });
+        }
+        self.set_balance(owner, sub_balance(balance, amount)?, block.height)?;
+        let supply = sub_balance(self.read().total_supply()?, amount)?;
+        TOTAL_SUPPLY.save(self.storage, &supply, block.height)?;
+        Ok(())
+    }
+
+    /// Burns `amount` of `address`'s tokens on the issuer's behalf, whether or
+    /// not the token is soulbound, paused or the account frozen. Locked tokens
+    /// are burned once the rest is used up; vesting and staked tokens stay
+    /// put.
+    pub fn revoke(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let balance = self.read().balance(address)?;
+        if balance < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: balance,
+                required: amount,
+            });
+        }
+        let locked = self.read().locked_balance(address)?;
+        let held = self
+            .read()
+            .unspendable(address, block)?
+            .saturating_sub(self.read().frozen_amount(address, block)?)
+            .saturating_sub(locked);
+        let revocable = balance.saturating_sub(held);
+        if revocable < amount {
+            return Err(ContractError::LockedTokens {
+                transferable: revocable,
+                required: amount,
+            });
+        }
+        let unlocked = revocable.saturating_sub(locked);
+        self.set_locked(address, locked - amount.saturating_sub(unlocked))?;
+        self.set_balance(address, sub_balance(balance, amount)?, block.height)?;
+        let supply = sub_balance(self.read().total_supply()?, amount)?;
+        TOTAL_SUPPLY.save(self.storage, &supply, block.height)?;
+        Ok(())
+    }
+
+    /// Makes `amount` of `address`'s locked tokens transferable.
+    pub fn release_locked(&mut self, address: &str, amount: Uint128) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let locked = self.read().locked_balance(address)?;
+        if locked < amount {
+            return Err(ContractError::InsufficientFunds {
+                available: locked,
+                required: amount,
+            });
+        }
+        self.set_locked(address, locked - amount)
+    }
+
+    fn set_locked(&mut self, address: &str, locked: Uint128) -> Result<(), ContractError> {
+        if locked.is_zero() {
+            LOCKED_BALANCES.remove(self.storage, address);
+        } else {
+            LOCKED_BALANCES.save(self.storage, address, &locked)?;
+        }
+        Ok(())
+    }
+
+    /// Freezes `address`, effective after the configured grace period and
+    /// lifted automatically at `expires`, and stores `record` alongside it.
+    /// Repeating a freeze that is already pending or active with the same
+    /// expiration is a no-op and returns `false`, so callers can report it
+    /// without touching any state.
+    pub fn freeze(
+        &mut self,
+        address: &str,
+        expires: Expiration,
+        record: &FreezeRecord,
+        block: &BlockInfo,
+    ) -> Result<bool, ContractError> {
+        if expires.is_expired(block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        let existing = self
+            .read()
+            .freeze_info(address)?
+            .filter(|freeze| !freeze.expires.is_expired(block));
+        let freeze = match existing {
+            Some(freeze) if freeze.amount.is_none() && freeze.expires == expires => {
+                return Ok(false)
+            }
+            // a pending or active freeze keeps its timing when widened or
+            // given a new expiration
+            Some(freeze) => FreezeInfo {
+                amount: None,
+                active_from: freeze.active_from,
+                expires,
+            },
+            None => FreezeInfo {
+                amount: None,
+                active_from: block.time.seconds() + self.read().freeze_grace_seconds()?,
+                expires,
+            },
+        };
+        self.set_freeze(address, Some(&freeze))?;
+        FREEZE_RECORDS.save(self.storage, address, record)?;
+        Ok(true)
+    }
+
+    /// Freezes `amount` of `address`'s balance, replacing any existing
+    /// freeze. The grace period only applies if the account had no freeze.
+    pub fn freeze_amount(
+        &mut self,
+        address: &str,
+        amount: Uint128,
+        record: &FreezeRecord,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let existing = self
+            .read()
+            .freeze_info(address)?
+            .filter(|freeze| !freeze.expires.is_expired(block));
+        let active_from = match existing {
+            Some(freeze) => freeze.active_from,
+            None => block.time.seconds() + self.read().freeze_grace_seconds()?,
+        };
+        self.set_freeze(
+            address,
+            Some(&FreezeInfo {
+                amount: Some(amount),
+                active_from,
+                expires: Expiration::Never {},
+            }),
+        )?;
+        FREEZE_RECORDS.save(self.storage, address, record)?;
+        Ok(())
+    }
+
+    /// Stores the freeze on `address`, or lifts it when `None`, keeping
+    /// `FROZEN_TOTAL` in step.
+    fn set_freeze(
+        &mut self,
+        address: &str,
+        freeze: Option<&FreezeInfo>,
+    ) -> Result<(), ContractError> {
+        let previous = self
+            .read()
+            .freeze_info(address)?
+            .and_then(|freeze| freeze.amount);
+        let total = self
+            .read()
+            .frozen_total()?
+            .saturating_sub(previous.unwrap_or_default())
+            .saturating_add(freeze.and_then(|freeze| freeze.amount).unwrap_or_default());
+        FROZEN_TOTAL.save(self.storage, &total)?;
+        match freeze {
+            Some(freeze) => FROZEN_BALANCES.save(self.storage, address, freeze)?,
+            None => FROZEN_BALANCES.remove(self.storage, address),
+        }
+        Ok(())
+    }
+
+    /// Records a privileged action, dropping the oldest entry once the log
+    /// holds `AUDIT_LOG_CAPACITY` entries.
+    pub fn append_audit_entry(
+        &mut self,
+        actor: Addr,
+        action: String,
+        payload: Binary,
+        block: &BlockInfo,
+    ) -> Result<(), ContractError> {
+        let id = AUDIT_LOG_NEXT.may_load(self.storage)?.unwrap_or_default();
+        AUDIT_LOG.save(
+            self.storage,
+            id,
+            &AuditEntry {
+                id,
+                actor,
+                action,
+                height: block.height,
+                time: block.time.seconds(),
+                payload,
+            },
+        )?;
+        if id >= AUDIT_LOG_CAPACITY {
+            AUDIT_LOG.remove(self.storage, id - AUDIT_LOG_CAPACITY);
+        }
+        AUDIT_LOG_NEXT.save(self.storage, &(id + 1))?;
+        Ok(())
+    }
+
+    pub fn watch_account(&mut self, address: &str) -> Result<(), ContractError> {
+        if WATCHED.has(self.storage, address) {
+            return Ok(());
+        }
+        if self.read().watched_balances()?.len() as u32 >= MAX_WATCHED_ACCOUNTS {
+            return Err(ContractError::TooManyWatchedAccounts {
+                max: MAX_WATCHED_ACCOUNTS,
+            });
+        }
+        WATCHED.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    /// Stops watching `address` and drops its recorded activity.
+    pub fn unwatch_account(&mut self, address: &str) -> Result<(), ContractError> {
+        WATCHED.remove(self.storage, address);
+        let ids = ACTIVITY
+            .prefix(address)
+            .keys(self.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for id in ids {
+            ACTIVITY.remove(self.storage, (address, id));
+        }
+        ACTIVITY_NEXT.remove(self.storage, address);
+        Ok(())
+    }
+
+    /// Records `action` for every watched account whose balance differs
+    /// from the one in `before`, dropping the oldest entry of an account
+    /// once it holds `ACTIVITY_CAPACITY`.
+    pub fn record_activity(
+        &mut self,
+        before: &[(String, Uint128)],
+        action: &str,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        for (address, previous) in before {
+            let balance = self.read().balance(address)?;
+            if balance == *previous {
+                continue;
+            }
+            let (delta, credit) = if balance > *previous {
+                (balance - *previous, true)
+            } else {
+                (*previous - balance, false)
+            };
+            let id = ACTIVITY_NEXT
+                .may_load(self.storage, address)?
+                .unwrap_or_default();
+            ACTIVITY.save(
+                self.storage,
+                (address, id),
+                &ActivityEntry {
+                    height,
+                    action: action.to_string(),
+                    delta,
+                    credit,
+                    balance,
+                },
+            )?;
+            if id >= ACTIVITY_CAPACITY {
+                ACTIVITY.remove(self.storage, (address, id - ACTIVITY_CAPACITY));
+            }
+            ACTIVITY_NEXT.save(self.storage, address, &(id + 1))?;
+        }
+        Ok(())
+    }
+
+    pub fn update_transfer_limits(&mut self, limits: TransferLimits) -> Result<(), ContractError> {
+        TRANSFER_LIMITS.save(self.storage, &limits)?;
+        Ok(())
+    }
+
+    /// Sets the per-account transfer rate limit, or removes it when `None`.
+    pub fn update_rate_limit(
+        &mut self,
+        rate_limit: Option<RateLimit>,
+    ) -> Result<(), ContractError> {
+        match rate_limit {
+            Some(rate_limit) if rate_limit.window_seconds == 0 => {
+                return Err(ContractError::InvalidRateLimit {})
+            }
+            Some(rate_limit) => RATE_LIMIT.save(self.storage, &rate_limit)?,
+            None => RATE_LIMIT.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    pub fn add_limit_exemption(&mut self, address: &str) -> Result<(), ContractError> {
+        LIMIT_EXEMPT.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    pub fn remove_limit_exemption(&mut self, address: &str) -> Result<(), ContractError> {
+        LIMIT_EXEMPT.remove(self.storage, address);
+        Ok(())
+    }
+
+    /// Sets the KYC registry contract, or stops consulting one when `None`.
+    pub fn update_kyc_registry(&mut self, registry: Option<String>) -> Result<(), ContractError> {
+        match registry {
+            Some(registry) => KYC_REGISTRY.save(self.storage, &registry)?,
+            None => KYC_REGISTRY.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    pub fn set_permissioned(&mut self, permissioned: bool) -> Result<(), ContractError> {
+        PERMISSIONED.save(self.storage, &permissioned)?;
+        Ok(())
+    }
+
+    pub fn add_to_allowlist(&mut self, address: &str) -> Result<(), ContractError> {
+        ALLOWLIST.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    pub fn remove_from_allowlist(&mut self, address: &str) -> Result<(), ContractError> {
+        ALLOWLIST.remove(self.storage, address);
+        Ok(())
+    }
+
+    pub fn blacklist(&mut self, address: &str) -> Result<(), ContractError> {
+        BLACKLIST.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    pub fn unblacklist(&mut self, address: &str) -> Result<(), ContractError> {
+        BLACKLIST.remove(self.storage, address);
+        Ok(())
+    }
+
+    pub fn unfreeze(&mut self, address: &str) -> Result<(), ContractError> {
+        self.set_freeze(address, None)?;
+        FREEZE_RECORDS.remove(self.storage, address);
+        Ok(())
+    }
+
+    /// Lifts the freezes placed with `reason` among up to `limit` frozen
+    /// accounts after `start_after`. Returns the accounts unfrozen and the
+    /// last one looked at, to continue from.
+    pub fn unfreeze_by_reason(
+        &mut self,
+        reason: &str,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<(Vec<String>, Option<String>), ContractError> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let records = FREEZE_RECORDS
+            .range(
+                self.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        let last = records.last().map(|(address, _)| address.clone());
+        let mut unfrozen = vec![];
+        for (address, record) in records {
+            if record.reason.as_deref() == Some(reason) {
+                self.unfreeze(&address)?;
+                unfrozen.push(address);
+            }
+        }
+        Ok((unfrozen, last))
+    }
+
+    pub fn set_mint_allowlist_enabled(&mut self, enabled: bool) -> Result<(), ContractError> {
+        MINT_ALLOWLIST_ENABLED.save(self.storage, &enabled)?;
+        Ok(())
+    }
+
+    pub fn add_to_mint_allowlist(&mut self, address: &str) -> Result<(), ContractError> {
+        MINT_ALLOWLIST.save(self.storage, address, &true)?;
+        Ok(())
+    }
+
+    pub fn remove_from_mint_allowlist(&mut self, address: &str) -> Result<(), ContractError> {
+        MINT_ALLOWLIST.remove(self.storage, address);
+        Ok(())
+    }
+
+    pub fn update_max_receive_per_tx(&mut self, amount: Uint128) -> Result<(), ContractError> {
+        MAX_RECEIVE_PER_TX.save(self.storage, &amount)?;
+        Ok(())
+    }
+
+    /// Sets the holder cap, or lifts it when `None`.
+    pub fn update_holder_cap(&mut self, cap: Option<HolderCap>) -> Result<(), ContractError> {
+        match cap {
+            Some(cap) => HOLDER_CAP.save(self.storage, &cap)?,
+            None => HOLDER_CAP.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    /// Credits legacy balances and bumps the total supply. Can only run once,
+    /// and the imported total is still bound by the minter cap.
+    pub fn import_legacy_balances(
+        &mut self,
+        entries: &[(String, Uint128)],
+        height: u64,
+    ) -> Result<Uint128, ContractError> {
+        if self.read().paused()? {
+            return Err(ContractError::Paused {});
+        }
+        if IMPORT_DONE.may_load(self.storage)?.unwrap_or_default() {
+            return Err(ContractError::AlreadyImported {});
+        }
+        self.ensure_minting_allowed()?;
+        let mut imported = Uint128::zero();
+        for (_, amount) in entries {
+            imported = add_balance(imported, *amount)?;
+        }
+        let supply = self.read().total_supply()?;
+        let cap = self.read().cap()?;
+        if cap.map_or(false, |cap| {
+            !math::within_cap(supply.u128(), imported.u128(), cap.u128())
+        }) {
+            return Err(ContractError::CapExceeded {});
+        }
+        for (address, amount) in entries {
+            let balance = add_balance(self.read().balance(address)?, *amount)?;
+            self.set_balance(address, balance, height)?;
+        }
+        let supply = add_balance(supply, imported)?;
+        TOTAL_SUPPLY.save(self.storage, &supply, height)?;
+        IMPORT_DONE.save(self.storage, &true)?;
+        Ok(imported)
+    }
+
+    pub fn update_default_mint_recipient(
+        &mut self,
+        address: Option<String>,
+    ) -> Result<(), ContractError> {
+        match address {
+            Some(address) => DEFAULT_MINT_RECIPIENT.save(self.storage, &address)?,
+            None => DEFAULT_MINT_RECIPIENT.remove(self.storage),
+        }
+        Ok(())
+    }
+
+    pub fn set_soulbound(&mut self, enabled: bool) -> Result<(), ContractError> {
+        SOULBOUND.save(self.storage, &enabled)?;
+        Ok(())
+    }
+
+    pub fn set_ignore_unknown_messages(&mut self, enabled: bool) -> Result<(), ContractError> {
+        IGNORE_UNKNOWN_MESSAGES.save(self.storage, &enabled)?;
+        Ok(())
+    }
+
+    pub fn mint_locked(
+        &mut self,
+        recipient: &str,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        self.mint(recipient, amount, height)?;
+        LOCKED_BALANCES.update(
+            self.storage,
+            recipient,
+            |locked| -> Result<_, ContractError> {
+                add_balance(locked.unwrap_or_default(), amount)
+            },
+        )?;
+        Ok(())
+    }
+
+    pub fn update_whale_threshold(&mut self, amount: Uint128) -> Result<(), ContractError> {
+        WHALE_THRESHOLD.save(self.storage, &amount)?;
+        Ok(())
+    }
+
+    pub fn update_freeze_grace_seconds(&mut self, seconds: u64) -> Result<(), ContractError> {
+        FREEZE_GRACE_SECONDS.save(self.storage, &seconds)?;
+        Ok(())
+    }
+
+    pub fn add_hook(&mut self, hook: &str, tolerate_failure: bool) -> Result<(), ContractError> {
+        let mut hooks = self.read().hooks()?;
+        if hooks.iter().any(|existing| existing.address == hook) {
+            return Err(ContractError::HookAlreadyRegistered {});
+        }
+        hooks.push(Hook {
+            address: hook.to_string(),
+            tolerate_failure,
+        });
+        HOOKS.save(self.storage, &hooks)?;
+        Ok(())
+    }
+
+    pub fn remove_hook(&mut self, hook: &str) -> Result<(), ContractError> {
+        let mut hooks = self.read().hooks()?;
+        let position = hooks
+            .iter()
+            .position(|existing| existing.address == hook)
+            .ok_or(ContractError::HookNotRegistered {})?;
+        hooks.remove(position);
+        HOOKS.save(self.storage, &hooks)?;
+        Ok(())
+    }
+
+    pub fn set_receive_failure_tolerance(
+        &mut self,
+        contract: &str,
+        tolerate: bool,
+    ) -> Result<(), ContractError> {
+        if tolerate {
+            TOLERANT_RECEIVERS.save(self.storage, contract, &true)?;
+        } else {
+            TOLERANT_RECEIVERS.remove(self.storage, contract);
+        }
+        Ok(())
+    }
+
+    /// Stores `kind` for the reply to a submessage dispatched at `height` and
+    /// returns its reply id. Contexts left from earlier blocks belong to
+    /// `reply_on_error` submessages that succeeded, and so got no reply, and
+    /// are dropped.
+    pub fn push_reply_context(
+        &mut self,
+        kind: ReplyKind,
+        height: u64,
+    ) -> Result<u64, ContractError> {
+        let stale = REPLY_CONTEXTS
+            .range(self.storage, None, None, Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((id, context)) if context.height < height => Some(Ok(id)),
+                Ok(_) => None,
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        for id in stale {
+            REPLY_CONTEXTS.remove(self.storage, id);
+        }
+        let id = REPLY_SEQ.may_load(self.storage)?.unwrap_or_default() + 1;
+        REPLY_SEQ.save(self.storage, &id)?;
+        REPLY_CONTEXTS.save(self.storage, id, &ReplyContext { height, kind })?;
+        Ok(id)
+    }
+
+    pub fn take_reply_context(&mut self, id: u64) -> Result<ReplyContext, ContractError> {
+        let context = REPLY_CONTEXTS.load(self.storage, id)?;
+        REPLY_CONTEXTS.remove(self.storage, id);
+        Ok(context)
+    }
+
+    /// Takes `amount` back from `contract` after its `Receive` failed, to
+    /// `refund_to`, or out of the supply when the tokens were minted. Fees
+    /// and burns charged on the way in are not refunded.
+    pub fn return_failed_receive(
+        &mut self,
+        contract: &str,
+        refund_to: Option<&str>,
+        amount: Uint128,
+        height: u64,
+    ) -> Result<(), ContractError> {
+        let balance = sub_balance(self.read().balance(contract)?, amount)?;
+        self.set_balance(contract, balance, height)?;
+        match refund_to {
+            Some(owner) => {
+                let balance = add_balance(self.read().balance(owner)?, amount)?;
+                self.set_balance(owner, balance, height)
+            }
+            None => {
+                let supply = sub_balance(self.read().total_supply()?, amount)?;
+                TOTAL_SUPPLY.save(self.storage, &supply, height)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Consumes `nonce` of `address`, which must be the next one.
+    pub fn use_nonce(&mut self, address: &str, nonce: u64) -> Result<(), ContractError> {
+        let expected = self.read().nonce(address)?;
+        if nonce != expected {
+            return Err(ContractError::InvalidNonce { expected });
+        }
+        NONCES.save(self.storage, address, &(expected + 1))?;
+        Ok(())
+    }
+
+    pub fn increase_allowance(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        block: &BlockInfo,
+    ) -> Result<AllowanceResponse, ContractError> {
+        if owner == spender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        // an expired allowance is not topped up, it starts over
+        let mut allowance = self.read().active_allowance(owner, spender, block)?;
+        if let Some(expires) = expires {
+            if expires.is_expired(block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            allowance.expires = expires;
+        }
+        allowance.allowance = add_balance(allowance.allowance, amount)?;
+        self.save_allowance(owner, spender, &allowance)?;
+        Ok(allowance)
+    }
+
+    /// Replaces the allowance of `spender` over the tokens of `owner`. A zero
+    /// amount removes it.
+    pub fn set_allowance(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: Uint128,
+        expires: Expiration,
+        block: &BlockInfo,
+    ) -> Result<AllowanceResponse, ContractError> {
+        if owner == spender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        if expires.is_expired(block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        if amount.is_zero() {
+            self.remove_allowance(owner, spender);
+            return Ok(AllowanceResponse::default());
+        }
+        let allowance = AllowanceResponse {
+            allowance: amount,
+            expires,
+        };
+        self.save_allowance(owner, spender, &allowance)?;
+        Ok(allowance)
+    }
+
+    pub fn decrease_allowance(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        block: &BlockInfo,
+    ) -> Result<AllowanceResponse, ContractError> {
+        if owner == spender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        let mut allowance = self.read().allowance(owner, spender)?;
+        if amount >= allowance.allowance {
+            self.remove_allowance(owner, spender);
+            return Ok(AllowanceResponse::default());
+        }
+        if let Some(expires) = expires {
+            if expires.is_expired(block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            allowance.expires = expires;
+        }
+        allowance.allowance = sub_balance(allowance.allowance, amount)?;
+        self.save_allowance(owner, spender, &allowance)?;
+        Ok(allowance)
+    }
+
+    /// Spends `amount` of the allowance `owner` granted to `spender`, failing
+    /// if it has expired or is too small.
+    pub fn deduct_allowance(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: Uint128,
+        block: &BlockInfo,
+    ) -> Result<AllowanceResponse, ContractError> {
+        if self.read().paused()? {
+            return Err(ContractError::Paused {});
+        }
+        let mut allowance = self.read().allowance(owner, spender)?;
+        if allowance.expires.is_expired(block) {
+            return Err(ContractError::Expired {});
+        }
+        if allowance.allowance < amount {
+            return Err(ContractError::InsufficientAllowance {
+                available: allowance.allowance,
+                required: amount,
+            });
+        }
+        allowance.allowance = sub_balance(allowance.allowance, amount)?;
+        self.save_allowance(owner, spender, &allowance)?;
+        self.record_spend(owner, spender, amount, block.time.seconds())?;
+        Ok(allowance)
+    }
+
+    fn save_allowance(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        allowance: &AllowanceResponse,
+    ) -> StdResult<()> {
+        ALLOWANCES.save(self.storage, (owner, spender), allowance)?;
+        ALLOWANCES_SPENDER.save(self.storage, (spender, owner), allowance)
+    }
+
+    fn remove_allowance(&mut self, owner: &str, spender: &str) {
+        ALLOWANCES.remove(self.storage, (owner, spender));
+        ALLOWANCES_SPENDER.remove(self.storage, (spender, owner));
+    }
+
+    /// Removes the expired allowances granted by `owner`, looking at up to
+    /// `limit` spenders after `start_after`. Returns how many were removed and
+    /// the last spender looked at, to continue from.
+    pub fn prune_expired_allowances(
+        &mut self,
+        owner: &str,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+        block: &BlockInfo,
+    ) -> Result<(u32, Option<String>), ContractError> {
+        let allowances = self.read().all_allowances(owner, start_after, limit)?;
+        let last = allowances.last().map(|info| info.spender.clone());
+        let mut pruned = 0;
+        for info in allowances {
+            if info.expires.is_expired(block) {
+                self.remove_allowance(owner, &info.spender);
+                pruned += 1;
+            }
+        }
+        Ok((pruned, last))
+    }
+
+    /// Removes up to `MAX_LIMIT` of the allowances granted by `owner`.
+    /// Returns how many were removed and whether any are left.
+    pub fn revoke_granted_allowances(&mut self, owner: &str) -> Result<(u32, bool), ContractError> {
+        let mut spenders = ALLOWANCES
+            .prefix(owner)
+            .keys(self.storage, None, None, Order::Ascending)
+            .take(MAX_LIMIT as usize + 1)
+            .collect::<StdResult<Vec<_>>>()?;
+        let more = spenders.len() > MAX_LIMIT as usize;
+        spenders.truncate(MAX_LIMIT as usize);
+        for spender in &spenders {
+            self.remove_allowance(owner, spender);
+        }
+        Ok((spenders.len() as u32, more))
+    }
+
+    /// Removes the allowances granted to `spender` by up to `limit` owners
+    /// after `start_after`. Returns how many were removed and the last owner
+    /// looked at, to continue from.
+    pub fn revoke_spender_all(
+        &mut self,
+        spender: &str,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<(u32, Option<String>), ContractError> {
+        let allowances = self
+            .read()
+            .all_spender_allowances(spender, start_after, limit)?;
+        let last = allowances.last().map(|info| info.owner.clone());
+        for info in &allowances {
+            self.remove_allowance(&info.owner, spender);
+        }
+        Ok((allowances.len() as u32, last))
+    }
+
+    /// Adds up to `limit` balances after `start_after` to the running sum of
+    /// a supply check, starting a new one without `start_after`. A check in
+    /// progress must be resumed after the last address it summed before a
+    /// new one can start. Returns the last address summed, or the sum of
+    /// every balance once none are left.
+    pub fn verify_invariants(
+        &mut self,
+        start_after: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<(Option<String>, Option<Uint128>), ContractError> {
+        let mut sum = match (INVARIANT_CHECK.may_load(self.storage)?, start_after) {
+            (None, None) => Uint128::zero(),
+            (None, Some(_)) => return Err(ContractError::NoInvariantCheck {}),
+            (Some(check), Some(start_after)) if check.last_account == start_after => check.sum,
+            (Some(check), _) => {
+                return Err(ContractError::InvariantCheckInProgress {
+                    last_account: check.last_account,
+                })
+            }
+        };
+        // an empty page would end the check with a partial sum
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT) as usize;
+        let balances = BALANCES
+            .range(
+                self.storage,
+                start_after.map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        for (_, balance) in &balances {
+            sum = sum.checked_add(*balance).map_err(StdError::from)?;
+        }
+        match balances.last() {
+            Some((last_account, _)) if balances.len() == limit => {
+                INVARIANT_CHECK.save(
+                    self.storage,
+                    &InvariantCheck {
+                        sum,
+                        last_account: last_account.clone(),
+                    },
+                )?;
+                Ok((Some(last_account.clone()), None))
+            }
+            _ => {
+                INVARIANT_CHECK.remove(self.storage);
+                Ok((None, Some(sum)))
+            }
+        }
+    }
+
+    /// Caps what `spender` can move from `owner` to `limit` per window,
+    /// whatever its allowance, or lifts the cap when `None`.
+    pub fn set_spend_limit(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        limit: Option<RateLimit>,
+    ) -> Result<(), ContractError> {
+        match limit {
+            Some(limit) => {
+                if limit.window_seconds == 0 {
+                    return Err(ContractError::InvalidRateLimit {});
+                }
+                SPEND_LIMITS.save(self.storage, (owner, spender), &limit)?;
+            }
+            None => {
+                SPEND_LIMITS.remove(self.storage, (owner, spender));
+                SPEND_WINDOWS.remove(self.storage, (owner, spender));
+            }
+        }
+        Ok(())
+    }
+
+    fn record_spend(
+        &mut self,
+        owner: &str,
+        spender: &str,
+        amount: Uint128,
+        block_time: u64,
+    ) -> Result<(), ContractError> {
+        let remaining = match self.read().spend_quota(owner, spender, block_time)? {
+            Some((remaining, _)) => remaining,
+            None => return Ok(()),
+        };
+        if amount > remaining {
+            return Err(ContractError::SpendLimitExceeded { remaining });
+        }
+        let limit = SPEND_LIMITS.load(self.storage, (owner, spender))?;
+        SPEND_WINDOWS.save(
+            self.storage,
+            (owner, spender),
+            &WindowUsage {
+                epoch: block_time / limit.window_seconds,
+                used: limit.limit - remaining + amount,
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Updates the marketing fields that are given. An empty project or
+    /// description clears the field, as does `Some(None)` for the marketing
+    /// admin.
+    pub fn update_marketing(
+        &mut self,
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<Option<Addr>>,
+    ) -> Result<(), ContractError> {
+        let mut info = self.read().marketing_info()?;
+        if let Some(project) = project {
+            info.project = Some(project).filter(|p| !p.is_empty());
+        }
+        if let Some(description) = description {
+            info.description = Some(description).filter(|d| !d.is_empty());
+        }
+        if let Some(marketing) = marketing {
+            info.marketing = marketing;
+        }
+        MARKETING_INFO.save(self.storage, &info)?;
+        Ok(())
+    }
+
+    pub fn upload_logo(&mut self, logo: Logo) -> Result<(), ContractError> {
+        verify_logo(&logo)?;
+        let mut info = self.read().marketing_info()?;
+        info.logo = Some(match &logo {
+            Logo::Url(url) => LogoInfo::Url(url.clone()),
+            Logo::Embedded(_) => LogoInfo::Embedded,
+        });
+        LOGO.save(self.storage, &logo)?;
+        MARKETING_INFO.save(self.storage, &info)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,1267 @@
+//! Tests driving the contract through its entry points, the way a chain
+//! would call it.
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    from_binary, from_slice, Binary, Env, OwnedDeps, Response, StdError, SubMsg, Uint128,
+};
+use cw20::{
+    AllSpenderAllowancesResponse, AllowanceResponse, BalanceResponse, Cw20Coin, Cw20ReceiveMsg,
+    Expiration, MinterResponse, TokenInfoResponse,
+};
+use serde::de::DeserializeOwned;
+
+use crate::contract::{execute, instantiate, query};
+use crate::error::ContractError;
+use crate::msg::{
+    AllFrozenAccountsResponse, AllowanceEntriesResponse, ExecuteMsg, FreezeInfoResponse,
+    FrozenResponse, HandleMsg, HealthCheckResponse, InitMsg, PreviewTransferResponse, QueryMsg,
+    RecentActivityResponse, RolesResponse,
+};
+use crate::state::{
+    AdminAction, FeeConfig, FeeTier, ACTIVITY_CAPACITY, ALLOWANCES, BALANCES, SECONDS_PER_DAY,
+};
+
+type TestDeps = OwnedDeps<MockStorage, MockApi, MockQuerier>;
+
+const ADMIN: &str = "admin";
+const MINTER: &str = "minter";
+
+fn init_msg(balances: &[(&str, u128)]) -> InitMsg {
+    InitMsg {
+        name: "Oasis Token".to_string(),
+        symbol: "OASIS".to_string(),
+        decimals: 6,
+        initial_balances: balances
+            .iter()
+            .map(|(address, amount)| Cw20Coin {
+                address: address.to_string(),
+                amount: Uint128::new(*amount),
+            })
+            .collect(),
+        mint: Some(MinterResponse {
+            minter: MINTER.to_string(),
+            cap: None,
+        }),
+        marketing: None,
+        fee: None,
+        permissioned: false,
+        wrapped_denom: None,
+        timelock_delay: None,
+        inflation: None,
+        mint_deadline: None,
+        exchange_rate: None,
+        bonding_curve: None,
+    }
+}
+
+fn setup_with(msg: InitMsg) -> TestDeps {
+    let mut deps = mock_dependencies();
+    instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN, &[]), msg).unwrap();
+    deps
+}
+
+fn setup_with_fee(balances: &[(&str, u128)], fee_bps: u16, exempt: &[&str]) -> TestDeps {
+    setup_with(InitMsg {
+        fee: Some(FeeConfig {
+            fee_bps,
+            treasury: "treasury".to_string(),
+            exempt: exempt.iter().map(|address| address.to_string()).collect(),
+            tiers: Vec::new(),
+        }),
+        ..init_msg(balances)
+    })
+}
+
+fn setup(balances: &[(&str, u128)]) -> TestDeps {
+    setup_with(init_msg(balances))
+}
+
+fn exec(deps: &mut TestDeps, sender: &str, msg: HandleMsg) -> Result<Response, ContractError> {
+    exec_at(deps, mock_env(), sender, msg)
+}
+
+fn exec_at(
+    deps: &mut TestDeps,
+    env: Env,
+    sender: &str,
+    msg: HandleMsg,
+) -> Result<Response, ContractError> {
+    execute(
+        deps.as_mut(),
+        env,
+        mock_info(sender, &[]),
+        ExecuteMsg::Handle(msg),
+    )
+}
+
+fn query_as<T: DeserializeOwned>(deps: &TestDeps, msg: QueryMsg) -> T {
+    query_at(deps, mock_env(), msg)
+}
+
+fn query_at<T: DeserializeOwned>(deps: &TestDeps, env: Env, msg: QueryMsg) -> T {
+    from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap()
+}
+
+fn attribute(response: &Response, key: &str) -> String {
+    response
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+        .unwrap_or_default()
+}
+
+fn event_attribute(response: &Response, ty: &str, key: &str) -> Option<String> {
+    response
+        .events
+        .iter()
+        .find(|event| event.ty == ty)?
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key == key)
+        .map(|attribute| attribute.value.clone())
+}
+
+fn balance(deps: &TestDeps, address: &str) -> Uint128 {
+    query_as::<BalanceResponse>(
+        deps,
+        QueryMsg::Balance {
+            address: address.to_string(),
+        },
+    )
+    .balance
+}
+
+fn transfer(deps: &mut TestDeps, sender: &str, recipient: &str, amount: u128) {
+    exec(
+        deps,
+        sender,
+        HandleMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+            memo: None,
+        },
+    )
+    .unwrap();
+}
+
+fn allowance(deps: &TestDeps, owner: &str, spender: &str) -> Uint128 {
+    query_as::<AllowanceResponse>(
+        deps,
+        QueryMsg::Allowance {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+        },
+    )
+    .allowance
+}
+
+fn approve(deps: &mut TestDeps, owner: &str, spender: &str, amount: u128) {
+    approve_until(deps, owner, spender, amount, None);
+}
+
+fn approve_until(
+    deps: &mut TestDeps,
+    owner: &str,
+    spender: &str,
+    amount: u128,
+    expires: Option<Expiration>,
+) {
+    exec(
+        deps,
+        owner,
+        HandleMsg::IncreaseAllowance {
+            spender: spender.to_string(),
+            amount: Uint128::new(amount),
+            expires,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn revoke_spender_all_removes_allowances_across_owners() {
+    let mut deps = setup(&[("owner1", 100), ("owner2", 100), ("owner3", 100)]);
+    for owner in ["owner1", "owner2", "owner3"] {
+        approve(&mut deps, owner, "spender", 10);
+    }
+    approve(&mut deps, "owner1", "other", 10);
+
+    let revoke = |start_after: Option<&str>| HandleMsg::RevokeSpenderAll {
+        spender: "spender".to_string(),
+        start_after: start_after.map(str::to_string),
+        limit: Some(2),
+    };
+    assert_eq!(
+        exec(&mut deps, "owner1", revoke(None)).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let first = exec(&mut deps, ADMIN, revoke(None)).unwrap();
+    assert_eq!(attribute(&first, "revoked"), "2");
+    assert_eq!(attribute(&first, "last_owner"), "owner2");
+    let second = exec(&mut deps, ADMIN, revoke(Some("owner2"))).unwrap();
+    assert_eq!(attribute(&second, "revoked"), "1");
+    assert_eq!(attribute(&second, "last_owner"), "owner3");
+
+    let remaining: AllSpenderAllowancesResponse = query_as(
+        &deps,
+        QueryMsg::AllSpenderAllowances {
+            spender: "spender".to_string(),
+            start_after: None,
+            limit: None,
+        },
+    );
+    assert!(remaining.allowances.is_empty());
+    for owner in ["owner1", "owner2", "owner3"] {
+        assert_eq!(allowance(&deps, owner, "spender"), Uint128::zero());
+    }
+    assert_eq!(allowance(&deps, "owner1", "other"), Uint128::new(10));
+}
+
+#[test]
+fn mint_allowlist_restricts_minting_but_not_transfers() {
+    let mut deps = setup(&[("holder", 100)]);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::SetMintAllowlistEnabled { enabled: true },
+    )
+    .unwrap();
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::AddToMintAllowlist {
+            address: "approved".to_string(),
+        },
+    )
+    .unwrap();
+
+    let mint = |recipient: &str| HandleMsg::Mint {
+        recipient: Some(recipient.to_string()),
+        amount: Uint128::new(10),
+    };
+    exec(&mut deps, MINTER, mint("approved")).unwrap();
+    assert_eq!(
+        exec(&mut deps, MINTER, mint("stranger")).unwrap_err(),
+        ContractError::NotOnMintAllowlist {
+            address: "stranger".to_string()
+        }
+    );
+
+    transfer(&mut deps, "holder", "stranger", 40);
+    assert_eq!(balance(&deps, "approved"), Uint128::new(10));
+    assert_eq!(balance(&deps, "stranger"), Uint128::new(40));
+}
+
+#[test]
+fn max_receive_per_tx_spares_the_treasury_and_exempt_addresses() {
+    let mut deps = setup_with_fee(&[("trader", 20_000)], 100, &[]);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::UpdateMaxReceivePerTx {
+            amount: Uint128::new(50),
+        },
+    )
+    .unwrap();
+    let send = |recipient: &str, amount| HandleMsg::Transfer {
+        recipient: recipient.to_string(),
+        amount: Uint128::new(amount),
+        memo: None,
+    };
+    assert_eq!(
+        exec(&mut deps, "trader", send("friend", 100)).unwrap_err(),
+        ContractError::ReceiveLimitExceeded {
+            max: Uint128::new(50)
+        }
+    );
+
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::AddLimitExemption {
+            address: "pool".to_string(),
+        },
+    )
+    .unwrap();
+    exec(&mut deps, "trader", send("pool", 10_000)).unwrap();
+    assert_eq!(balance(&deps, "pool"), Uint128::new(9_900));
+    // the fee is above the limit too
+    assert_eq!(balance(&deps, "treasury"), Uint128::new(100));
+}
+
+#[test]
+fn soulbound_blocks_transfers_but_not_mint_burn_or_revoke() {
+    let mut deps = setup(&[("holder", 100)]);
+    exec(&mut deps, ADMIN, HandleMsg::SetSoulbound { enabled: true }).unwrap();
+
+    let send = HandleMsg::Transfer {
+        recipient: "friend".to_string(),
+        amount: Uint128::new(10),
+        memo: None,
+    };
+    assert_eq!(
+        exec(&mut deps, "holder", send).unwrap_err(),
+        ContractError::NonTransferable {}
+    );
+    exec(
+        &mut deps,
+        MINTER,
+        HandleMsg::Mint {
+            recipient: Some("holder".to_string()),
+            amount: Uint128::new(50),
+        },
+    )
+    .unwrap();
+    exec(
+        &mut deps,
+        "holder",
+        HandleMsg::Burn {
+            amount: Uint128::new(20),
+            memo: None,
+        },
+    )
+    .unwrap();
+
+    let revoke = HandleMsg::Revoke {
+        address: "holder".to_string(),
+        amount: Uint128::new(30),
+    };
+    assert_eq!(
+        exec(&mut deps, "friend", revoke.clone()).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    exec(&mut deps, MINTER, revoke.clone()).unwrap();
+    exec(&mut deps, ADMIN, revoke).unwrap();
+    assert_eq!(balance(&deps, "holder"), Uint128::new(70));
+    assert_eq!(balance(&deps, "friend"), Uint128::zero());
+    let info: TokenInfoResponse = query_as(&deps, QueryMsg::TokenInfo {});
+    assert_eq!(info.total_supply, Uint128::new(70));
+}
+
+#[test]
+fn locked_mints_stay_put_until_released_or_revoked() {
+    let mut deps = setup(&[]);
+    let mint = |locked: bool| {
+        let recipient = "holder".to_string();
+        let amount = Uint128::new(100);
+        if locked {
+            HandleMsg::MintLocked { recipient, amount }
+        } else {
+            HandleMsg::Mint {
+                recipient: Some(recipient),
+                amount,
+            }
+        }
+    };
+    exec(&mut deps, MINTER, mint(true)).unwrap();
+    exec(&mut deps, MINTER, mint(false)).unwrap();
+
+    let send = |amount| HandleMsg::Transfer {
+        recipient: "friend".to_string(),
+        amount: Uint128::new(amount),
+        memo: None,
+    };
+    assert_eq!(
+        exec(&mut deps, "holder", send(101)).unwrap_err(),
+        ContractError::LockedTokens {
+            transferable: Uint128::new(100),
+            required: Uint128::new(101),
+        }
+    );
+    exec(&mut deps, "holder", send(80)).unwrap();
+
+    // revoking uses up the 20 unlocked tokens before the locked ones
+    exec(
+        &mut deps,
+        MINTER,
+        HandleMsg::Revoke {
+            address: "holder".to_string(),
+            amount: Uint128::new(50),
+        },
+    )
+    .unwrap();
+    assert_eq!(balance(&deps, "holder"), Uint128::new(70));
+    assert_eq!(
+        exec(&mut deps, "holder", send(1)).unwrap_err(),
+        ContractError::LockedTokens {
+            transferable: Uint128::zero(),
+            required: Uint128::new(1),
+        }
+    );
+
+    let release = HandleMsg::ReleaseLocked {
+        address: "holder".to_string(),
+        amount: Uint128::new(30),
+    };
+    assert_eq!(
+        exec(&mut deps, MINTER, release.clone()).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    exec(&mut deps, ADMIN, release).unwrap();
+    exec(&mut deps, "holder", send(30)).unwrap();
+    assert_eq!(balance(&deps, "friend"), Uint128::new(110));
+}
+
+fn total_supply(deps: &TestDeps) -> Uint128 {
+    query_as::<TokenInfoResponse>(deps, QueryMsg::TokenInfo {}).total_supply
+}
+
+#[test]
+fn legacy_balances_can_be_imported_once() {
+    let mut deps = setup(&[("holder", 10)]);
+    let import = |entries: &[(&str, u128)]| HandleMsg::ImportLegacyBalances {
+        entries: entries
+            .iter()
+            .map(|(address, amount)| Cw20Coin {
+                address: address.to_string(),
+                amount: Uint128::new(*amount),
+            })
+            .collect(),
+        proof: "snapshot-42".to_string(),
+    };
+    assert_eq!(
+        exec(&mut deps, "holder", import(&[("holder", 1)])).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let response = exec(
+        &mut deps,
+        ADMIN,
+        import(&[("legacy1", 100), ("holder", 50)]),
+    )
+    .unwrap();
+    assert_eq!(attribute(&response, "amount"), "150");
+    assert_eq!(balance(&deps, "legacy1"), Uint128::new(100));
+    assert_eq!(balance(&deps, "holder"), Uint128::new(60));
+    assert_eq!(total_supply(&deps), Uint128::new(160));
+
+    assert_eq!(
+        exec(&mut deps, ADMIN, import(&[("legacy2", 100)])).unwrap_err(),
+        ContractError::AlreadyImported {}
+    );
+    assert_eq!(balance(&deps, "legacy2"), Uint128::zero());
+    assert_eq!(total_supply(&deps), Uint128::new(160));
+}
+
+#[test]
+fn lock_cap_refuses_later_cap_increases() {
+    let mut deps = setup_with(InitMsg {
+        mint: Some(MinterResponse {
+            minter: MINTER.to_string(),
+            cap: Some(Uint128::new(1_000)),
+        }),
+        timelock_delay: Some(0),
+        ..init_msg(&[])
+    });
+    let increase = |new_cap| HandleMsg::ScheduleAdminAction {
+        action: AdminAction::IncreaseCap {
+            new_cap: Uint128::new(new_cap),
+        },
+    };
+    let scheduled = exec(&mut deps, ADMIN, increase(2_000)).unwrap();
+    let id: u64 = attribute(&scheduled, "id").parse().unwrap();
+
+    exec(&mut deps, ADMIN, HandleMsg::LockCap {}).unwrap();
+    assert_eq!(
+        exec(&mut deps, ADMIN, increase(3_000)).unwrap_err(),
+        ContractError::CapLocked {}
+    );
+    // increases queued before the lock cannot go through either
+    assert_eq!(
+        exec(&mut deps, ADMIN, HandleMsg::ExecuteAdminAction { id }).unwrap_err(),
+        ContractError::CapLocked {}
+    );
+    let minter: Option<MinterResponse> = query_as(&deps, QueryMsg::Minter {});
+    assert_eq!(minter.unwrap().cap, Some(Uint128::new(1_000)));
+}
+
+#[test]
+fn mint_without_recipient_goes_to_the_default_recipient() {
+    let mut deps = setup(&[]);
+    let mint = HandleMsg::Mint {
+        recipient: None,
+        amount: Uint128::new(25),
+    };
+    assert_eq!(
+        exec(&mut deps, MINTER, mint.clone()).unwrap_err(),
+        ContractError::NoMintRecipient {}
+    );
+
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::UpdateDefaultMintRecipient {
+            address: Some("reserve".to_string()),
+        },
+    )
+    .unwrap();
+    exec(&mut deps, MINTER, mint).unwrap();
+    assert_eq!(balance(&deps, "reserve"), Uint128::new(25));
+}
+
+#[test]
+fn mint_and_send_mints_and_notifies_the_contract() {
+    let mut deps = setup_with(InitMsg {
+        mint: Some(MinterResponse {
+            minter: MINTER.to_string(),
+            cap: Some(Uint128::new(1_000)),
+        }),
+        ..init_msg(&[("holder", 900)])
+    });
+    let payload = Binary::from(br#"{"deposit":{}}"#.to_vec());
+    let mint_and_send = |amount| HandleMsg::MintAndSend {
+        contract: "vault".to_string(),
+        amount: Uint128::new(amount),
+        msg: payload.clone(),
+    };
+    assert_eq!(
+        exec(&mut deps, "holder", mint_and_send(50)).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+
+    let response = exec(&mut deps, MINTER, mint_and_send(50)).unwrap();
+    let receive = Cw20ReceiveMsg {
+        sender: MINTER.to_string(),
+        amount: Uint128::new(50),
+        msg: payload.clone(),
+    };
+    let notify: SubMsg = SubMsg::new(receive.into_cosmos_msg("vault").unwrap());
+    assert_eq!(response.messages, [notify]);
+    assert_eq!(balance(&deps, "vault"), Uint128::new(50));
+    assert_eq!(total_supply(&deps), Uint128::new(950));
+
+    assert_eq!(
+        exec(&mut deps, MINTER, mint_and_send(51)).unwrap_err(),
+        ContractError::CapExceeded {}
+    );
+    assert_eq!(total_supply(&deps), Uint128::new(950));
+}
+
+#[test]
+fn large_transfers_carry_a_whale_alert() {
+    let mut deps = setup(&[("whale", 10_000)]);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::UpdateWhaleThreshold {
+            amount: Uint128::new(1_000),
+        },
+    )
+    .unwrap();
+    let send = |amount| HandleMsg::Transfer {
+        recipient: "friend".to_string(),
+        amount: Uint128::new(amount),
+        memo: None,
+    };
+    let large = exec(&mut deps, "whale", send(1_000)).unwrap();
+    assert_eq!(attribute(&large, "whale_alert"), "1000");
+    let small = exec(&mut deps, "whale", send(999)).unwrap();
+    assert!(small
+        .attributes
+        .iter()
+        .all(|attr| attr.key != "whale_alert"));
+}
+
+#[test]
+fn self_transfer_is_a_no_op_without_a_fee() {
+    let mut deps = setup_with_fee(&[("trader", 1_000)], 100, &[]);
+    let response = exec(
+        &mut deps,
+        "trader",
+        HandleMsg::Transfer {
+            recipient: "trader".to_string(),
+            amount: Uint128::new(500),
+            memo: None,
+        },
+    )
+    .unwrap();
+    assert!(response.attributes.iter().all(|attr| attr.key != "fee"));
+    assert_eq!(balance(&deps, "trader"), Uint128::new(1_000));
+    assert_eq!(balance(&deps, "treasury"), Uint128::zero());
+}
+
+fn verify_invariants(deps: &mut TestDeps) -> String {
+    let mut start_after = None;
+    loop {
+        let response = exec(
+            deps,
+            "anyone",
+            HandleMsg::VerifyInvariants {
+                start_after,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        if let Some(status) = event_attribute(&response, "supply_invariant", "status") {
+            return status;
+        }
+        start_after = Some(attribute(&response, "last_account"));
+    }
+}
+
+#[test]
+fn verify_invariants_pages_balances_against_supply() {
+    let mut deps = setup(&[("holder1", 100), ("holder2", 200), ("holder3", 300)]);
+    assert_eq!(verify_invariants(&mut deps), "ok");
+
+    BALANCES
+        .save(
+            deps.as_mut().storage,
+            "holder2",
+            &Uint128::new(250),
+            mock_env().block.height,
+        )
+        .unwrap();
+    assert_eq!(verify_invariants(&mut deps), "mismatch");
+}
+
+#[test]
+fn verify_invariants_only_resumes_where_the_check_stopped() {
+    let mut deps = setup(&[("holder1", 100), ("holder2", 200), ("holder3", 300)]);
+    let verify = |start_after: Option<&str>| HandleMsg::VerifyInvariants {
+        start_after: start_after.map(str::to_string),
+        limit: Some(2),
+    };
+    assert_eq!(
+        exec(&mut deps, "anyone", verify(Some("holder2"))).unwrap_err(),
+        ContractError::NoInvariantCheck {}
+    );
+    let first = exec(&mut deps, "anyone", verify(None)).unwrap();
+    assert_eq!(attribute(&first, "last_account"), "holder2");
+
+    let in_progress = ContractError::InvariantCheckInProgress {
+        last_account: "holder2".to_string(),
+    };
+    for start_after in [None, Some("holder1"), Some("holder3")] {
+        let err = exec(&mut deps, "other", verify(start_after)).unwrap_err();
+        assert_eq!(err, in_progress);
+    }
+    let last = exec(&mut deps, "other", verify(Some("holder2"))).unwrap();
+    assert_eq!(
+        event_attribute(&last, "supply_invariant", "status").as_deref(),
+        Some("ok")
+    );
+    assert_eq!(
+        event_attribute(&last, "supply_invariant", "balances").as_deref(),
+        Some("600")
+    );
+}
+
+#[test]
+fn expiring_allowances_lists_only_those_within_the_window() {
+    let mut deps = setup(&[("owner1", 100), ("owner2", 100)]);
+    let now = mock_env().block.time;
+    let at = |seconds| Some(Expiration::AtTime(now.plus_seconds(seconds)));
+    approve_until(&mut deps, "owner1", "soon", 10, at(100));
+    approve_until(&mut deps, "owner1", "later", 10, at(10_000));
+    approve_until(&mut deps, "owner2", "soon", 10, at(1_000));
+    approve_until(
+        &mut deps,
+        "owner2",
+        "height",
+        10,
+        Some(Expiration::AtHeight(mock_env().block.height + 1)),
+    );
+    approve(&mut deps, "owner2", "never", 10);
+
+    let expiring: AllowanceEntriesResponse = query_as(
+        &deps,
+        QueryMsg::ExpiringAllowances {
+            within_seconds: 1_000,
+            start_after: None,
+            limit: None,
+        },
+    );
+    let found: Vec<_> = expiring
+        .allowances
+        .iter()
+        .map(|entry| (entry.owner.as_str(), entry.spender.as_str()))
+        .collect();
+    assert_eq!(found, [("owner1", "soon"), ("owner2", "soon")]);
+    assert_eq!(expiring.last, None);
+}
+
+fn preview(deps: &TestDeps, from: &str, to: &str, amount: u128) -> PreviewTransferResponse {
+    query_as(
+        deps,
+        QueryMsg::PreviewTransfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: Uint128::new(amount),
+        },
+    )
+}
+
+#[test]
+fn preview_transfer_applies_fee_exemptions_per_pair() {
+    let deps = setup_with_fee(&[("trader", 1_000)], 100, &["pool"]);
+
+    let exempt = preview(&deps, "trader", "pool", 500);
+    assert_eq!(exempt.fee, Uint128::zero());
+    assert_eq!(exempt.received, Uint128::new(500));
+    assert_eq!(exempt.to_balance, Uint128::new(500));
+
+    let charged = preview(&deps, "trader", "friend", 500);
+    assert_eq!(charged.fee, Uint128::new(5));
+    assert_eq!(charged.received, Uint128::new(495));
+    assert_eq!(charged.from_balance, Uint128::new(500));
+    assert_eq!(charged.to_balance, Uint128::new(495));
+    assert_eq!(charged.blocked, None);
+
+    let blocked = preview(&deps, "trader", "friend", 5_000);
+    assert_eq!(blocked.received, Uint128::zero());
+    assert_eq!(blocked.from_balance, Uint128::new(1_000));
+    assert!(blocked.blocked.is_some());
+}
+
+fn freeze(deps: &mut TestDeps, address: &str, reason: &str) {
+    exec(
+        deps,
+        MINTER,
+        HandleMsg::Freeze {
+            address: address.to_string(),
+            expires: None,
+            reason: Some(reason.to_string()),
+        },
+    )
+    .unwrap();
+}
+
+fn frozen(deps: &TestDeps, address: &str) -> bool {
+    query_as::<FrozenResponse>(
+        deps,
+        QueryMsg::Frozen {
+            address: address.to_string(),
+        },
+    )
+    .frozen
+}
+
+#[test]
+fn freezing_a_frozen_account_changes_nothing() {
+    let mut deps = setup(&[("account", 100)]);
+    freeze(&mut deps, "account", "kyc");
+    let freeze_info = |deps: &TestDeps| {
+        query_as::<FreezeInfoResponse>(
+            deps,
+            QueryMsg::FreezeInfo {
+                address: "account".to_string(),
+            },
+        )
+    };
+    let all_frozen = |deps: &TestDeps| {
+        query_as::<AllFrozenAccountsResponse>(
+            deps,
+            QueryMsg::AllFrozenAccounts {
+                start_after: None,
+                limit: None,
+            },
+        )
+    };
+    let info = freeze_info(&deps);
+    let accounts = all_frozen(&deps);
+
+    let mut later = mock_env();
+    later.block.height += 10;
+    later.block.time = later.block.time.plus_seconds(60);
+    let response = exec_at(
+        &mut deps,
+        later,
+        MINTER,
+        HandleMsg::Freeze {
+            address: "account".to_string(),
+            expires: None,
+            reason: Some("sanctions".to_string()),
+        },
+    )
+    .unwrap();
+    assert_eq!(attribute(&response, "already_frozen"), "account");
+    assert!(response.events.is_empty());
+    assert_eq!(freeze_info(&deps), info);
+    assert_eq!(all_frozen(&deps), accounts);
+    assert_eq!(accounts.accounts.len(), 1);
+}
+
+#[test]
+fn freezes_take_effect_after_the_grace_period() {
+    let mut deps = setup(&[("holder", 100)]);
+    exec(
+        &mut deps,
+        MINTER,
+        HandleMsg::UpdateFreezeGraceSeconds { seconds: 3_600 },
+    )
+    .unwrap();
+    freeze(&mut deps, "holder", "kyc");
+
+    let at = |seconds| {
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(seconds);
+        env.block.height += seconds / 5;
+        env
+    };
+    let send = || HandleMsg::Transfer {
+        recipient: "friend".to_string(),
+        amount: Uint128::new(10),
+        memo: None,
+    };
+    exec_at(&mut deps, at(3_599), "holder", send()).unwrap();
+    assert_eq!(
+        exec_at(&mut deps, at(3_600), "holder", send()).unwrap_err(),
+        ContractError::AccountFrozen {
+            address: "holder".to_string()
+        }
+    );
+    assert_eq!(balance(&deps, "friend"), Uint128::new(10));
+}
+
+#[test]
+fn unfreeze_by_reason_keeps_other_reasons_frozen() {
+    let mut deps = setup(&[]);
+    freeze(&mut deps, "account1", "sanctions");
+    freeze(&mut deps, "account2", "kyc");
+    freeze(&mut deps, "account3", "sanctions");
+
+    let unfreeze = |start_after: Option<&str>| HandleMsg::UnfreezeByReason {
+        reason: "sanctions".to_string(),
+        start_after: start_after.map(str::to_string),
+        limit: Some(2),
+    };
+    let first = exec(&mut deps, MINTER, unfreeze(None)).unwrap();
+    assert_eq!(attribute(&first, "unfrozen"), "1");
+    assert_eq!(attribute(&first, "last_address"), "account2");
+    let second = exec(&mut deps, MINTER, unfreeze(Some("account2"))).unwrap();
+    assert_eq!(attribute(&second, "unfrozen"), "1");
+
+    assert!(!frozen(&deps, "account1"));
+    assert!(frozen(&deps, "account2"));
+    assert!(!frozen(&deps, "account3"));
+}
+
+#[test]
+fn recent_activity_keeps_the_latest_changes_of_watched_accounts() {
+    let mut deps = setup(&[("alice", 100)]);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::WatchAccount {
+            address: "alice".to_string(),
+        },
+    )
+    .unwrap();
+    for _ in 0..25 {
+        transfer(&mut deps, "alice", "bob", 1);
+    }
+
+    let activity: RecentActivityResponse = query_as(
+        &deps,
+        QueryMsg::RecentActivity {
+            address: "alice".to_string(),
+            limit: Some(100),
+        },
+    );
+    assert_eq!(activity.entries.len() as u64, ACTIVITY_CAPACITY);
+    let newest = &activity.entries[0];
+    assert_eq!(newest.action, "transfer");
+    assert_eq!(newest.delta, Uint128::new(1));
+    assert!(!newest.credit);
+    assert_eq!(newest.balance, Uint128::new(75));
+    // the first five transfers rolled off
+    assert_eq!(activity.entries.last().unwrap().balance, Uint128::new(94));
+
+    let unwatched: RecentActivityResponse = query_as(
+        &deps,
+        QueryMsg::RecentActivity {
+            address: "bob".to_string(),
+            limit: None,
+        },
+    );
+    assert!(unwatched.entries.is_empty());
+}
+
+#[test]
+fn health_check_flags_frozen_amounts_beyond_supply() {
+    let mut deps = setup(&[("holder", 100)]);
+    let healthy = HealthCheckResponse {
+        supply_le_cap: true,
+        reserve_backs_supply: true,
+        frozen_le_supply: true,
+    };
+    assert_eq!(
+        query_as::<HealthCheckResponse>(&deps, QueryMsg::HealthCheck {}),
+        healthy
+    );
+
+    let freeze_amount = |amount| HandleMsg::FreezeAmount {
+        address: "holder".to_string(),
+        amount: Uint128::new(amount),
+        reason: None,
+    };
+    exec(&mut deps, MINTER, freeze_amount(500)).unwrap();
+    let health: HealthCheckResponse = query_as(&deps, QueryMsg::HealthCheck {});
+    assert!(!health.frozen_le_supply);
+    assert!(health.supply_le_cap && health.reserve_backs_supply);
+
+    // replacing the freeze adjusts the total rather than adding to it
+    exec(&mut deps, MINTER, freeze_amount(50)).unwrap();
+    assert_eq!(
+        query_as::<HealthCheckResponse>(&deps, QueryMsg::HealthCheck {}),
+        healthy
+    );
+}
+
+#[test]
+fn secure_account_freezes_and_revokes_granted_allowances() {
+    let mut deps = setup(&[("victim", 100), ("other", 100)]);
+    for index in 0..35 {
+        approve(&mut deps, "victim", &format!("spender{:02}", index), 10);
+    }
+    approve(&mut deps, "other", "spender00", 10);
+
+    let secure = HandleMsg::SecureAccount {
+        address: "victim".to_string(),
+    };
+    assert_eq!(
+        exec(&mut deps, "other", secure.clone()).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    let first = exec(&mut deps, ADMIN, secure.clone()).unwrap();
+    assert_eq!(attribute(&first, "revoked"), "30");
+    assert_eq!(attribute(&first, "more"), "true");
+    assert!(frozen(&deps, "victim"));
+    let second = exec(&mut deps, ADMIN, secure).unwrap();
+    assert_eq!(attribute(&second, "revoked"), "5");
+    assert_eq!(attribute(&second, "more"), "false");
+
+    for index in 0..35 {
+        let spender = format!("spender{:02}", index);
+        assert_eq!(allowance(&deps, "victim", &spender), Uint128::zero());
+    }
+    assert_eq!(allowance(&deps, "other", "spender00"), Uint128::new(10));
+    assert!(!frozen(&deps, "other"));
+}
+
+#[test]
+fn roles_reports_current_and_pending_holders() {
+    let mut deps = setup(&[]);
+    let roles: RolesResponse = query_as(&deps, QueryMsg::Roles {});
+    assert_eq!(
+        roles,
+        RolesResponse {
+            admins: vec![ADMIN.to_string()],
+            minter: Some(MINTER.to_string()),
+            pending_minter: None,
+        }
+    );
+
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::ProposeNewMinter {
+            address: "successor".to_string(),
+        },
+    )
+    .unwrap();
+    let roles: RolesResponse = query_as(&deps, QueryMsg::Roles {});
+    assert_eq!(roles.minter.as_deref(), Some(MINTER));
+    assert_eq!(roles.pending_minter.as_deref(), Some("successor"));
+
+    exec(&mut deps, "successor", HandleMsg::AcceptMinter {}).unwrap();
+    let roles: RolesResponse = query_as(&deps, QueryMsg::Roles {});
+    assert_eq!(roles.minter.as_deref(), Some("successor"));
+    assert_eq!(roles.pending_minter, None);
+}
+
+fn tiered_fee_msg(tiers: &[(u128, u16)]) -> InitMsg {
+    InitMsg {
+        fee: Some(FeeConfig {
+            fee_bps: 100,
+            treasury: "treasury".to_string(),
+            exempt: vec![],
+            tiers: tiers
+                .iter()
+                .map(|(min_amount, fee_bps)| FeeTier {
+                    min_amount: Uint128::new(*min_amount),
+                    fee_bps: *fee_bps,
+                })
+                .collect(),
+        }),
+        ..init_msg(&[("trader", 100_000)])
+    }
+}
+
+#[test]
+fn fee_tiers_charge_the_rate_of_the_highest_tier_reached() {
+    let mut deps = setup_with(tiered_fee_msg(&[(1_000, 50), (10_000, 20)]));
+    let send = |amount| HandleMsg::Transfer {
+        recipient: "friend".to_string(),
+        amount: Uint128::new(amount),
+        memo: None,
+    };
+    for (amount, fee) in [(999, "9"), (1_000, "5"), (9_999, "49"), (20_000, "40")] {
+        let response = exec(&mut deps, "trader", send(amount)).unwrap();
+        assert_eq!(attribute(&response, "fee"), fee);
+    }
+    assert_eq!(balance(&deps, "treasury"), Uint128::new(103));
+
+    let err = instantiate(
+        mock_dependencies().as_mut(),
+        mock_env(),
+        mock_info(ADMIN, &[]),
+        tiered_fee_msg(&[(10_000, 20), (1_000, 50)]),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::InvalidFeeTiers {});
+}
+
+#[test]
+fn claim_treasury_moves_collected_fees() {
+    let mut deps = setup_with_fee(&[("trader", 10_000)], 100, &[]);
+    transfer(&mut deps, "trader", "friend", 5_000);
+    assert_eq!(balance(&deps, "treasury"), Uint128::new(50));
+
+    let claim = |to: &str, amount| HandleMsg::ClaimTreasury {
+        to: to.to_string(),
+        amount: Uint128::new(amount),
+    };
+    assert_eq!(
+        exec(&mut deps, "trader", claim("trader", 10)).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    exec(&mut deps, "treasury", claim("vault", 30)).unwrap();
+    assert_eq!(balance(&deps, "treasury"), Uint128::new(20));
+    assert_eq!(balance(&deps, "vault"), Uint128::new(30));
+
+    assert_eq!(
+        exec(&mut deps, ADMIN, claim("vault", 21)).unwrap_err(),
+        ContractError::InsufficientTreasury {
+            available: Uint128::new(20)
+        }
+    );
+    freeze(&mut deps, "vault", "compromised");
+    assert_eq!(
+        exec(&mut deps, ADMIN, claim("vault", 20)).unwrap_err(),
+        ContractError::AccountFrozen {
+            address: "vault".to_string()
+        }
+    );
+}
+
+#[test]
+fn mint_daily_quota_resets_the_next_day() {
+    let mut deps = setup(&[]);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::SetMintDailyQuota {
+            address: MINTER.to_string(),
+            quota: Some(Uint128::new(100)),
+        },
+    )
+    .unwrap();
+
+    let mint = |amount| HandleMsg::Mint {
+        recipient: Some("holder".to_string()),
+        amount: Uint128::new(amount),
+    };
+    let today = mock_env();
+    exec_at(&mut deps, today.clone(), MINTER, mint(60)).unwrap();
+    let day = today.block.time.seconds() / SECONDS_PER_DAY;
+    assert_eq!(
+        exec_at(&mut deps, today.clone(), MINTER, mint(50)).unwrap_err(),
+        ContractError::MintQuotaExceeded {
+            remaining: Uint128::new(40),
+            resets_at: (day + 1) * SECONDS_PER_DAY,
+        }
+    );
+    exec_at(&mut deps, today.clone(), MINTER, mint(40)).unwrap();
+
+    let mut tomorrow = today;
+    tomorrow.block.time = tomorrow.block.time.plus_seconds(SECONDS_PER_DAY);
+    tomorrow.block.height += 1;
+    exec_at(&mut deps, tomorrow, MINTER, mint(100)).unwrap();
+    assert_eq!(balance(&deps, "holder"), Uint128::new(200));
+}
+
+#[test]
+fn stale_allowances_lists_zero_and_expired_entries() {
+    let mut deps = setup(&[("owner", 100)]);
+    let env = mock_env();
+    approve(&mut deps, "owner", "live", 10);
+    approve_until(
+        &mut deps,
+        "owner",
+        "lapsing",
+        10,
+        Some(Expiration::AtHeight(env.block.height + 1)),
+    );
+    ALLOWANCES
+        .save(
+            deps.as_mut().storage,
+            ("owner", "zero"),
+            &AllowanceResponse {
+                allowance: Uint128::zero(),
+                expires: Expiration::Never {},
+            },
+        )
+        .unwrap();
+
+    let mut later = env;
+    later.block.height += 1;
+    let stale: AllowanceEntriesResponse = query_at(
+        &deps,
+        later,
+        QueryMsg::StaleAllowances {
+            start_after: None,
+            limit: None,
+        },
+    );
+    let spenders: Vec<_> = stale
+        .allowances
+        .iter()
+        .map(|entry| entry.spender.as_str())
+        .collect();
+    assert_eq!(spenders, ["lapsing", "zero"]);
+}
+
+fn cap_holders(deps: &mut TestDeps, max_holders: u64, minter_bypasses_holder_cap: bool) {
+    exec(
+        deps,
+        ADMIN,
+        HandleMsg::UpdateHolderCap {
+            max_holders: Some(max_holders),
+            minter_bypasses_holder_cap,
+        },
+    )
+    .unwrap();
+}
+
+#[test]
+fn holder_cap_binds_mints_unless_the_minter_bypasses_it() {
+    let mut deps = setup(&[("holder1", 100), ("holder2", 100)]);
+    let mint = |recipient: &str| HandleMsg::Mint {
+        recipient: Some(recipient.to_string()),
+        amount: Uint128::new(10),
+    };
+    let full = ContractError::HolderCapReached { max: 2 };
+
+    cap_holders(&mut deps, 2, false);
+    assert_eq!(exec(&mut deps, MINTER, mint("newcomer")).unwrap_err(), full);
+    exec(&mut deps, MINTER, mint("holder1")).unwrap();
+
+    cap_holders(&mut deps, 2, true);
+    exec(&mut deps, MINTER, mint("newcomer")).unwrap();
+    assert_eq!(balance(&deps, "newcomer"), Uint128::new(10));
+
+    // transfers stay bound by the cap either way
+    let to_newcomer = HandleMsg::Transfer {
+        recipient: "another".to_string(),
+        amount: Uint128::new(10),
+        memo: None,
+    };
+    assert_eq!(exec(&mut deps, "holder1", to_newcomer).unwrap_err(), full);
+    transfer(&mut deps, "holder1", "holder2", 10);
+    assert_eq!(balance(&deps, "holder2"), Uint128::new(110));
+}
+
+#[test]
+fn rotate_treasury_moves_the_balance_and_redirects_fees() {
+    let mut deps = setup_with_fee(&[("trader", 10_000)], 100, &[]);
+    transfer(&mut deps, "trader", "friend", 5_000);
+    assert_eq!(balance(&deps, "treasury"), Uint128::new(50));
+
+    let rotate = |new_treasury: &str| HandleMsg::RotateTreasury {
+        new_treasury: new_treasury.to_string(),
+    };
+    freeze(&mut deps, "frozen", "compromised");
+    assert_eq!(
+        exec(&mut deps, ADMIN, rotate("frozen")).unwrap_err(),
+        ContractError::AccountFrozen {
+            address: "frozen".to_string()
+        }
+    );
+    let response = exec(&mut deps, ADMIN, rotate("vault")).unwrap();
+    assert_eq!(attribute(&response, "moved"), "50");
+    assert_eq!(balance(&deps, "treasury"), Uint128::zero());
+    assert_eq!(balance(&deps, "vault"), Uint128::new(50));
+
+    transfer(&mut deps, "trader", "friend", 1_000);
+    assert_eq!(balance(&deps, "treasury"), Uint128::zero());
+    assert_eq!(balance(&deps, "vault"), Uint128::new(60));
+    let config: Option<FeeConfig> = query_as(&deps, QueryMsg::FeeConfig {});
+    assert_eq!(config.unwrap().treasury, "vault");
+}
+
+#[test]
+fn rotate_treasury_moves_the_balance_of_a_frozen_treasury() {
+    let mut deps = setup_with_fee(&[("trader", 10_000)], 100, &[]);
+    transfer(&mut deps, "trader", "friend", 5_000);
+    exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::UpdateMaxReceivePerTx {
+            amount: Uint128::new(10),
+        },
+    )
+    .unwrap();
+    freeze(&mut deps, "treasury", "compromised key");
+
+    let response = exec(
+        &mut deps,
+        ADMIN,
+        HandleMsg::RotateTreasury {
+            new_treasury: "vault".to_string(),
+        },
+    )
+    .unwrap();
+    assert_eq!(attribute(&response, "moved"), "50");
+    assert_eq!(balance(&deps, "treasury"), Uint128::zero());
+    assert_eq!(balance(&deps, "vault"), Uint128::new(50));
+}
+
+/// Deserializes `msg` the way the wasm entry point does and executes it.
+fn exec_raw(deps: &mut TestDeps, sender: &str, msg: &[u8]) -> Result<Response, ContractError> {
+    let msg: ExecuteMsg = from_slice(msg)?;
+    execute(deps.as_mut(), mock_env(), mock_info(sender, &[]), msg)
+}
+
+#[test]
+fn malformed_messages_are_rejected_unless_ignored() {
+    let mut deps = setup(&[("alice", 1_000)]);
+    let inputs: [&[u8]; 4] = [
+        b"{\"self_destruct\": {}}",
+        b"{\"transfer\": {\"recipient\": \"bob\", \"amount\": \"lots\"}}",
+        b"{\"transfer\": {\"recipient\": \"bob\"}}",
+        b"[1, 2, 3]",
+    ];
+    for input in inputs {
+        let err = exec_raw(&mut deps, "alice", input).unwrap_err();
+        assert_eq!(err, ContractError::UnknownMessage {});
+    }
+    // Input that is not JSON at all never reaches the contract.
+    let truncated = exec_raw(&mut deps, "alice", b"{\"transfer\": {\"recipient\": ");
+    assert!(matches!(
+        truncated.unwrap_err(),
+        ContractError::Std(StdError::ParseErr { .. })
+    ));
+
+    let transfer = br#"{"transfer": {"recipient": "bob", "amount": "100"}}"#;
+    exec_raw(&mut deps, "alice", transfer).unwrap();
+    assert_eq!(balance(&deps, "bob"), Uint128::new(100));
+
+    let ignore = HandleMsg::SetIgnoreUnknownMessages { enabled: true };
+    assert_eq!(
+        exec(&mut deps, "alice", ignore.clone()).unwrap_err(),
+        ContractError::Unauthorized {}
+    );
+    exec(&mut deps, ADMIN, ignore).unwrap();
+    for input in inputs {
+        let response = exec_raw(&mut deps, "alice", input).unwrap();
+        assert_eq!(attribute(&response, "action"), "ignore_unknown_message");
+    }
+    assert_eq!(balance(&deps, "alice"), Uint128::new(900));
+}